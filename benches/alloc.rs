@@ -0,0 +1,47 @@
+// Counts heap allocations made by the classic `dpll` and by `solve` on the
+// same random 50 variable 3-SAT instance.
+//
+//     cargo bench --bench alloc
+
+use sat_solver::{Assignment, dpll, random_3sat, solve};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+struct Counting;
+
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+fn count<T>(run: impl FnOnce() -> T) -> (T, usize) {
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let result = run();
+    (result, ALLOCATIONS.load(Ordering::Relaxed) - before)
+}
+
+fn main() {
+    // clause/variable ratio near the 4.26 phase transition
+    let cnf = random_3sat(50, 213, 0x5eed);
+
+    let (classic, classic_allocs) = count(|| dpll(cnf.clone(), &mut Assignment::new()));
+    let (indexed, indexed_allocs) = count(|| solve(cnf.clone()));
+    assert_eq!(
+        matches!(classic, sat_solver::SatResult::Sat(_)),
+        matches!(indexed, sat_solver::SatResult::Sat(_))
+    );
+
+    println!("dpll:  {classic_allocs} allocations");
+    println!("solve: {indexed_allocs} allocations");
+}
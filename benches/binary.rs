@@ -0,0 +1,54 @@
+// Times `solve` on a formula of mostly binary clauses: a random 3-SAT
+// instance near the phase transition where every variable is split into
+// copies made equivalent by a cycle of implications, so each assignment
+// propagates through a chain of binary clauses.
+//
+//     cargo bench --bench binary
+
+use sat_solver::{CNF, Literal, random_3sat, solve_with_stats};
+use std::time::Instant;
+
+// copies of each variable of the 3-SAT instance
+const COPIES: usize = 20;
+
+fn copy(name: &str, k: usize, negated: bool) -> Literal {
+    Literal {
+        name: format!("{name}_{k}"),
+        negated,
+    }
+}
+
+fn main() {
+    let vars = 200;
+    let base = random_3sat(vars, 852, 0x5eed);
+    let mut cnf: CNF = Vec::new();
+    for var in 1..=vars {
+        let name = var.to_string();
+        for k in 0..COPIES {
+            cnf.push(vec![
+                copy(&name, k, true),
+                copy(&name, (k + 1) % COPIES, false),
+            ]);
+        }
+    }
+    // each occurrence uses a different copy
+    for (i, clause) in base.iter().enumerate() {
+        cnf.push(
+            clause
+                .iter()
+                .map(|literal| copy(&literal.name, i % COPIES, literal.negated))
+                .collect(),
+        );
+    }
+
+    let start = Instant::now();
+    let (model, stats) = solve_with_stats(cnf);
+    let elapsed = start.elapsed();
+    println!(
+        "{}: {} conflicts, {} propagations in {elapsed:?}, {:.1} per microsecond",
+        if model.is_some() { "SAT" } else { "UNSAT" },
+        stats.conflicts,
+        stats.propagations,
+        stats.propagations as f64 / elapsed.as_micros().max(1) as f64
+    );
+}
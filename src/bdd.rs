@@ -0,0 +1,242 @@
+use crate::{Assignment, CNF, Clause, Map, VarMap};
+use alloc::{vec, vec::Vec};
+
+/// The most variables `solve_bdd` and `count_bdd` accept. A BDD can need
+/// exponentially many nodes in the number of variables, so larger
+/// formulas are left to the search based solvers.
+pub const BDD_MAX_VARS: usize = 32;
+
+// the two terminals, every other node tests a variable
+const FALSE: usize = 0;
+const TRUE: usize = 1;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    // the terminals test `vars`, one past the last variable
+    var: usize,
+    low: usize,
+    high: usize,
+}
+
+// a reduced ordered BDD over variables in order of first occurrence, no
+// two nodes are equal and none has equal children
+struct Bdd {
+    nodes: Vec<Node>,
+    unique: Map<(usize, usize, usize), usize>,
+    conjunctions: Map<(usize, usize), usize>,
+}
+
+impl Bdd {
+    fn new(vars: usize) -> Self {
+        let terminal = Node {
+            var: vars,
+            low: FALSE,
+            high: FALSE,
+        };
+        Bdd {
+            nodes: vec![terminal, terminal],
+            unique: Map::new(),
+            conjunctions: Map::new(),
+        }
+    }
+
+    fn node(&mut self, var: usize, low: usize, high: usize) -> usize {
+        if low == high {
+            return low;
+        }
+        if let Some(&node) = self.unique.get(&(var, low, high)) {
+            return node;
+        }
+        self.nodes.push(Node { var, low, high });
+        self.unique.insert((var, low, high), self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    // `lits` are variable ids and signs, without repeats
+    fn clause(&mut self, mut lits: Vec<(usize, bool)>) -> usize {
+        // built bottom up, so the last variable first
+        lits.sort_unstable_by(|a, b| b.cmp(a));
+        if lits.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+            return TRUE;
+        }
+        lits.into_iter().fold(FALSE, |rest, (var, negated)| {
+            if negated {
+                self.node(var, TRUE, rest)
+            } else {
+                self.node(var, rest, TRUE)
+            }
+        })
+    }
+
+    fn and(&mut self, a: usize, b: usize) -> usize {
+        if a == FALSE || b == FALSE {
+            return FALSE;
+        }
+        if a == TRUE || a == b {
+            return b;
+        }
+        if b == TRUE {
+            return a;
+        }
+        let key = (a.min(b), a.max(b));
+        if let Some(&node) = self.conjunctions.get(&key) {
+            return node;
+        }
+
+        let (na, nb) = (self.nodes[a], self.nodes[b]);
+        let var = na.var.min(nb.var);
+        let cofactors = |node: Node, other| {
+            if node.var == var {
+                (node.low, node.high)
+            } else {
+                (other, other)
+            }
+        };
+        let (a_low, a_high) = cofactors(na, a);
+        let (b_low, b_high) = cofactors(nb, b);
+        let low = self.and(a_low, b_low);
+        let high = self.and(a_high, b_high);
+        let node = self.node(var, low, high);
+        self.conjunctions.insert(key, node);
+        node
+    }
+
+    // models of `node` over its variable and every later one
+    fn count(&self, node: usize, counts: &mut Map<usize, u64>) -> u64 {
+        match node {
+            FALSE => return 0,
+            TRUE => return 1,
+            _ => {}
+        }
+        if let Some(&count) = counts.get(&node) {
+            return count;
+        }
+        let Node { var, low, high } = self.nodes[node];
+        // variables skipped on the way to a child are free
+        let skipped = |child: usize| 1u64 << (self.nodes[child].var - var - 1);
+        let count =
+            self.count(low, counts) * skipped(low) + self.count(high, counts) * skipped(high);
+        counts.insert(node, count);
+        count
+    }
+}
+
+// the BDD of `cnf` and the variables it is ordered by
+fn build(cnf: &CNF) -> (Bdd, usize, VarMap) {
+    let vars = VarMap::from_cnf(cnf);
+    assert!(
+        vars.len() <= BDD_MAX_VARS,
+        "{} variables are more than the BDD limit of {BDD_MAX_VARS}",
+        vars.len()
+    );
+    let mut bdd = Bdd::new(vars.len());
+    let mut root = TRUE;
+    for clause in cnf {
+        let lits = lits(&vars, clause);
+        let clause = bdd.clause(lits);
+        root = bdd.and(root, clause);
+    }
+    (bdd, root, vars)
+}
+
+fn lits(vars: &VarMap, clause: &Clause) -> Vec<(usize, bool)> {
+    let mut lits: Vec<(usize, bool)> = clause
+        .iter()
+        .map(|literal| {
+            let var = vars.id(&literal.name).expect("interned from cnf");
+            (var as usize, literal.negated)
+        })
+        .collect();
+    lits.sort_unstable();
+    lits.dedup();
+    lits
+}
+
+/// Solves `cnf` by building its reduced ordered BDD, with the variables in
+/// order of first occurrence, instead of searching. The model sets every
+/// variable of `cnf`, those the formula does not depend on false. `None`
+/// means the formula is unsatisfiable.
+///
+/// Panics if `cnf` has more than `BDD_MAX_VARS` variables.
+pub fn solve_bdd(cnf: &CNF) -> Option<Assignment> {
+    let (bdd, mut node, vars) = build(cnf);
+    if node == FALSE {
+        return None;
+    }
+
+    let mut model: Assignment = vars.names().map(|name| (name.into(), false)).collect();
+    while node != TRUE {
+        let Node { var, low, high } = bdd.nodes[node];
+        let value = low == FALSE;
+        let name = vars.name(var as u32).expect("interned from cnf");
+        model.insert(name.into(), value);
+        node = if value { high } else { low };
+    }
+    Some(model)
+}
+
+/// Counts the models of `cnf` over its variables from its BDD, like
+/// `model_count` but without a search. Built the same way as by
+/// `solve_bdd`.
+///
+/// Panics if `cnf` has more than `BDD_MAX_VARS` variables.
+pub fn count_bdd(cnf: &CNF) -> u64 {
+    let (bdd, root, _) = build(cnf);
+    // the variables before the root are free
+    bdd.count(root, &mut Map::new()) << bdd.nodes[root].var
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Literal, SatResult, dpll, model_count, parse2, verify};
+    use alloc::format;
+
+    #[test]
+    fn test_bdd_agrees_with_dpll() {
+        // formulas 1 to 8 of the classic DPLL tests
+        for formula in [
+            "{a}",
+            "{a},{b}",
+            "{a,b}",
+            "{-a,b}",
+            "{a},{-a}",
+            "{a,b},{-a,c},{-b,-c}",
+            "{a,b},{b,c},{-a,-b},{-c,d}",
+            "{a,b},{-a,b},{a,-b},{-a,-b}",
+        ] {
+            let cnf = parse2(formula);
+            let model = solve_bdd(&cnf);
+            match dpll(cnf.clone(), &mut Assignment::new()) {
+                SatResult::Sat(_) => assert!(verify(&cnf, &model.unwrap()), "{formula}"),
+                SatResult::Unsat => assert_eq!(model, None, "{formula}"),
+            }
+            assert_eq!(count_bdd(&cnf), model_count(cnf), "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_count_bdd() {
+        // `c` only occurs in a tautology
+        assert_eq!(count_bdd(&parse2("{a,b},{c,-c}")), 6);
+        assert_eq!(count_bdd(&parse2("{a,-a}")), 2);
+        assert_eq!(count_bdd(&vec![vec![]]), 0);
+        assert_eq!(count_bdd(&CNF::new()), 1);
+        for seed in 0..20 {
+            let cnf = crate::random_3sat(12, 40, seed);
+            assert_eq!(count_bdd(&cnf), model_count(cnf), "seed {seed}");
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "more than the BDD limit")]
+    fn test_bdd_variable_limit() {
+        let clause = (0..=BDD_MAX_VARS)
+            .map(|i| Literal {
+                name: format!("x{i}"),
+                negated: false,
+            })
+            .collect();
+        count_bdd(&vec![clause]);
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{Assignment, CNF, Clause, Literal};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ops::Range;
+
+/// Assembles a formula clause by clause without going through a parser.
+///
+/// ```
+/// use sat_solver::{CnfBuilder, parse2};
+///
+/// let cnf = CnfBuilder::new()
+///     .clause().pos("a").pos("b")
+///     .clause().neg("a")
+///     .build();
+/// assert_eq!(cnf, parse2("{a,b},{-a}"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CnfBuilder {
+    cnf: CNF,
+}
+
+impl CnfBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Starts a new clause, empty until literals are added to it.
+    pub fn clause(self) -> ClauseBuilder {
+        ClauseBuilder {
+            builder: self,
+            clause: Clause::new(),
+        }
+    }
+
+    pub fn build(self) -> CNF {
+        self.cnf
+    }
+}
+
+/// The clause a `CnfBuilder` is currently adding literals to. It becomes
+/// part of the formula when the next clause is started or the formula is
+/// built.
+#[derive(Debug, Clone)]
+pub struct ClauseBuilder {
+    builder: CnfBuilder,
+    clause: Clause,
+}
+
+impl ClauseBuilder {
+    /// Adds the literal `name`.
+    pub fn pos(self, name: &str) -> Self {
+        self.literal(name, false)
+    }
+
+    /// Adds the literal `-name`.
+    pub fn neg(self, name: &str) -> Self {
+        self.literal(name, true)
+    }
+
+    /// Finishes this clause and starts the next one.
+    pub fn clause(self) -> ClauseBuilder {
+        self.finish().clause()
+    }
+
+    /// Finishes this clause and returns the formula.
+    pub fn build(self) -> CNF {
+        self.finish().build()
+    }
+
+    fn literal(mut self, name: &str, negated: bool) -> Self {
+        self.clause.push(Literal {
+            name: name.to_string(),
+            negated,
+        });
+        self
+    }
+
+    fn finish(self) -> CnfBuilder {
+        let mut builder = self.builder;
+        builder.cnf.push(self.clause);
+        builder
+    }
+}
+
+/// Variables indexed like an array, `base_i` for every `i` in a range, so
+/// `VarFamily::new("x", 0..8)` stands for `x_0` to `x_7`. Read a model of
+/// them back as a number with `model_bits`.
+///
+/// ```
+/// use sat_solver::{SatResult, VarFamily, exactly_one, model_bits, solve};
+///
+/// let x = VarFamily::new("x", 0..8);
+/// let mut cnf = exactly_one(&x.lits());
+/// cnf.push(vec![x.pos(2)]);
+/// let SatResult::Sat(model) = solve(cnf) else { panic!() };
+/// assert_eq!(model_bits(&model, "x", 8), 4);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VarFamily {
+    base: String,
+    range: Range<usize>,
+}
+
+impl VarFamily {
+    pub fn new(base: &str, range: Range<usize>) -> Self {
+        VarFamily {
+            base: base.to_string(),
+            range,
+        }
+    }
+
+    /// The name of variable `i`.
+    ///
+    /// Panics if `i` is outside the range of the family.
+    pub fn name(&self, i: usize) -> String {
+        assert!(
+            self.range.contains(&i),
+            "{i} is outside {}..{} of `{}`",
+            self.range.start,
+            self.range.end,
+            self.base
+        );
+        format!("{}_{i}", self.base)
+    }
+
+    /// The names of every variable, in order of index.
+    pub fn names(&self) -> Vec<String> {
+        self.range.clone().map(|i| self.name(i)).collect()
+    }
+
+    /// The literal `base_i`.
+    pub fn pos(&self, i: usize) -> Literal {
+        Literal {
+            name: self.name(i),
+            negated: false,
+        }
+    }
+
+    /// The literal `-base_i`.
+    pub fn neg(&self, i: usize) -> Literal {
+        Literal {
+            name: self.name(i),
+            negated: true,
+        }
+    }
+
+    /// Every variable as a positive literal, in order of index, as taken
+    /// by the cardinality encodings.
+    pub fn lits(&self) -> Vec<Literal> {
+        self.range.clone().map(|i| self.pos(i)).collect()
+    }
+}
+
+/// Reads the variables `base_0` to `base_{width-1}` of `model` as the bits
+/// of a number, `base_0` the least significant. Variables the model does
+/// not set read as 0.
+///
+/// Panics if `width` is more than 64.
+pub fn model_bits(model: &Assignment, base: &str, width: usize) -> u64 {
+    assert!(width <= 64, "{width} bits do not fit in a u64");
+    (0..width)
+        .filter(|i| model.get(&format!("{base}_{i}")) == Some(&true))
+        .map(|i| 1 << i)
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, exactly_one, parse2, solve};
+    use alloc::vec;
+
+    #[test]
+    fn test_builder() {
+        let cnf = CnfBuilder::new()
+            .clause()
+            .pos("a")
+            .pos("b")
+            .clause()
+            .neg("a")
+            .build();
+        assert_eq!(cnf, parse2("{a,b},{-a}"));
+
+        assert_eq!(CnfBuilder::new().build(), CNF::new());
+        assert_eq!(CnfBuilder::new().clause().build(), vec![vec![]]);
+    }
+
+    #[test]
+    fn test_var_family() {
+        let x = VarFamily::new("x", 0..8);
+        assert_eq!(x.names()[..3], ["x_0", "x_1", "x_2"]);
+        assert_eq!(x.names().len(), 8);
+        assert_eq!(x.neg(7), Literal::from(("x_7", true)));
+
+        // one-hot on `x_2` reads back as bit 2
+        let mut cnf = exactly_one(&x.lits());
+        cnf.push(vec![x.pos(2)]);
+        let SatResult::Sat(model) = solve(cnf) else {
+            panic!("x_2 alone can be true");
+        };
+        assert_eq!(model_bits(&model, "x", 8), 4);
+        assert_eq!(model_bits(&model, "x", 2), 0);
+
+        let cnf = CnfBuilder::new()
+            .clause()
+            .pos(&x.name(0))
+            .clause()
+            .pos(&x.name(3))
+            .clause()
+            .neg(&x.name(1))
+            .build();
+        let SatResult::Sat(model) = solve(cnf) else {
+            panic!("satisfiable");
+        };
+        assert_eq!(model_bits(&model, "x", 8), 0b1001);
+        assert_eq!(VarFamily::new("y", 2..4).names(), ["y_2", "y_3"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "8 is outside 0..8 of `x`")]
+    fn test_var_family_out_of_range() {
+        VarFamily::new("x", 0..8).name(8);
+    }
+}
@@ -0,0 +1,318 @@
+use crate::{CNF, Clause, Literal};
+use alloc::{format, vec, vec::Vec};
+
+/// Whether a `Cardinality` bound is an upper or a lower one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CardinalityKind {
+    AtMost,
+    AtLeast,
+}
+
+/// A bound on how many of `lits` are true.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cardinality {
+    pub lits: Vec<Literal>,
+    pub bound: usize,
+    pub kind: CardinalityKind,
+}
+
+impl Cardinality {
+    /// Appends clauses enforcing the bound to `cnf`. Auxiliary variables are
+    /// named `@` followed by a number and do not clash with any auxiliary
+    /// variable already in `cnf`.
+    pub fn encode_into(&self, cnf: &mut CNF) {
+        match self.kind {
+            CardinalityKind::AtMost => at_most(&self.lits, self.bound, cnf),
+            // at least k are true exactly when at most n - k are false
+            CardinalityKind::AtLeast => match self.lits.len().checked_sub(self.bound) {
+                Some(bound) => {
+                    let negated: Vec<Literal> = self.lits.iter().map(negate).collect();
+                    at_most(&negated, bound, cnf);
+                }
+                None => cnf.push(Clause::new()),
+            },
+        }
+    }
+}
+
+/// Clauses that allow at most `k` of `lits` to be true, using the sequential
+/// counter encoding. At most one of a few literals is encoded pairwise,
+/// which takes fewer clauses and no auxiliary variables.
+pub fn encode_at_most_k(lits: &[Literal], k: usize) -> CNF {
+    let mut cnf = CNF::new();
+    at_most(lits, k, &mut cnf);
+    cnf
+}
+
+/// Clauses that make exactly one of `lits` true: one clause requiring at
+/// least one, and at most one encoded as in `encode_at_most_k`. With no
+/// literals the result is unsatisfiable.
+pub fn exactly_one(lits: &[Literal]) -> CNF {
+    let mut cnf = vec![lits.to_vec()];
+    at_most(lits, 1, &mut cnf);
+    cnf
+}
+
+fn at_most(lits: &[Literal], k: usize, cnf: &mut CNF) {
+    let n = lits.len();
+    if k >= n {
+        return;
+    }
+    if k == 0 {
+        cnf.extend(lits.iter().map(|lit| vec![negate(lit)]));
+        return;
+    }
+    if k == 1 && n * (n - 1) / 2 <= 3 * n - 4 {
+        for (i, a) in lits.iter().enumerate() {
+            for b in &lits[i + 1..] {
+                cnf.push(vec![negate(a), negate(b)]);
+            }
+        }
+        return;
+    }
+
+    // `s(i, j)` is forced true once at least `j + 1` of the first `i + 1`
+    // literals are true, and the `k + 1`th true literal is rejected
+    let first = next_aux(cnf);
+    let s = |i: usize, j: usize, negated| Literal {
+        name: format!("@{}", first + i * k + j),
+        negated,
+    };
+
+    cnf.push(vec![negate(&lits[0]), s(0, 0, false)]);
+    for j in 1..k {
+        cnf.push(vec![s(0, j, true)]);
+    }
+    for (i, lit) in lits.iter().enumerate().take(n - 1).skip(1) {
+        cnf.push(vec![negate(lit), s(i, 0, false)]);
+        cnf.push(vec![s(i - 1, 0, true), s(i, 0, false)]);
+        for j in 1..k {
+            cnf.push(vec![negate(lit), s(i - 1, j - 1, true), s(i, j, false)]);
+            cnf.push(vec![s(i - 1, j, true), s(i, j, false)]);
+        }
+        cnf.push(vec![negate(lit), s(i - 1, k - 1, true)]);
+    }
+    cnf.push(vec![negate(&lits[n - 1]), s(n - 2, k - 1, true)]);
+}
+
+/// Appends clauses to `cnf` that keep the total weight of the true literals
+/// of `lits` at most `bound`, using the generalized totalizer encoding.
+/// Its size grows with the number of distinct partial sums rather than
+/// with the weights, so a few heavy literals stay cheap. Auxiliary
+/// variables are named as in `Cardinality::encode_into`.
+pub(crate) fn encode_weighted_at_most(lits: &[(u64, Literal)], bound: u64, cnf: &mut CNF) {
+    let lits: Vec<(u64, Literal)> = lits
+        .iter()
+        .filter(|(weight, _)| *weight > 0)
+        .cloned()
+        .collect();
+    if lits.iter().map(|(weight, _)| weight).sum::<u64>() <= bound {
+        return;
+    }
+    let mut next = next_aux(cnf);
+    totalizer(&lits, bound, &mut next, cnf);
+}
+
+// for each reachable sum up to `bound` of the weights of the true `lits`,
+// a literal implied by reaching it, sorted by sum. Sums above `bound` are
+// rejected where they arise
+fn totalizer(
+    lits: &[(u64, Literal)],
+    bound: u64,
+    next: &mut usize,
+    cnf: &mut CNF,
+) -> Vec<(u64, Literal)> {
+    if let [(weight, lit)] = lits {
+        if *weight > bound {
+            cnf.push(vec![negate(lit)]);
+            return Vec::new();
+        }
+        return vec![(*weight, lit.clone())];
+    }
+    let (left, right) = lits.split_at(lits.len() / 2);
+    let left = totalizer(left, bound, next, cnf);
+    let right = totalizer(right, bound, next, cnf);
+
+    let mut sums: Vec<u64> = left.iter().chain(&right).map(|&(sum, _)| sum).collect();
+    for (a, _) in &left {
+        sums.extend(right.iter().map(|(b, _)| a + b).filter(|&sum| sum <= bound));
+    }
+    sums.sort_unstable();
+    sums.dedup();
+    let outputs: Vec<(u64, Literal)> = sums
+        .into_iter()
+        .map(|sum| {
+            *next += 1;
+            let output = Literal {
+                name: format!("@{}", *next - 1),
+                negated: false,
+            };
+            (sum, output)
+        })
+        .collect();
+    let output = |sum: u64| {
+        let index = outputs.binary_search_by_key(&sum, |&(sum, _)| sum).unwrap();
+        outputs[index].1.clone()
+    };
+
+    for (sum, lit) in left.iter().chain(&right) {
+        cnf.push(vec![negate(lit), output(*sum)]);
+    }
+    for (a, a_lit) in &left {
+        for (b, b_lit) in &right {
+            let mut clause = vec![negate(a_lit), negate(b_lit)];
+            if a + b <= bound {
+                clause.push(output(a + b));
+            }
+            cnf.push(clause);
+        }
+    }
+    outputs
+}
+
+// the first auxiliary number not used in `cnf`
+pub(crate) fn next_aux(cnf: &CNF) -> usize {
+    cnf.iter()
+        .flatten()
+        .filter_map(|lit| lit.name.strip_prefix('@')?.parse::<usize>().ok())
+        .max()
+        .map_or(0, |max| max + 1)
+}
+
+fn negate(lit: &Literal) -> Literal {
+    Literal {
+        name: lit.name.clone(),
+        negated: !lit.negated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, parse2, solve};
+
+    fn vars(n: usize) -> Vec<Literal> {
+        (0..n)
+            .map(|i| Literal {
+                name: format!("x{i}"),
+                negated: false,
+            })
+            .collect()
+    }
+
+    // whether `cnf` allows exactly the assignments to the `x`s that `allowed`
+    // accepts, the rest of the variables being free
+    fn allows(cnf: &CNF, n: usize, allowed: impl Fn(usize) -> bool) -> bool {
+        (0..1 << n).all(|bits: u32| {
+            let mut cnf = cnf.clone();
+            for (i, lit) in vars(n).into_iter().enumerate() {
+                cnf.push(vec![Literal {
+                    negated: bits >> i & 1 == 0,
+                    ..lit
+                }]);
+            }
+            matches!(solve(cnf), SatResult::Sat(_)) == allowed(bits.count_ones() as usize)
+        })
+    }
+
+    #[test]
+    fn test_at_most_one_pairwise() {
+        assert_eq!(
+            encode_at_most_k(&vars(3), 1),
+            parse2("{-x0,-x1},{-x0,-x2},{-x1,-x2}")
+        );
+    }
+
+    #[test]
+    fn test_at_most_k() {
+        for n in 1..7 {
+            for k in 0..=n {
+                let cnf = encode_at_most_k(&vars(n), k);
+                assert!(allows(&cnf, n, |count| count <= k), "n={n} k={k}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_exactly_one() {
+        assert_eq!(
+            exactly_one(&vars(3)),
+            parse2("{x0,x1,x2},{-x0,-x1},{-x0,-x2},{-x1,-x2}")
+        );
+        let models = crate::all_models(exactly_one(&vars(3))).collect::<Vec<_>>();
+        assert_eq!(models.len(), 3);
+        for model in models {
+            assert_eq!(model.values().filter(|&&value| value).count(), 1);
+        }
+
+        // larger sets use the sequential counter
+        for n in 1..8 {
+            assert!(
+                allows(&exactly_one(&vars(n)), n, |count| count == 1),
+                "n={n}"
+            );
+        }
+        assert_eq!(solve(exactly_one(&[])), SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_at_least_k() {
+        for k in 0..6 {
+            let mut cnf = CNF::new();
+            Cardinality {
+                lits: vars(5),
+                bound: k,
+                kind: CardinalityKind::AtLeast,
+            }
+            .encode_into(&mut cnf);
+            assert!(allows(&cnf, 5, |count| count >= k), "k={k}");
+        }
+    }
+
+    #[test]
+    fn test_auxiliary_variables_are_fresh() {
+        let mut cnf = CNF::new();
+        for kind in [CardinalityKind::AtMost, CardinalityKind::AtLeast] {
+            Cardinality {
+                lits: vars(6),
+                bound: 3,
+                kind,
+            }
+            .encode_into(&mut cnf);
+        }
+        assert!(allows(&cnf, 6, |count| count == 3));
+    }
+
+    #[test]
+    fn test_weighted_at_most() {
+        let weights = [3, 1, 0, 2, 5];
+        let lits: Vec<(u64, Literal)> = weights.into_iter().zip(vars(5)).collect();
+        for bound in 0..12 {
+            let mut cnf = CNF::new();
+            encode_weighted_at_most(&lits, bound, &mut cnf);
+            let allowed = (0..1u32 << 5).all(|bits| {
+                let mut cnf = cnf.clone();
+                let mut total = 0;
+                for (i, (weight, lit)) in lits.iter().enumerate() {
+                    let value = bits >> i & 1 == 1;
+                    if value {
+                        total += weight;
+                    }
+                    cnf.push(vec![Literal {
+                        negated: !value,
+                        ..lit.clone()
+                    }]);
+                }
+                matches!(solve(cnf), SatResult::Sat(_)) == (total <= bound)
+            });
+            assert!(allowed, "bound={bound}");
+        }
+
+        // heavy weights do not make for more clauses
+        let heavy: Vec<(u64, Literal)> =
+            [1_000_000, 3_000_000, 1].into_iter().zip(vars(3)).collect();
+        let mut cnf = CNF::new();
+        encode_weighted_at_most(&heavy, 3_000_000, &mut cnf);
+        assert!(cnf.len() < 20, "{} clauses", cnf.len());
+    }
+}
@@ -0,0 +1,95 @@
+use crate::cardinality::next_aux;
+use crate::{CNF, Literal, Map, Set};
+use alloc::{format, string::String, vec::Vec};
+
+/// Joins `a` and `b` into one formula in which they share no variables.
+/// Variables of `b` that also occur in `a` are renamed to names used in
+/// neither, all occurrences of a name the same way; the others keep their
+/// names. The map takes every variable of `b` to its name in the union, so
+/// a model of the union restricted to the values of the map is a model of
+/// `b`.
+///
+/// Renamed auxiliary `@` variables stay auxiliary and are numbered after
+/// those of both formulas, any other name gets a `_` and the first number
+/// that makes it unused.
+pub fn disjoint_union(a: CNF, b: CNF) -> (CNF, Map<String, String>) {
+    let mut taken: Set<String> = a
+        .iter()
+        .chain(&b)
+        .flatten()
+        .map(|lit| lit.name.clone())
+        .collect();
+    let in_a: Set<&str> = a.iter().flatten().map(|lit| lit.name.as_str()).collect();
+    let mut aux = next_aux(&a).max(next_aux(&b));
+
+    let mut renames = Map::new();
+    for lit in b.iter().flatten() {
+        if renames.contains_key(&lit.name) {
+            continue;
+        }
+        let name = if !in_a.contains(lit.name.as_str()) {
+            lit.name.clone()
+        } else if lit.name.starts_with('@') {
+            aux += 1;
+            format!("@{}", aux - 1)
+        } else {
+            (1..)
+                .map(|n| format!("{}_{n}", lit.name))
+                .find(|name| !taken.contains(name))
+                .expect("some suffix is unused")
+        };
+        taken.insert(name.clone());
+        renames.insert(lit.name.clone(), name);
+    }
+
+    let mut union = a;
+    union.extend(b.into_iter().map(|clause| {
+        clause
+            .into_iter()
+            .map(|lit| Literal {
+                name: renames[&lit.name].clone(),
+                negated: lit.negated,
+            })
+            .collect::<Vec<_>>()
+    }));
+    (union, renames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, model_count, parse2, solve, verify};
+
+    #[test]
+    fn test_disjoint_union() {
+        let a = parse2("{x,y},{-x,@0}");
+        let b = parse2("{-x,x_1},{z},{-@0,x}");
+        let (union, renames) = disjoint_union(a.clone(), b.clone());
+
+        assert_eq!(renames["x"], "x_2");
+        assert_eq!(renames["x_1"], "x_1");
+        assert_eq!(renames["z"], "z");
+        assert_eq!(renames["@0"], "@1");
+        assert_eq!(union.len(), 5);
+        assert_eq!(
+            model_count(union.clone()),
+            model_count(a.clone()) * model_count(b.clone())
+        );
+
+        let SatResult::Sat(model) = solve(union) else {
+            panic!("both parts are satisfiable");
+        };
+        assert!(verify(&a, &model));
+        let back = renames
+            .iter()
+            .map(|(old, new)| (old.clone(), model[new]))
+            .collect();
+        assert!(verify(&b, &back));
+    }
+
+    #[test]
+    fn test_disjoint_union_with_unsat_part() {
+        let (union, _) = disjoint_union(parse2("{a}"), parse2("{a},{-a}"));
+        assert_eq!(solve(union), SatResult::Unsat);
+    }
+}
@@ -0,0 +1,169 @@
+use crate::Map;
+use alloc::string::String;
+use core::time::Duration;
+
+/// Which search `solve_with` runs.
+///
+/// Both give the same verdict on every formula, they only differ in how
+/// long it takes and in which model they find.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Algorithm {
+    /// The classic recursive DPLL of `dpll`: unit propagation, pure literal
+    /// elimination and branching, copying the formula at every step. Easy
+    /// to follow, but it forgets why a branch failed and is only practical
+    /// up to a few dozen variables. Panics past `DPLL_MAX_DEPTH` nested
+    /// decisions.
+    Dpll,
+    /// Conflict driven clause learning with watched literals, restarts and
+    /// backjumping, as used by `solve`. Learned clauses prune the search, so
+    /// it handles formulas that are far out of reach for `Dpll`.
+    #[default]
+    Cdcl,
+}
+
+/// How the solver picks the next variable to branch on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Heuristic {
+    /// The unassigned variable that occurs first in the input.
+    FirstUnassigned,
+    /// The unassigned variable with the highest activity, where activity is
+    /// bumped for variables in conflicting clauses and decays over time.
+    #[default]
+    Vsids,
+    /// Try both values of every unassigned variable, propagate, and branch
+    /// on the variable forcing the most assignments either way, measured
+    /// as the product of both counts. A value that fails right away is
+    /// avoided. Expensive per decision but it can make the search much
+    /// smaller on small hard formulas.
+    Lookahead,
+    /// The Jeroslow-Wang rule: branch on the literal with the highest sum of
+    /// `2^-len` over the input clauses not yet satisfied it occurs in,
+    /// `len` counting the unassigned literals. Short clauses weigh most, so
+    /// it goes for those closest to becoming unit first.
+    JeroslowWang,
+    /// Maximum Occurrences in clauses of Minimum size: among the input
+    /// clauses not yet satisfied with the fewest unassigned literals, the
+    /// variable occurring in most, with the value it occurs with more.
+    Mom,
+}
+
+/// When the solver abandons its current decisions and starts over from the
+/// top, keeping everything it has learned.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RestartPolicy {
+    /// Never restart.
+    None,
+    /// Restart after `base` times the next term of the Luby sequence
+    /// (1, 1, 2, 1, 1, 2, 4, ...) conflicts.
+    Luby(u32),
+    /// Restart after 100 conflicts, then after that many times the factor
+    /// more each time.
+    Geometric(f64),
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        RestartPolicy::Luby(100)
+    }
+}
+
+/// When learned clauses are forgotten, so the clause database stays small
+/// on long runs. Clauses of the formula are never forgotten.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ClauseDeletion {
+    /// Keep every learned clause.
+    Never,
+    /// Whenever more than `limit` learned clauses are kept, forget those
+    /// with the highest LBD until half of them are left. The LBD of a
+    /// clause is the number of decision levels among its literals when it
+    /// was learned, and clauses connecting few levels tend to be useful
+    /// again. Clauses that currently force a literal are always kept.
+    Lbd { limit: usize },
+}
+
+impl Default for ClauseDeletion {
+    fn default() -> Self {
+        ClauseDeletion::Lbd { limit: 2000 }
+    }
+}
+
+/// Tuning knobs for the search. `SolverConfig::default()` is what `solve`
+/// uses.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SolverConfig {
+    pub heuristic: Heuristic,
+    pub restarts: RestartPolicy,
+    /// Postpone the next restart when the trail is much longer than it was
+    /// on average over this many recent conflicts, as glucose does, since
+    /// the search is then likely close to a model. Off by default.
+    pub restart_blocking: Option<usize>,
+    /// Branch on a variable with the value it last had, so a restart or
+    /// backjump returns to the assignment it left instead of starting over.
+    /// Otherwise every decision tries true first.
+    pub phase_saving: bool,
+    /// Seed for choosing branching values at random instead of true first.
+    /// With phase saving only a variable's first decision is random, later
+    /// ones reuse its saved value. The same seed gives the same run.
+    pub rand_polarity: Option<u64>,
+    /// Remove clauses subsumed by another clause before searching. Off by
+    /// default since the check is quadratic in the number of clauses.
+    pub subsumption: bool,
+    /// Simplify the clauses again every this many restarts, with bounded
+    /// rounds of subsumption, failed literal probing and variable
+    /// elimination at the top level. Eliminated variables get values
+    /// satisfying their removed clauses in the model, and their clauses
+    /// come back when a later added clause or assumption mentions them.
+    /// Off by default, and skipped while tracking a core, a proof or parity
+    /// constraints.
+    pub inprocessing: Option<u64>,
+    pub clause_deletion: ClauseDeletion,
+    /// Drop literals from learned clauses that are implied false by the
+    /// other literals, following their reasons back through the
+    /// implication graph.
+    pub minimize: bool,
+    /// Priorities of variables by name, 0 for those not listed. Branching
+    /// `FirstUnassigned` or `Vsids` always picks an unassigned variable of
+    /// the highest priority, the heuristic only breaks ties among them. The
+    /// other heuristics ignore priorities.
+    pub branch_priority: Map<String, f64>,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        SolverConfig {
+            heuristic: Heuristic::default(),
+            restarts: RestartPolicy::default(),
+            restart_blocking: None,
+            phase_saving: true,
+            rand_polarity: None,
+            subsumption: false,
+            inprocessing: None,
+            clause_deletion: ClauseDeletion::default(),
+            minimize: true,
+            branch_priority: Map::new(),
+        }
+    }
+}
+
+/// Bounds on a single solve, see `solve_with_limits`. Every limit left
+/// `None` is unbounded, and the solve stops at whichever limit is reached
+/// first. Limits are checked after conflicts, so they can be overshot a
+/// little. Without `std` there is no clock and `max_time` is ignored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SolverLimits {
+    pub max_time: Option<Duration>,
+    pub max_conflicts: Option<u64>,
+    pub max_propagations: Option<u64>,
+}
+
+/// Which of the `SolverLimits` stopped a solve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    Time,
+    Conflicts,
+    Propagations,
+}
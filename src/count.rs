@@ -0,0 +1,451 @@
+use crate::dpll::{simplify, unit_propagate};
+use crate::random::XorShift;
+use crate::{
+    Assignment, CNF, Clause, Literal, Map, SatResult, Set, Solver, VarMap, XorClause, solve,
+};
+use alloc::{string::String, vec, vec::Vec};
+
+/// Counts the total assignments over the variables of `cnf` that satisfy
+/// it. Variables left unconstrained once every clause is satisfied each
+/// double the count. Saturates at `u64::MAX`.
+pub fn model_count(cnf: CNF) -> u64 {
+    let total = cnf
+        .iter()
+        .flatten()
+        .map(|literal| literal.name.as_str())
+        .collect::<Set<_>>()
+        .len() as u32;
+
+    count(cnf, Assignment::new(), total)
+}
+
+fn count(cnf: CNF, mut assignment: Assignment, total: u32) -> u64 {
+    // pure literal elimination would lose models, only propagate units
+    let Some(cnf) = unit_propagate(cnf, &mut assignment) else {
+        return 0;
+    };
+
+    if cnf.iter().any(|clause| clause.is_empty()) {
+        return 0;
+    }
+    if cnf.is_empty() {
+        let free = total - assignment.len() as u32;
+        return 2u64.saturating_pow(free);
+    }
+
+    let name = cnf[0][0].name.clone();
+    let mut models = 0u64;
+    for value in [true, false] {
+        let mut assignment = assignment.clone();
+        assignment.insert(name.clone(), value);
+        if let Some(cnf) = simplify(cnf.clone(), &assignment) {
+            models = models.saturating_add(count(cnf, assignment, total));
+        }
+    }
+    models
+}
+
+/// Sums the weights of the models of `cnf`, where the weight of a model is
+/// the product of the weights of its literals. `weights` maps a variable
+/// and a value to the weight of that literal, and literals without an entry
+/// weigh 1. Variables left unconstrained contribute the sum of their two
+/// weights.
+pub fn weighted_model_count(cnf: CNF, weights: Map<(String, bool), f64>) -> f64 {
+    let variables: Set<String> = cnf
+        .iter()
+        .flatten()
+        .map(|literal| literal.name.clone())
+        .collect();
+
+    weighted_count(cnf, Assignment::new(), &variables, &weights)
+}
+
+fn weighted_count(
+    cnf: CNF,
+    mut assignment: Assignment,
+    variables: &Set<String>,
+    weights: &Map<(String, bool), f64>,
+) -> f64 {
+    let Some(cnf) = unit_propagate(cnf, &mut assignment) else {
+        return 0.0;
+    };
+
+    if cnf.iter().any(|clause| clause.is_empty()) {
+        return 0.0;
+    }
+    if cnf.is_empty() {
+        let weight = |name: &String, value: bool| {
+            weights.get(&(name.clone(), value)).copied().unwrap_or(1.0)
+        };
+        return variables
+            .iter()
+            .map(|name| match assignment.get(name) {
+                Some(&value) => weight(name, value),
+                None => weight(name, true) + weight(name, false),
+            })
+            .product();
+    }
+
+    let name = cnf[0][0].name.clone();
+    let mut total = 0.0;
+    for value in [true, false] {
+        let mut assignment = assignment.clone();
+        assignment.insert(name.clone(), value);
+        if let Some(cnf) = simplify(cnf.clone(), &assignment) {
+            total += weighted_count(cnf, assignment, variables, weights);
+        }
+    }
+    total
+}
+
+/// Calls `on_model` with every total model of `cnf`, stopping early once
+/// it returns `false`. Models are not collected, and one incremental solver
+/// is used throughout so what it learns carries over between models.
+pub fn solve_all<F: FnMut(&Assignment) -> bool>(cnf: CNF, mut on_model: F) {
+    let mut solver = Solver::new(cnf);
+    while let Some(model) = solver.solve_under(&[]) {
+        if !on_model(&model) {
+            return;
+        }
+        solver.add_clause(blocking_clause(&model));
+    }
+}
+
+/// The clause falsified by `model` and by no other assignment of its
+/// variables: the negation of every literal the model makes true. Adding it
+/// to a formula and solving again yields a different model.
+pub fn blocking_clause(model: &Assignment) -> Clause {
+    model
+        .iter()
+        .map(|(name, &value)| Literal {
+            name: name.clone(),
+            negated: value,
+        })
+        .collect()
+}
+
+// most models of a hash cell that are enumerated to pick from
+const CELL_LIMIT: usize = 16;
+
+/// Draws `count` models of `cnf`, approximately uniformly and the same for
+/// the same seed. Models may repeat, and none are returned if `cnf` is
+/// unsatisfiable.
+///
+/// Each sample adds random parity constraints, each over a random half of
+/// the variables, so that only a small random cell of the models is left,
+/// lists the cell and picks one of its models at random. The number of
+/// constraints is adjusted until cells hold between 1 and 16 models, so
+/// that every model is about equally likely to be drawn.
+pub fn sample_models(cnf: CNF, count: usize, seed: u64) -> Vec<Assignment> {
+    let mut samples = Vec::with_capacity(count);
+    if count == 0 || !crate::is_satisfiable(&cnf) {
+        return samples;
+    }
+    let vars = VarMap::from_cnf(&cnf);
+    let mut rng = XorShift::new(seed);
+    // the number of constraints is kept from one sample to the next
+    let mut hashes = 0;
+
+    while samples.len() < count {
+        let xors: Vec<XorClause> = (0..hashes)
+            .map(|_| {
+                let vars: Vec<String> = vars
+                    .names()
+                    .filter(|_| rng.below(2) == 0)
+                    .map(String::from)
+                    .collect();
+                // a parity over no variables can only be even
+                let parity = !vars.is_empty() && rng.below(2) == 0;
+                XorClause { vars, parity }
+            })
+            .collect();
+        let mut solver = Solver::new(cnf.clone());
+        solver.add_xors(&xors);
+
+        let mut cell = Vec::new();
+        while cell.len() <= CELL_LIMIT {
+            let Some(model) = solver.solve_under(&[]) else {
+                break;
+            };
+            solver.add_clause(blocking_clause(&model));
+            cell.push(model);
+        }
+        match cell.len() {
+            0 => hashes -= 1, // the formula has a model, so it was not 0
+            size if size > CELL_LIMIT => hashes += 1,
+            size => samples.push(cell.swap_remove(rng.below(size))),
+        }
+    }
+    samples
+}
+
+/// Lazily yields every total model of `cnf`. After each model a clause
+/// forbidding exactly that model is added and the formula is solved again,
+/// so nothing is computed before it is asked for.
+pub fn all_models(mut cnf: CNF) -> impl Iterator<Item = Assignment> {
+    let mut exhausted = false;
+    core::iter::from_fn(move || {
+        if exhausted {
+            return None;
+        }
+        match solve(cnf.clone()) {
+            SatResult::Sat(model) => {
+                cnf.push(blocking_clause(&model));
+                Some(model)
+            }
+            SatResult::Unsat => {
+                exhausted = true;
+                None
+            }
+        }
+    })
+}
+
+/// A few models that between them make true every literal that is true in
+/// some model of `cnf`, as diverse test vectors. Built greedily: each
+/// model is solved with a literal no model covers yet assumed, and then
+/// with every further uncovered literal added as an assumption that keeps
+/// the formula satisfiable. Empty if `cnf` is unsatisfiable.
+pub fn covering_models(cnf: CNF) -> Vec<Assignment> {
+    let vars = VarMap::from_cnf(&cnf);
+    let mut uncovered: Vec<Literal> = vars
+        .names()
+        .flat_map(|name| {
+            [false, true].map(|negated| Literal {
+                name: name.into(),
+                negated,
+            })
+        })
+        .collect();
+    let holds =
+        |model: &Assignment, literal: &Literal| model.get(&literal.name) == Some(&!literal.negated);
+
+    let mut solver = Solver::new(cnf);
+    let Some(any) = solver.solve_under(&[]) else {
+        return Vec::new();
+    };
+    let mut models = Vec::new();
+    while let Some(target) = uncovered.first() {
+        let mut assumptions = vec![target.clone()];
+        let Some(mut model) = solver.solve_under(&assumptions) else {
+            // false in every model
+            uncovered.remove(0);
+            continue;
+        };
+        for literal in &uncovered[1..] {
+            if holds(&model, literal) || assumptions.iter().any(|a| a.name == literal.name) {
+                continue;
+            }
+            assumptions.push(literal.clone());
+            match solver.solve_under(&assumptions) {
+                Some(wider) => model = wider,
+                None => {
+                    assumptions.pop();
+                }
+            }
+        }
+        uncovered.retain(|literal| !holds(&model, literal));
+        models.push(model);
+    }
+
+    // without variables any model covers everything
+    if models.is_empty() {
+        models.push(any);
+    }
+    models
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format_assignment, parse2, verify};
+
+    #[test]
+    fn test_model_count() {
+        assert_eq!(model_count(parse2("{a,b}")), 3);
+        assert_eq!(model_count(parse2("{a},{-a}")), 0);
+        assert_eq!(model_count(parse2("{a,b},{-a,b},{a,-b},{-a,-b}")), 0);
+        assert_eq!(model_count(parse2("{a,b},{-a,c},{-b,-c}")), 2);
+        assert_eq!(model_count(CNF::new()), 1);
+        assert_eq!(model_count(vec![vec![]]), 0);
+    }
+
+    #[test]
+    fn test_model_count_free_variables() {
+        // `c` only appears in a tautology, so both of its values count
+        assert_eq!(model_count(parse2("{a,b},{c,-c}")), 6);
+        // once `a` holds, `b` and `d` are unconstrained
+        assert_eq!(model_count(parse2("{a},{a,b},{-d,a}")), 4);
+    }
+
+    #[test]
+    fn test_weighted_model_count() {
+        let weights: Map<(String, bool), f64> = [
+            (("a".to_string(), true), 0.3),
+            (("a".to_string(), false), 0.7),
+            (("b".to_string(), true), 0.6),
+            (("b".to_string(), false), 0.4),
+            (("c".to_string(), true), 2.0),
+        ]
+        .into_iter()
+        .collect();
+        let close = |a: f64, b: f64| (a - b).abs() < 1e-9;
+
+        // every model but a=false, b=false: 1 - 0.7 * 0.4
+        let count = weighted_model_count(parse2("{a,b}"), weights.clone());
+        assert!(close(count, 0.72), "{count}");
+        // `c` is free and weighs 2 + 1
+        let count = weighted_model_count(parse2("{a,b},{c,-c}"), weights.clone());
+        assert!(close(count, 0.72 * 3.0), "{count}");
+        // only a=true, b=false
+        let count = weighted_model_count(parse2("{a},{-b}"), weights.clone());
+        assert!(close(count, 0.3 * 0.4), "{count}");
+        assert_eq!(weighted_model_count(parse2("{a},{-a}"), weights), 0.0);
+    }
+
+    #[test]
+    fn test_all_models() {
+        let cnf = parse2("{a,b}");
+        let models: Vec<Assignment> = all_models(cnf.clone()).collect();
+        assert_eq!(models.len(), 3);
+        for (i, model) in models.iter().enumerate() {
+            assert_eq!(model.len(), 2);
+            assert!(verify(&cnf, model));
+            assert!(!models[..i].contains(model));
+        }
+
+        assert_eq!(all_models(parse2("{a},{-a}")).count(), 0);
+        assert_eq!(all_models(CNF::new()).count(), 1);
+    }
+
+    #[test]
+    fn test_blocking_clause() {
+        let mut solver = Solver::new(parse2("{a,b}"));
+        let first = solver.solve_under(&[]).unwrap();
+        let blocking = blocking_clause(&first);
+        assert_eq!(blocking.len(), 2);
+        assert!(!verify(&vec![blocking.clone()], &first));
+
+        solver.add_clause(blocking);
+        let second = solver.solve_under(&[]).unwrap();
+        assert_ne!(first, second);
+        assert!(verify(&parse2("{a,b}"), &second));
+    }
+
+    #[test]
+    fn test_solve_all() {
+        let cnf = parse2("{a,b,c}");
+        let mut models = Vec::new();
+        solve_all(cnf.clone(), |model| {
+            models.push(model.clone());
+            true
+        });
+        assert_eq!(models.len(), 7);
+        for (i, model) in models.iter().enumerate() {
+            assert!(verify(&cnf, model));
+            assert!(!models[..i].contains(model));
+        }
+
+        // count the models with `a` set, stopping after two of them
+        let mut with_a = 0;
+        solve_all(cnf, |model| {
+            with_a += model["a"] as usize;
+            with_a < 2
+        });
+        assert_eq!(with_a, 2);
+
+        let mut calls = 0;
+        solve_all(parse2("{a},{-a}"), |_| {
+            calls += 1;
+            true
+        });
+        assert_eq!(calls, 0);
+        solve_all(CNF::new(), |model| {
+            assert!(model.is_empty());
+            calls += 1;
+            true
+        });
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_sample_models() {
+        let cnf = parse2("{a,b}");
+        let samples = sample_models(cnf.clone(), 300, 1);
+        assert_eq!(samples.len(), 300);
+        assert_eq!(samples, sample_models(cnf.clone(), 300, 1));
+        assert!(samples.iter().all(|model| verify(&cnf, model)));
+        assert!(samples.iter().all(|model| model["a"] || model["b"]));
+        // each of the three models about a third of the time
+        for (a, b) in [(true, true), (true, false), (false, true)] {
+            let drawn = samples
+                .iter()
+                .filter(|m| m["a"] == a && m["b"] == b)
+                .count();
+            assert!((60..140).contains(&drawn), "{a} {b}: {drawn}");
+        }
+
+        // more models than fit in a cell
+        let cnf = parse2("{a,b,c,d,e,f,g}");
+        let samples = sample_models(cnf.clone(), 50, 2);
+        assert!(samples.iter().all(|model| verify(&cnf, model)));
+        let distinct: Vec<_> = samples
+            .iter()
+            .map(format_assignment)
+            .collect::<Set<_>>()
+            .into_iter()
+            .collect();
+        assert!(distinct.len() > 30);
+
+        assert!(sample_models(parse2("{a},{-a}"), 5, 0).is_empty());
+        assert!(sample_models(cnf, 0, 0).is_empty());
+    }
+
+    #[test]
+    fn test_covering_models() {
+        let covered = |models: &[Assignment], name: &str, value: bool| {
+            models.iter().any(|model| model[name] == value)
+        };
+
+        let cnf = parse2("{a,b}");
+        let models = covering_models(cnf.clone());
+        assert_eq!(models.len(), 2);
+        assert!(models.iter().all(|model| verify(&cnf, model)));
+        for name in ["a", "b"] {
+            assert!(covered(&models, name, true), "{name}");
+            assert!(covered(&models, name, false), "{name}");
+        }
+
+        // `a` is a backbone, so `-a` is never covered
+        let cnf = parse2("{a},{-a,b,c},{-b,-c}");
+        let models = covering_models(cnf.clone());
+        assert!(models.iter().all(|model| verify(&cnf, model) && model["a"]));
+        for name in ["b", "c"] {
+            assert!(covered(&models, name, true) && covered(&models, name, false));
+        }
+
+        assert!(covering_models(parse2("{a},{-a}")).is_empty());
+        assert_eq!(covering_models(CNF::new()), [Assignment::new()]);
+    }
+
+    #[test]
+    fn test_all_models_is_lazy() {
+        // 2^30 models, only the ones asked for are computed
+        let cnf: CNF = (0..30)
+            .map(|i| {
+                let name = format!("x{i}");
+                vec![
+                    Literal {
+                        name: name.clone(),
+                        negated: false,
+                    },
+                    Literal {
+                        name,
+                        negated: true,
+                    },
+                ]
+            })
+            .collect();
+        assert_eq!(all_models(cnf).take(10).count(), 10);
+    }
+}
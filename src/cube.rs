@@ -0,0 +1,125 @@
+use crate::dpll::{simplify, unit_propagate};
+use crate::{Assignment, CNF, Literal, Set, solve_assuming};
+use alloc::{collections::VecDeque, string::String, vec::Vec};
+
+/// Splits the search space of `cnf` into about `target` cubes, partial
+/// assignments given as literals, for cube-and-conquer: every assignment
+/// agrees with exactly one cube, so the cubes can be solved independently
+/// with `solve_cube` and `cnf` is satisfiable exactly when one of them is.
+///
+/// Cubes are split breadth first on the variable whose two values each
+/// force the most others by unit propagation, as lookahead solvers do. A
+/// cube that propagation refutes or that leaves no clause open is not
+/// split further, so there can be fewer than `target` cubes.
+pub fn generate_cubes(cnf: &CNF, target: usize) -> Vec<Vec<Literal>> {
+    let mut open = VecDeque::from([Vec::new()]);
+    let mut done = Vec::new();
+    while open.len() + done.len() < target {
+        let Some(cube) = open.pop_front() else {
+            break;
+        };
+        match split_var(cnf, &cube) {
+            Some(name) => {
+                for negated in [false, true] {
+                    let mut branch = cube.clone();
+                    branch.push(Literal {
+                        name: name.clone(),
+                        negated,
+                    });
+                    open.push_back(branch);
+                }
+            }
+            None => done.push(cube),
+        }
+    }
+    done.extend(open);
+    done
+}
+
+/// Solves `cnf` under the literals of `cube`, see `generate_cubes`. `None`
+/// means there is no model within the cube.
+pub fn solve_cube(cnf: CNF, cube: &[Literal]) -> Option<Assignment> {
+    solve_assuming(cnf, cube)
+}
+
+// the variable to split `cube` on, `None` if propagation settles it
+fn split_var(cnf: &CNF, cube: &[Literal]) -> Option<String> {
+    let mut assignment: Assignment = cube
+        .iter()
+        .map(|literal| (literal.name.clone(), !literal.negated))
+        .collect();
+    let residual = unit_propagate(simplify(cnf.clone(), &assignment)?, &mut assignment)?;
+
+    let mut seen = Set::new();
+    let mut best: Option<(String, usize)> = None;
+    for literal in residual.iter().flatten() {
+        if !seen.insert(literal.name.as_str()) {
+            continue;
+        }
+        let forced = |value| {
+            let mut probe = assignment.clone();
+            probe.insert(literal.name.clone(), value);
+            let residual = simplify(residual.clone(), &probe)?;
+            unit_propagate(residual, &mut probe)?;
+            Some(probe.len() - assignment.len())
+        };
+        // a value that fails counts as forcing everything, so failed
+        // literals are split on first
+        let score = match (forced(true), forced(false)) {
+            (Some(t), Some(f)) => t * f,
+            _ => usize::MAX,
+        };
+        if best.as_ref().is_none_or(|(_, best)| score > *best) {
+            best = Some((literal.name.clone(), score));
+        }
+    }
+    best.map(|(name, _)| name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, random_3sat, solve, verify};
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_cubes_find_a_model() {
+        for seed in 0..10 {
+            let cnf = random_3sat(20, 80, seed);
+            let cubes = generate_cubes(&cnf, 8);
+            assert_eq!(cubes.len(), 8);
+            let models: Vec<Assignment> = cubes
+                .iter()
+                .filter_map(|cube| solve_cube(cnf.clone(), cube))
+                .collect();
+            assert!(models.iter().all(|model| verify(&cnf, model)));
+            let sat = matches!(solve(cnf), SatResult::Sat(_));
+            assert_eq!(!models.is_empty(), sat, "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_cubes_partition_assignments() {
+        let cnf = random_3sat(6, 12, 3);
+        let cubes = generate_cubes(&cnf, 5);
+        let names: Vec<String> = (1..=6).map(|var: usize| var.to_string()).collect();
+        assert!(
+            cubes
+                .iter()
+                .flatten()
+                .all(|literal| names.contains(&literal.name))
+        );
+        for bits in 0..1 << names.len() {
+            let holds = |literal: &Literal| {
+                let i = names.iter().position(|name| *name == literal.name).unwrap();
+                (bits >> i & 1 == 1) != literal.negated
+            };
+            let agreeing = cubes.iter().filter(|cube| cube.iter().all(holds));
+            assert_eq!(agreeing.count(), 1, "{bits:b}");
+        }
+
+        assert_eq!(generate_cubes(&cnf, 1), [Vec::new()]);
+        // nothing open to split on
+        assert_eq!(generate_cubes(&CNF::new(), 4), [Vec::new()]);
+    }
+}
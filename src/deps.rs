@@ -0,0 +1,66 @@
+use crate::{CNF, Clause, Literal};
+use alloc::{string::ToString, vec, vec::Vec};
+
+fn literal(name: &str, negated: bool) -> Literal {
+    Literal {
+        name: name.to_string(),
+        negated,
+    }
+}
+
+/// Clauses saying that installing `pkg` requires every group of `deps`,
+/// each satisfied by installing any one of its alternatives. Variables are
+/// package names, true when the package is installed. A group without
+/// alternatives cannot be satisfied, so `pkg` cannot be installed.
+pub fn requires(pkg: &str, deps: &[Vec<&str>]) -> CNF {
+    deps.iter()
+        .map(|alternatives| {
+            let mut clause = Clause::with_capacity(alternatives.len() + 1);
+            clause.push(literal(pkg, true));
+            clause.extend(alternatives.iter().map(|&dep| literal(dep, false)));
+            clause
+        })
+        .collect()
+}
+
+/// The clause saying that `a` and `b` are not both installed, see
+/// `requires`.
+pub fn conflicts(a: &str, b: &str) -> Clause {
+    vec![literal(a, true), literal(b, true)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, parse2, solve, solve_assuming};
+
+    #[test]
+    fn test_requires() {
+        let mut cnf = requires("A", &[vec!["B", "C"]]);
+        assert_eq!(cnf, parse2("{-A,B,C}"));
+        cnf.push(vec![literal("A", false)]);
+        let model = match solve(cnf.clone()) {
+            SatResult::Sat(model) => model,
+            SatResult::Unsat => panic!("A is installable"),
+        };
+        assert!(model["B"] || model["C"]);
+        let neither = [literal("B", true), literal("C", true)];
+        assert_eq!(solve_assuming(cnf, &neither), None);
+
+        assert_eq!(
+            requires("A", &[vec!["B"], vec![], vec!["C", "D"]]),
+            parse2("{-A,B},{-A},{-A,C,D}")
+        );
+    }
+
+    #[test]
+    fn test_conflicts() {
+        // A needs B or C but conflicts with B
+        let mut cnf = requires("A", &[vec!["B", "C"]]);
+        cnf.push(conflicts("A", "B"));
+        assert_eq!(cnf[1], parse2("{-A,-B}")[0]);
+        let installed = [literal("A", false)];
+        let model = solve_assuming(cnf, &installed).unwrap();
+        assert!(!model["B"] && model["C"]);
+    }
+}
@@ -0,0 +1,184 @@
+use crate::{Assignment, CNF, Clause, VarMap};
+use alloc::{
+    format,
+    string::{String, ToString},
+};
+
+/// Writes `cnf` in DIMACS CNF format, numbering variables by their id in
+/// `vars` plus one. Clauses keep their order and literals their order
+/// within each clause, so the output only depends on `cnf` and `vars`.
+/// `VarMap` ids are dense, so the header counts exactly the numbers used
+/// up to the largest. Panics if a variable of `cnf` is missing from `vars`.
+pub fn to_dimacs(cnf: &CNF, vars: &VarMap) -> String {
+    let mut out = format!("p cnf {} {}\n", vars.len(), cnf.len());
+    for clause in cnf {
+        write_clause(&mut out, clause, vars);
+    }
+    out
+}
+
+/// Renames the variables of `cnf` to `1`, `2`, ... in order of first
+/// occurrence, the numbering `to_dimacs` uses. The returned `VarMap` gives
+/// each original name the id one less than its new number, so a model of
+/// the result translates back through `VarMap::name`.
+pub fn renumber(mut cnf: CNF) -> (CNF, VarMap) {
+    let vars = VarMap::from_cnf(&cnf);
+    for literal in cnf.iter_mut().flatten() {
+        let id = vars.id(&literal.name).expect("interned from cnf");
+        literal.name = (id + 1).to_string();
+    }
+    (cnf, vars)
+}
+
+// one `0` terminated line of numbered literals
+pub(crate) fn write_clause(out: &mut String, clause: &Clause, vars: &VarMap) {
+    for literal in clause {
+        let id = vars
+            .id(&literal.name)
+            .unwrap_or_else(|| panic!("variable `{}` is not in the VarMap", literal.name));
+        if literal.negated {
+            out.push('-');
+        }
+        out.push_str(&(id + 1).to_string());
+        out.push(' ');
+    }
+    out.push_str("0\n");
+}
+
+// competition checkers read value lines up to this length
+const VALUE_LINE_WIDTH: usize = 80;
+
+/// Writes `model` as the `v` lines SAT competition solvers print after
+/// `s SATISFIABLE`, numbering variables like `to_dimacs`: `1` when true,
+/// `-1` when false, in id order and ending with `0`. Long models are
+/// split over several lines. Variables of `vars` missing from `model` are
+/// left out.
+pub fn model_to_value_lines(model: &Assignment, vars: &VarMap) -> String {
+    let mut out = String::new();
+    let mut line = String::from("v");
+    let values = vars.names().enumerate().filter_map(|(id, name)| {
+        let value = *model.get(name)?;
+        Some(if value {
+            (id + 1).to_string()
+        } else {
+            format!("-{}", id + 1)
+        })
+    });
+    for value in values.chain(["0".to_string()]) {
+        if line.len() + 1 + value.len() > VALUE_LINE_WIDTH {
+            out.push_str(&line);
+            out.push('\n');
+            line = String::from("v");
+        }
+        line.push(' ');
+        line.push_str(&value);
+    }
+    out.push_str(&line);
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_dimacs, parse2};
+
+    #[test]
+    fn test_renumber() {
+        let (cnf, vars) = renumber(parse2("{x,y},{z}"));
+        assert_eq!(cnf, parse2("{1,2},{3}"));
+        assert_eq!(vars.names().collect::<Vec<_>>(), ["x", "y", "z"]);
+
+        let (cnf, vars) = renumber(parse2("{b,-a},{-b,c},{a}"));
+        assert_eq!(cnf, parse2("{1,-2},{-1,3},{2}"));
+        assert_eq!(vars.name(1), Some("a"));
+    }
+
+    #[test]
+    fn test_to_dimacs() {
+        let cnf = parse2("{a,b},{-a,c},{-b,-c}");
+        let vars = VarMap::from_cnf(&cnf);
+        assert_eq!(
+            to_dimacs(&cnf, &vars),
+            "p cnf 3 3\n1 2 0\n-1 3 0\n-2 -3 0\n"
+        );
+    }
+
+    #[test]
+    fn test_to_dimacs_is_deterministic() {
+        let cnf = crate::random_3sat(30, 120, 7);
+        let (renumbered, _) = renumber(cnf.clone());
+        let first = to_dimacs(&renumbered, &VarMap::from_cnf(&renumbered));
+        for _ in 0..10 {
+            let (again, _) = renumber(cnf.clone());
+            assert_eq!(to_dimacs(&again, &VarMap::from_cnf(&again)), first);
+        }
+        // a renumbered formula keeps its numbers
+        let (again, vars) = renumber(renumbered.clone());
+        assert_eq!(again, renumbered);
+        assert_eq!(to_dimacs(&renumbered, &vars), first);
+    }
+
+    #[test]
+    #[should_panic(expected = "variable `c` is not in the VarMap")]
+    fn test_to_dimacs_unknown_variable() {
+        to_dimacs(&parse2("{a,b},{c}"), &VarMap::from_cnf(&parse2("{a,b}")));
+    }
+
+    #[test]
+    fn test_model_to_value_lines() {
+        let cnf = parse2("{a,b},{-a,c},{-b,-c}");
+        let vars = VarMap::from_cnf(&cnf);
+        let model = [("a", true), ("b", false), ("c", true)]
+            .map(|(name, value)| (name.to_string(), value))
+            .into_iter()
+            .collect();
+        assert_eq!(model_to_value_lines(&model, &vars), "v 1 -2 3 0\n");
+        assert_eq!(model_to_value_lines(&Assignment::new(), &vars), "v 0\n");
+
+        let names: Vec<String> = (0..100).map(|i| format!("x{i}")).collect();
+        let model: Assignment = names.iter().map(|name| (name.clone(), false)).collect();
+        let cnf = vec![
+            names
+                .iter()
+                .map(|name| crate::Literal {
+                    name: name.clone(),
+                    negated: false,
+                })
+                .collect(),
+        ];
+        let lines = model_to_value_lines(&model, &VarMap::from_cnf(&cnf));
+        assert!(lines.lines().count() > 1);
+        assert!(
+            lines
+                .lines()
+                .all(|line| line.starts_with("v ") && line.len() <= 80)
+        );
+        let values: Vec<i64> = lines
+            .split_whitespace()
+            .filter(|&token| token != "v")
+            .map(|token| token.parse().unwrap())
+            .collect();
+        assert_eq!(
+            values,
+            (1..=100).map(|id| -id).chain([0]).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_dimacs_round_trip() {
+        let cnf = parse2("{a,b},{b,c},{-a,-b},{-c,d}");
+        let vars = VarMap::from_cnf(&cnf);
+        let parsed = parse_dimacs(&to_dimacs(&cnf, &vars)).unwrap();
+
+        assert_eq!(parsed.len(), cnf.len());
+        for (original, parsed) in cnf.iter().zip(&parsed) {
+            assert_eq!(original.len(), parsed.len());
+            for (original, parsed) in original.iter().zip(parsed) {
+                let id: u32 = parsed.name.parse().unwrap();
+                assert_eq!(vars.name(id - 1), Some(original.name.as_str()));
+                assert_eq!(original.negated, parsed.negated);
+            }
+        }
+    }
+}
@@ -0,0 +1,1016 @@
+use crate::occurs::Occurrences;
+use crate::parse::ClauseMeta;
+use crate::{Assignment, CNF, Clause, Literal, Reason, SatResult, Set};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+// DPLL has 4 steps:
+// 1. Unit propagation
+// 2. Pure literal elimination
+// 3. Branching
+// 5. Termination
+
+/// The classic recursive DPLL, nesting at most `DPLL_MAX_DEPTH`
+/// decisions.
+///
+/// # Panics
+///
+/// Panics if the formula needs more nested decisions than that, instead of
+/// overflowing the stack. `dpll_with_depth_limit` reports it as an error,
+/// and `solve` has no such limit.
+pub fn dpll(cnf: CNF, assignment: &mut Assignment) -> SatResult {
+    dpll_with_depth_limit(cnf, assignment, DPLL_MAX_DEPTH).expect(TOO_DEEP)
+}
+
+/// The depth limit `dpll` runs with. Far beyond what the formulas the
+/// classic search is practical for need, and measured to fit in a 2 MiB
+/// thread stack, the default for spawned threads, in debug builds too,
+/// which take about 1.4 KiB of stack per level.
+pub const DPLL_MAX_DEPTH: usize = 1000;
+
+const TOO_DEEP: &str = "more than DPLL_MAX_DEPTH nested decisions, use `solve` instead";
+
+/// Returned when the classic `dpll` would need more nested decisions than
+/// allowed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TooDeep;
+
+impl core::fmt::Display for TooDeep {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "dpll recursion too deep")
+    }
+}
+
+impl core::error::Error for TooDeep {}
+
+/// Runs `dpll` with at most `max_depth` decisions in effect at once. Each
+/// decision is a level of recursion, so on formulas needing many this
+/// gives up with `TooDeep` instead of overflowing the stack.
+/// `DPLL_MAX_DEPTH` is a safe choice. The CDCL solver behind `solve`
+/// has no such limit.
+pub fn dpll_with_depth_limit(
+    cnf: CNF,
+    assignment: &mut Assignment,
+    max_depth: usize,
+) -> Result<SatResult, TooDeep> {
+    // simplify drops satisfied clauses, and with them any variable that only
+    // appeared there, so remember the original variables up front
+    let variables: Set<String> = cnf
+        .iter()
+        .flatten()
+        .map(|literal| literal.name.clone())
+        .collect();
+
+    match search(cnf, assignment, max_depth, &[], &mut Vec::new())? {
+        SatResult::Sat(mut model) => {
+            complete(&mut model, variables);
+            Ok(SatResult::Sat(model))
+        }
+        SatResult::Unsat => Ok(SatResult::Unsat),
+    }
+}
+
+// variables that were never forced are free, any value satisfies the formula
+fn complete(model: &mut Assignment, variables: Set<String>) {
+    for name in variables {
+        model.entry(name).or_insert(true);
+    }
+}
+
+/// Runs the classic `dpll`, branching on the variables of `order` first,
+/// in that order, and then on the first unassigned one as usual. Each
+/// value is still the one making the more frequent literal true, so the
+/// search is the same as `dpll` up to the order. `None` means the formula
+/// is unsatisfiable.
+pub fn solve_with_order(cnf: CNF, order: &[String]) -> Option<Assignment> {
+    solve_traced(cnf, order, &mut Vec::new())
+}
+
+// `solve_with_order`, recording every branch taken in `trace`
+fn solve_traced(cnf: CNF, order: &[String], trace: &mut Vec<(String, bool)>) -> Option<Assignment> {
+    let variables: Set<String> = cnf
+        .iter()
+        .flatten()
+        .map(|literal| literal.name.clone())
+        .collect();
+    let result = search(cnf, &mut Assignment::new(), DPLL_MAX_DEPTH, order, trace);
+    match result.expect(TOO_DEEP) {
+        SatResult::Sat(mut model) => {
+            complete(&mut model, variables);
+            Some(model)
+        }
+        SatResult::Unsat => None,
+    }
+}
+
+// `depth` is how many more decisions may be nested, variables of `order`
+// are branched on first and every branch taken is pushed to `trace`
+fn search(
+    cnf: CNF,
+    assignment: &mut Assignment,
+    depth: usize,
+    order: &[String],
+    trace: &mut Vec<(String, bool)>,
+) -> Result<SatResult, TooDeep> {
+    let Some(cnf) = unit_propagate(cnf, assignment) else {
+        return Ok(SatResult::Unsat);
+    };
+    let Some(cnf) = pure_literal_eliminate(&cnf, assignment) else {
+        return Ok(SatResult::Unsat);
+    };
+
+    if cnf.is_empty() {
+        return Ok(SatResult::Sat(assignment.clone())); // all clauses satisfied
+    }
+
+    if cnf.iter().any(|clause| clause.is_empty()) {
+        return Ok(SatResult::Unsat); // conflict
+    }
+
+    // find a variable that is not yet assigned
+    let ordered = order.iter().find(|name| {
+        !assignment.contains_key(*name)
+            && cnf.iter().flatten().any(|literal| literal.name == **name)
+    });
+    let pick = match ordered {
+        Some(name) => Some((name.clone(), polarity(&cnf, name))),
+        None => pick_literal(&cnf, assignment),
+    };
+    let (name, polarity) = match pick {
+        Some(pick) => pick,
+        None => {
+            if cnf.is_empty() {
+                return Ok(SatResult::Sat(assignment.clone()));
+            } else {
+                // unsatisfiable -> we have
+                return Ok(SatResult::Unsat);
+            }
+        }
+    };
+    if depth == 0 {
+        return Err(TooDeep);
+    }
+
+    // branch, trying the suggested polarity first
+    for value in [polarity, !polarity] {
+        let mut assignment = assignment.clone();
+        assignment.insert(name.clone(), value);
+        trace.push((name.clone(), value));
+        if let SatResult::Sat(result) =
+            search(cnf.clone(), &mut assignment, depth - 1, order, trace)?
+        {
+            return Ok(SatResult::Sat(result));
+        }
+    }
+
+    // neither branch was satisfiable
+    Ok(SatResult::Unsat)
+}
+
+/// Follows the classic `dpll` down to its first conflict and returns the
+/// decisions taken on the way, in order, with the clause of `cnf` that
+/// became false. `None` if the search finds a model without ever running
+/// into a conflict.
+pub fn trace_first_conflict(cnf: CNF) -> Option<(Vec<(String, bool)>, Clause)> {
+    let falsified = |assignment: &Assignment| {
+        cnf.iter()
+            .find(|clause| {
+                clause
+                    .iter()
+                    .all(|literal| assignment.get(&literal.name) == Some(&literal.negated))
+            })
+            .cloned()
+            .expect("a conflict falsifies a clause of the formula")
+    };
+
+    let mut decisions = Vec::new();
+    let mut assignment = Assignment::new();
+    let mut formula = cnf.clone();
+    // the same steps as `search`, where the first conflict is always met
+    // before the first branch is left
+    loop {
+        let Some(simplified) = unit_propagate(formula, &mut assignment) else {
+            return Some((decisions, falsified(&assignment)));
+        };
+        let Some(simplified) = pure_literal_eliminate(&simplified, &mut assignment) else {
+            return Some((decisions, falsified(&assignment)));
+        };
+        if simplified.is_empty() {
+            return None;
+        }
+        if simplified.iter().any(|clause| clause.is_empty()) {
+            return Some((decisions, falsified(&assignment)));
+        }
+
+        let (name, value) = pick_literal(&simplified, &assignment)?;
+        assignment.insert(name.clone(), value);
+        decisions.push((name, value));
+        formula = simplified;
+    }
+}
+
+/// The literals the classic DPLL makes true on its way to a model of
+/// `cnf`, in the order it sets them, each with why: a branch, a clause of
+/// `cnf` that became unit, given by its index, or a pure literal. Branches
+/// that failed are left out. `None` if `cnf` is unsatisfiable.
+///
+/// Units are found in the clauses as given so that their indices stay
+/// meaningful, which can make the model differ from the one of `dpll`.
+///
+/// # Panics
+///
+/// Like `dpll`, past `DPLL_MAX_DEPTH` nested decisions.
+pub fn dpll_trail(cnf: &CNF) -> Option<Vec<(Literal, Reason)>> {
+    let mut trail = Vec::new();
+    let found = trail_search(cnf, &mut Assignment::new(), &mut trail, DPLL_MAX_DEPTH);
+    found.expect(TOO_DEEP).then_some(trail)
+}
+
+// `search` for `dpll_trail`. on failure `assignment` and `trail` are left
+// as they were
+fn trail_search(
+    cnf: &CNF,
+    assignment: &mut Assignment,
+    trail: &mut Vec<(Literal, Reason)>,
+    depth: usize,
+) -> Result<bool, TooDeep> {
+    let start = trail.len();
+    let set = |assignment: &mut Assignment, trail: &mut Vec<_>, literal: Literal, reason| {
+        assignment.insert(literal.name.clone(), !literal.negated);
+        trail.push((literal, reason));
+    };
+    let undo = |assignment: &mut Assignment, trail: &mut Vec<(Literal, Reason)>, mark| {
+        for (literal, _) in trail.drain(mark..) {
+            assignment.remove(&literal.name);
+        }
+    };
+    // the unassigned literals of each clause not yet satisfied
+    let open = |assignment: &Assignment| -> Vec<(usize, Clause)> {
+        (0..cnf.len())
+            .filter(|&index| !satisfied(&cnf[index], assignment))
+            .map(|index| {
+                let mut clause: Clause = cnf[index]
+                    .iter()
+                    .filter(|literal| !assignment.contains_key(&literal.name))
+                    .cloned()
+                    .collect();
+                clause.dedup();
+                (index, clause)
+            })
+            .collect()
+    };
+
+    loop {
+        let open = open(assignment);
+        if open.iter().any(|(_, clause)| clause.is_empty()) {
+            undo(assignment, trail, start);
+            return Ok(false); // conflict
+        }
+        let Some((index, clause)) = open.into_iter().find(|(_, clause)| clause.len() == 1) else {
+            break;
+        };
+        set(
+            assignment,
+            trail,
+            clause[0].clone(),
+            Reason::Propagated(index),
+        );
+    }
+
+    let remaining: CNF = open(assignment)
+        .into_iter()
+        .map(|(_, clause)| clause)
+        .collect();
+    for literal in pure_literals(&remaining) {
+        set(assignment, trail, literal, Reason::PureLiteral);
+    }
+    // pure literals only satisfy clauses, so nothing new became unit
+    let remaining: CNF = open(assignment)
+        .into_iter()
+        .map(|(_, clause)| clause)
+        .collect();
+    let Some((name, polarity)) = pick_literal(&remaining, assignment) else {
+        return Ok(true);
+    };
+    if depth == 0 {
+        undo(assignment, trail, start);
+        return Err(TooDeep);
+    }
+
+    for value in [polarity, !polarity] {
+        let mark = trail.len();
+        let decision = Literal {
+            name: name.clone(),
+            negated: !value,
+        };
+        set(assignment, trail, decision, Reason::Decision);
+        if trail_search(cnf, assignment, trail, depth - 1)? {
+            return Ok(true);
+        }
+        undo(assignment, trail, mark);
+    }
+    undo(assignment, trail, start);
+    Ok(false)
+}
+
+// returns the first unassigned variable and the value that makes its more
+// frequent literal true
+fn pick_literal(cnf: &CNF, assignment: &Assignment) -> Option<(String, bool)> {
+    let name = &cnf
+        .iter()
+        .flatten()
+        .find(|literal| !assignment.contains_key(&literal.name))?
+        .name;
+    Some((name.clone(), polarity(cnf, name)))
+}
+
+// whether `name` occurs positively at least as often as negated
+fn polarity(cnf: &CNF, name: &str) -> bool {
+    let (mut positive, mut negative) = (0, 0);
+    for literal in cnf.iter().flatten().filter(|literal| literal.name == name) {
+        if literal.negated {
+            negative += 1;
+        } else {
+            positive += 1;
+        }
+    }
+    positive >= negative
+}
+
+/// Extends `partial` with every value forced by unit propagation, without
+/// branching. `None` means propagation ran into a clause with every literal
+/// false.
+pub fn propagate_only(cnf: &CNF, partial: &Assignment) -> Option<Assignment> {
+    let mut assignment = partial.clone();
+    let cnf = simplify(cnf.clone(), &assignment)?;
+    unit_propagate(cnf, &mut assignment)?;
+    Some(assignment)
+}
+
+/// The literals forced by unit propagation once every literal of `lits`
+/// is true, like `propagate_only` but as a list: `lits` first, each once,
+/// then the forced ones in order of first occurrence in `cnf`. `None` if
+/// `lits` contradict each other or propagation runs into a false clause.
+pub fn implied_by(cnf: &CNF, lits: &[Literal]) -> Option<Vec<Literal>> {
+    let mut partial = Assignment::new();
+    for literal in lits {
+        if *partial
+            .entry(literal.name.clone())
+            .or_insert(!literal.negated)
+            == literal.negated
+        {
+            return None;
+        }
+    }
+    let assignment = propagate_only(cnf, &partial)?;
+
+    let mut implied: Vec<Literal> = Vec::with_capacity(assignment.len());
+    let mut seen = Set::new();
+    let names = lits
+        .iter()
+        .chain(cnf.iter().flatten())
+        .map(|literal| &literal.name);
+    for name in names {
+        if let Some(&value) = assignment.get(name)
+            && seen.insert(name)
+        {
+            implied.push(Literal {
+                name: name.clone(),
+                negated: !value,
+            });
+        }
+    }
+    Some(implied)
+}
+
+pub(crate) fn unit_propagate(cnf: CNF, assignment: &mut Assignment) -> Option<CNF> {
+    // pick clause
+    let unit = cnf.iter().find_map(|clause| {
+        // pattern match literal with slice representation of clause
+        if let [literal] = &clause[..] {
+            Some(literal)
+        } else {
+            None
+        }
+    });
+
+    // shadow unwrap
+    let unit = match unit {
+        Some(lit) => lit,
+        None => return Some(cnf), // no unit clauses
+    };
+
+    // value we want to assign
+    let value_to_assign = !unit.negated;
+    if let Some(&existing) = assignment.get(&unit.name) {
+        // if existing value and value_to_assign does not match, it is unsatisfiable
+        if existing != value_to_assign {
+            return None;
+        }
+    } else {
+        assignment.insert(unit.name.clone(), value_to_assign);
+    }
+
+    // one full pass applies everything assigned so far, after that only the
+    // clauses mentioning a newly assigned variable can change
+    let mut cnf = simplify(cnf, assignment)?;
+
+    let occurrences = Occurrences::from_cnf(&cnf);
+    let mut satisfied = vec![false; cnf.len()];
+    let mut units: Vec<usize> = (0..cnf.len()).filter(|&i| cnf[i].len() == 1).collect();
+
+    while let Some(index) = units.pop() {
+        if satisfied[index] {
+            continue;
+        }
+        // every other literal of the clause was removed as false, and a
+        // clause losing its last literal ends the propagation
+        let unit = cnf[index][0].clone();
+        assignment.insert(unit.name.clone(), !unit.negated);
+
+        let positive = occurrences.of(&unit.name, false);
+        let negative = occurrences.of(&unit.name, true);
+        for &other in positive.iter().chain(negative) {
+            if satisfied[other] {
+                continue;
+            }
+            let clause = &mut cnf[other];
+            if clause.contains(&unit) {
+                satisfied[other] = true;
+                continue;
+            }
+            clause.retain(|literal| literal.name != unit.name);
+            match clause.len() {
+                0 => return None, // conflict
+                1 => units.push(other),
+                _ => {}
+            }
+        }
+    }
+
+    // in the end return the simplified formula
+    let mut satisfied = satisfied.into_iter();
+    cnf.retain(|_| !satisfied.next().unwrap());
+    Some(cnf)
+}
+
+/// The literals whose variable occurs with only one sign in `cnf`, in
+/// order of first occurrence. Making them true satisfies their clauses
+/// without falsifying any other, which is what the classic `dpll` does
+/// with them.
+pub fn pure_literals(cnf: &CNF) -> Vec<Literal> {
+    let occurrences = Occurrences::from_cnf(cnf);
+    occurrences
+        .names()
+        .filter_map(|name| {
+            let positive = !occurrences.of(name, false).is_empty();
+            let negative = !occurrences.of(name, true).is_empty();
+            (positive != negative).then(|| Literal {
+                name: name.to_string(),
+                negated: negative,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn pure_literal_eliminate(cnf: &CNF, assignment: &mut Assignment) -> Option<CNF> {
+    // assign in order of appearance rather than hash order
+    for literal in pure_literals(cnf) {
+        assignment.entry(literal.name).or_insert(!literal.negated);
+    }
+
+    simplify(cnf.clone(), assignment)
+}
+
+/// What is left of each clause of a formula under an assignment, see
+/// `apply`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyResult {
+    /// Indices of the clauses with a true literal.
+    pub satisfied: Vec<usize>,
+    /// Indices of the clauses with every literal false.
+    pub conflicting: Vec<usize>,
+    /// The other clauses in their original order, without their false
+    /// literals.
+    pub remaining: CNF,
+}
+
+/// Sorts the clauses of `cnf` by what `assignment` does to them, for
+/// stepping through examples. Unlike `simplify`, which this mirrors,
+/// nothing is dropped silently: every clause is either satisfied,
+/// conflicting or remaining. Variables missing from `assignment` are
+/// unassigned.
+pub fn apply(cnf: &CNF, assignment: &Assignment) -> ApplyResult {
+    let mut result = ApplyResult {
+        satisfied: Vec::new(),
+        conflicting: Vec::new(),
+        remaining: CNF::new(),
+    };
+    for (index, clause) in cnf.iter().enumerate() {
+        if satisfied(clause, assignment) {
+            result.satisfied.push(index);
+            continue;
+        }
+        let open: Clause = clause
+            .iter()
+            .filter(|literal| !assignment.contains_key(&literal.name))
+            .cloned()
+            .collect();
+        if open.is_empty() {
+            result.conflicting.push(index);
+        } else {
+            result.remaining.push(open);
+        }
+    }
+    result
+}
+
+/// Simplifies `cnf` under `assignment` the way the classic `dpll` does,
+/// dropping satisfied clauses and false literals, and drops the `meta` of
+/// every dropped clause along with it. `None` when a clause becomes empty.
+/// `meta` must have one entry per clause, as from `parse_with_meta`.
+pub fn simplify_with_meta(
+    cnf: CNF,
+    meta: &[ClauseMeta],
+    assignment: &Assignment,
+) -> Option<(CNF, Vec<ClauseMeta>)> {
+    assert_eq!(cnf.len(), meta.len(), "one meta entry per clause");
+    let (cnf, meta) = cnf
+        .into_iter()
+        .zip(meta)
+        .filter(|(clause, _)| !satisfied(clause, assignment))
+        .unzip();
+    Some((simplify(cnf, assignment)?, meta))
+}
+
+fn satisfied(clause: &Clause, assignment: &Assignment) -> bool {
+    clause.iter().any(|literal| {
+        match assignment.get(&literal.name) {
+            Some(&value) => value != literal.negated, // literal evaluates to true?
+            None => false,
+        }
+    })
+}
+
+pub(crate) fn simplify(mut cnf: CNF, assignment: &Assignment) -> Option<CNF> {
+    // keep only the clauses that arent satisified
+    cnf.retain(|clause| !satisfied(clause, assignment));
+
+    // remove all literals assigned false
+    for clause in cnf.iter_mut() {
+        clause.retain(|literal| match assignment.get(&literal.name) {
+            Some(&value) => value != literal.negated, // keep if literal is not false
+            None => true,
+        })
+    }
+
+    // check for empty clauses
+    for clause in &cnf {
+        if clause.is_empty() {
+            return None;
+        }
+    }
+
+    Some(cnf)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_with_meta, parse2, verify};
+    use alloc::format;
+
+    #[test]
+    fn test_formula_1() {
+        let cnf = parse2("{a}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf.clone(), &mut assignment);
+        assert!(matches!(&result, SatResult::Sat(model) if verify(&cnf, model)));
+    }
+
+    #[test]
+    fn test_formula_2() {
+        let cnf = parse2("{a},{b}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf.clone(), &mut assignment);
+        assert!(matches!(&result, SatResult::Sat(model) if verify(&cnf, model)));
+    }
+
+    #[test]
+    fn test_formula_3() {
+        let cnf = parse2("{a,b}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf.clone(), &mut assignment);
+        assert!(matches!(&result, SatResult::Sat(model) if verify(&cnf, model)));
+    }
+
+    #[test]
+    fn test_depth_limit() {
+        // every pair needs a decision of its own, and the unit a decision
+        // leaves is only picked up after the next one, so 10 pairs take 20
+        // levels
+        let pairs: Vec<String> = (0..10)
+            .map(|i| format!("{{x{i},y{i}}},{{-x{i},-y{i}}}"))
+            .collect();
+        let cnf = parse2(&pairs.join(","));
+        let run = |max_depth| dpll_with_depth_limit(cnf.clone(), &mut Assignment::new(), max_depth);
+        assert_eq!(run(19), Err(TooDeep));
+        assert!(matches!(run(20), Ok(SatResult::Sat(model)) if verify(&cnf, &model)));
+
+        // propagation alone needs no decision
+        let cnf = parse2("{a},{-a,b},{-b}");
+        assert_eq!(
+            dpll_with_depth_limit(cnf, &mut Assignment::new(), 0),
+            Ok(SatResult::Unsat)
+        );
+    }
+
+    #[cfg(not(feature = "no_std"))]
+    #[test]
+    fn test_max_depth_fits_default_thread_stack() {
+        let pairs = |count: usize| {
+            let pairs: Vec<String> = (0..count)
+                .map(|i| format!("{{x{i},y{i}}},{{-x{i},-y{i}}}"))
+                .collect();
+            parse2(&pairs.join(","))
+        };
+        // every pair takes two levels, see `test_depth_limit`
+        let deepest = pairs(DPLL_MAX_DEPTH / 2);
+        let deeper = pairs(DPLL_MAX_DEPTH / 2 + 1);
+        let thread = std::thread::Builder::new().stack_size(2 << 20);
+        let handle = thread.spawn(move || {
+            let result = dpll(deepest.clone(), &mut Assignment::new());
+            assert!(matches!(result, SatResult::Sat(model) if verify(&deepest, &model)));
+            dpll_with_depth_limit(deeper, &mut Assignment::new(), DPLL_MAX_DEPTH)
+        });
+        // one more pair than the limit is refused without overflowing
+        assert_eq!(handle.unwrap().join().unwrap(), Err(TooDeep));
+    }
+
+    #[cfg(not(feature = "no_std"))]
+    #[test]
+    #[should_panic(expected = "more than DPLL_MAX_DEPTH")]
+    fn test_dpll_panics_beyond_max_depth() {
+        let pairs: Vec<String> = (0..=DPLL_MAX_DEPTH / 2)
+            .map(|i| format!("{{x{i},y{i}}},{{-x{i},-y{i}}}"))
+            .collect();
+        dpll(parse2(&pairs.join(",")), &mut Assignment::new());
+    }
+
+    #[test]
+    fn test_formula_4() {
+        let cnf = parse2("{-a,b}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf.clone(), &mut assignment);
+        assert!(matches!(&result, SatResult::Sat(model) if verify(&cnf, model)));
+    }
+
+    #[test]
+    fn test_formula_5() {
+        let cnf = parse2("{a},{-a}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf, &mut assignment);
+        assert!(matches!(result, SatResult::Unsat));
+    }
+
+    #[test]
+    fn test_formula_6() {
+        let cnf = parse2("{a,b},{-a,c},{-b,-c}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf.clone(), &mut assignment);
+        assert!(matches!(&result, SatResult::Sat(model) if verify(&cnf, model)));
+    }
+
+    #[test]
+    fn test_formula_7() {
+        let cnf = parse2("{a,b},{b,c},{-a,-b},{-c,d}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf.clone(), &mut assignment);
+        assert!(matches!(&result, SatResult::Sat(model) if verify(&cnf, model)));
+    }
+
+    #[test]
+    fn test_formula_8() {
+        let cnf = parse2("{a,b},{-a,b},{a,-b},{-a,-b}");
+        let mut assignment = Assignment::new();
+        let result = dpll(cnf, &mut assignment);
+        assert!(matches!(result, SatResult::Unsat));
+    }
+
+    #[test]
+    fn test_solve_with_order() {
+        let order = |names: &[&str]| {
+            names
+                .iter()
+                .map(|name| name.to_string())
+                .collect::<Vec<_>>()
+        };
+        let formula_8 = parse2("{a,b},{-a,b},{a,-b},{-a,-b}");
+        let mut trace = Vec::new();
+        assert_eq!(
+            solve_traced(formula_8.clone(), &order(&["b", "a"]), &mut trace),
+            None
+        );
+        let branches = |trace: &[(String, bool)]| {
+            trace
+                .iter()
+                .map(|(name, value)| format!("{}{name}", if *value { "" } else { "-" }))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        // every branch fails only once both variables are set
+        assert_eq!(branches(&trace), "b a -a -b a -a");
+        trace.clear();
+        solve_traced(formula_8, &[], &mut trace);
+        assert_eq!(branches(&trace), "a b -b -a b -b");
+
+        // `d` is pure, so it is set without a decision
+        let formula_7 = parse2("{a,b},{b,c},{-a,-b},{-c,d}");
+        let mut trace = Vec::new();
+        let model = solve_traced(formula_7.clone(), &order(&["d", "c", "b"]), &mut trace).unwrap();
+        assert_eq!(branches(&trace), "c b -a");
+        assert!(verify(&formula_7, &model));
+        // unknown names are skipped
+        let mut unordered = Vec::new();
+        solve_traced(formula_7.clone(), &order(&["x"]), &mut unordered);
+        trace.clear();
+        solve_traced(formula_7, &[], &mut trace);
+        assert_eq!(unordered, trace);
+    }
+
+    #[test]
+    fn test_pick_literal_prefers_frequent_polarity() {
+        let assignment = Assignment::new();
+        let cnf = parse2("{-a,b},{-a,c},{a,-c}");
+        assert_eq!(
+            pick_literal(&cnf, &assignment),
+            Some(("a".to_string(), false))
+        );
+
+        let cnf = parse2("{b,a},{-b,c},{b,-c}");
+        assert_eq!(
+            pick_literal(&cnf, &assignment),
+            Some(("b".to_string(), true))
+        );
+
+        let mut assignment = Assignment::new();
+        assignment.insert("b".to_string(), true);
+        assert_eq!(
+            pick_literal(&cnf, &assignment),
+            Some(("a".to_string(), true))
+        );
+    }
+
+    #[test]
+    fn test_simplify_removes_false_literals() {
+        let mut assignment = Assignment::new();
+        assignment.insert("a".to_string(), false);
+        let cnf = simplify(parse2("{a,b},{-a,c}"), &assignment).unwrap();
+        assert_eq!(cnf, parse2("{b}"));
+    }
+
+    #[test]
+    fn test_implied_by() {
+        let lit = |name: &str| Literal {
+            name: name.trim_start_matches('-').to_string(),
+            negated: name.starts_with('-'),
+        };
+        let cnf = parse2("{-a,b},{-b,c}");
+        assert_eq!(
+            implied_by(&cnf, &[lit("a")]),
+            Some(vec![lit("a"), lit("b"), lit("c")])
+        );
+        assert_eq!(
+            implied_by(&cnf, &[lit("-c")]),
+            Some(vec![lit("-c"), lit("-a"), lit("-b")])
+        );
+        assert_eq!(
+            implied_by(&cnf, &[lit("b"), lit("b")]),
+            Some(vec![lit("b"), lit("c")])
+        );
+        assert_eq!(implied_by(&cnf, &[lit("a"), lit("-c")]), None);
+        assert_eq!(implied_by(&cnf, &[lit("a"), lit("-a")]), None);
+        assert_eq!(implied_by(&cnf, &[]), Some(vec![]));
+    }
+
+    #[test]
+    fn test_apply() {
+        let cnf = parse2("{a,b},{-a,c}");
+        let mut assignment = Assignment::new();
+        assignment.insert("a".to_string(), true);
+        assert_eq!(
+            apply(&cnf, &assignment),
+            ApplyResult {
+                satisfied: vec![0],
+                conflicting: vec![],
+                remaining: parse2("{c}"),
+            }
+        );
+
+        assignment.insert("c".to_string(), false);
+        let result = apply(&cnf, &assignment);
+        assert_eq!(
+            (result.conflicting, result.remaining),
+            (vec![1], CNF::new())
+        );
+        assert_eq!(apply(&cnf, &Assignment::new()).remaining, cnf);
+    }
+
+    #[test]
+    fn test_simplify_with_meta() {
+        let (cnf, meta) = parse_with_meta("p cnf 3 3\n1 2 0\n-1 3 0\n2 -3 0\n").unwrap();
+        let mut assignment = Assignment::new();
+        assignment.insert("1".to_string(), false);
+        let (cnf, meta) = simplify_with_meta(cnf, &meta, &assignment).unwrap();
+        assert_eq!(cnf, parse2("{2},{2,-3}"));
+        assert_eq!(
+            meta,
+            [
+                ClauseMeta { index: 0, line: 2 },
+                ClauseMeta { index: 2, line: 4 }
+            ]
+        );
+
+        assignment.insert("2".to_string(), false);
+        assert_eq!(simplify_with_meta(cnf, &meta, &assignment), None);
+    }
+
+    #[test]
+    fn test_trace_first_conflict() {
+        // deciding `a` leaves the units `c` and `-c`, which `dpll` only
+        // looks for after its next decision
+        let cnf = parse2("{a,b},{a,-b},{-a,c},{-a,-c}");
+        let (decisions, clause) = trace_first_conflict(cnf).unwrap();
+        assert_eq!(
+            decisions,
+            [("a".to_string(), true), ("c".to_string(), true)]
+        );
+        assert_eq!(clause, parse2("{-a,-c}")[0]);
+
+        // conflicts without any decision
+        let (decisions, clause) = trace_first_conflict(parse2("{a},{-a}")).unwrap();
+        assert!(decisions.is_empty());
+        assert_eq!(clause, parse2("{-a}")[0]);
+        assert_eq!(trace_first_conflict(vec![vec![]]), Some((vec![], vec![])));
+
+        assert_eq!(trace_first_conflict(parse2("{a,b},{-a,b}")), None);
+    }
+
+    #[test]
+    fn test_dpll_trail() {
+        let trail = |formula: &str| -> Option<Vec<(String, Reason)>> {
+            let trail = dpll_trail(&parse2(formula))?;
+            Some(
+                trail
+                    .into_iter()
+                    .map(|(literal, reason)| (literal.to_string(), reason))
+                    .collect(),
+            )
+        };
+        let entry = |literal: &str, reason| (literal.to_string(), reason);
+
+        assert_eq!(
+            trail("{a},{-a,b}").unwrap(),
+            [
+                entry("a", Reason::Propagated(0)),
+                entry("b", Reason::Propagated(1))
+            ]
+        );
+        // `a` occurs with both signs, `b` and `c` with one
+        assert_eq!(
+            trail("{a,b},{-a,b},{a,-c}").unwrap(),
+            [
+                entry("b", Reason::PureLiteral),
+                entry("-c", Reason::PureLiteral)
+            ]
+        );
+        // `a` true runs into `c` and `-c`, and that branch is left out
+        assert_eq!(
+            trail("{a,b},{a,-b,c},{-a,c},{-a,-c},{-c,b}").unwrap(),
+            [
+                entry("-a", Reason::Decision),
+                entry("b", Reason::Propagated(0)),
+                entry("c", Reason::Propagated(1))
+            ]
+        );
+        assert_eq!(trail("{a},{-a}"), None);
+        assert_eq!(trail("{a,b},{a,-b},{-a,c},{-a,-c}"), None);
+        assert_eq!(dpll_trail(&CNF::new()), Some(vec![]));
+    }
+
+    #[test]
+    fn test_propagate_only() {
+        let forced = propagate_only(&parse2("{a},{-a,b}"), &Assignment::new()).unwrap();
+        assert_eq!(forced.get("a"), Some(&true));
+        assert_eq!(forced.get("b"), Some(&true));
+        assert_eq!(forced.len(), 2);
+
+        // nothing is decided, `b` and `c` stay open
+        let cnf = parse2("{-a,b,c},{-b,d}");
+        let mut partial = Assignment::new();
+        partial.insert("a".to_string(), true);
+        assert_eq!(propagate_only(&cnf, &partial), Some(partial.clone()));
+
+        partial.insert("c".to_string(), false);
+        let forced = propagate_only(&cnf, &partial).unwrap();
+        assert_eq!((forced["b"], forced["d"]), (true, true));
+
+        partial.insert("d".to_string(), false);
+        assert_eq!(propagate_only(&cnf, &partial), None);
+    }
+
+    #[test]
+    fn test_model_keeps_variables_of_satisfied_clauses() {
+        let SatResult::Sat(model) = dpll(parse2("{a,b},{a}"), &mut Assignment::new()) else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.get("a"), Some(&true));
+        assert!(model.contains_key("b"));
+    }
+
+    #[test]
+    fn test_model_is_total() {
+        let cnf = parse2("{a},{a,b},{-c,a}");
+        let mut assignment = Assignment::new();
+        let SatResult::Sat(model) = dpll(cnf, &mut assignment) else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.len(), 3);
+        assert_eq!(model.get("a"), Some(&true));
+        assert!(model.contains_key("b"));
+        assert!(model.contains_key("c"));
+    }
+
+    #[test]
+    fn test_pure_literal_eliminate() {
+        let cnf = parse2("{a,b},{a,c}");
+        let mut assignment = Assignment::new();
+        let cnf = pure_literal_eliminate(&cnf, &mut assignment).unwrap();
+        assert!(cnf.is_empty());
+        assert_eq!(assignment.get("a"), Some(&true));
+    }
+
+    #[test]
+    fn test_pure_literals() {
+        let cnf = parse2("{a,b},{a,c}");
+        assert_eq!(pure_literals(&cnf), parse2("{a,b,c}").remove(0));
+        assert_eq!(cnf, parse2("{a,b},{a,c}"));
+        assert_eq!(
+            pure_literals(&parse2("{-a,b},{a,-c},{-b,-c}")),
+            parse2("{-c}").remove(0)
+        );
+        assert!(pure_literals(&CNF::new()).is_empty());
+    }
+
+    #[test]
+    fn test_pure_literal_negative() {
+        let cnf = parse2("{-a,b},{-a,-b}");
+        let mut assignment = Assignment::new();
+        let cnf = pure_literal_eliminate(&cnf, &mut assignment).unwrap();
+        assert!(cnf.is_empty());
+        assert_eq!(assignment.get("a"), Some(&false));
+    }
+
+    #[test]
+    fn test_unit_propagate_chain() {
+        // `a` forces `b`, which forces `c`, leaving `{d,e}` untouched
+        let cnf = parse2("{a},{-a,b},{-b,c},{-c,a,d},{d,e}");
+        let mut assignment = Assignment::new();
+        let cnf = unit_propagate(cnf, &mut assignment).unwrap();
+        assert_eq!(cnf, parse2("{d,e}"));
+        assert_eq!(assignment.len(), 3);
+        assert_eq!(assignment.get("c"), Some(&true));
+
+        let cnf = parse2("{a},{-a,b},{-b,-a}");
+        assert_eq!(unit_propagate(cnf, &mut Assignment::new()), None);
+    }
+
+    #[test]
+    fn test_unit_propagate_long_chain() {
+        // rescanning the formula after every assignment would take
+        // quadratic time here. the implications are listed last to first,
+        // so every assignment makes a clause further back unit
+        let n = 20_000;
+        let literal = |i: usize, negated| Literal {
+            name: i.to_string(),
+            negated,
+        };
+        let mut cnf: CNF = (1..n)
+            .rev()
+            .map(|i| vec![literal(i - 1, true), literal(i, false)])
+            .collect();
+        cnf.push(vec![literal(0, false)]);
+
+        let mut assignment = Assignment::new();
+        let rest = unit_propagate(cnf.clone(), &mut assignment).unwrap();
+        assert!(rest.is_empty());
+        assert_eq!(assignment.len(), n);
+        assert!(assignment.values().all(|&value| value));
+
+        cnf.push(vec![literal(n - 1, true)]);
+        assert_eq!(unit_propagate(cnf, &mut Assignment::new()), None);
+    }
+}
@@ -0,0 +1,135 @@
+//! A C interface for linking the solver into other languages. Variables
+//! are identified by their DIMACS numbers. The shared library is built with
+//!
+//! ```text
+//! cargo rustc --lib --release --crate-type cdylib
+//! ```
+//!
+//! ```c
+//! typedef struct SatHandle SatHandle;
+//!
+//! SatHandle *sat_solver_from_dimacs(const char *input);
+//! int sat_solver_solve(SatHandle *handle);
+//! int sat_solver_value(const SatHandle *handle, int var);
+//! void sat_solver_free(SatHandle *handle);
+//! ```
+
+use crate::{Assignment, Solver, parse_dimacs};
+use alloc::{boxed::Box, string::ToString};
+use core::ffi::{CStr, c_char, c_int};
+
+/// `sat_solver_solve` results, the exit codes used by SAT competition
+/// solvers.
+pub const SAT_SOLVER_SAT: c_int = 10;
+pub const SAT_SOLVER_UNSAT: c_int = 20;
+
+/// A formula loaded through the C interface, and its model once solved.
+pub struct SatHandle {
+    solver: Solver,
+    model: Option<Assignment>,
+}
+
+/// Parses a NUL terminated DIMACS string into a new handle. Returns null if
+/// the input is not valid UTF-8 or not valid DIMACS. The caller owns the
+/// handle and releases it with `sat_solver_free`. `input` is only read
+/// during the call.
+///
+/// # Safety
+///
+/// `input` must point to a NUL terminated string.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sat_solver_from_dimacs(input: *const c_char) -> *mut SatHandle {
+    if input.is_null() {
+        return core::ptr::null_mut();
+    }
+    let input = unsafe { CStr::from_ptr(input) };
+    let Some(cnf) = input.to_str().ok().and_then(|text| parse_dimacs(text).ok()) else {
+        return core::ptr::null_mut();
+    };
+    Box::into_raw(Box::new(SatHandle {
+        solver: Solver::new(cnf),
+        model: None,
+    }))
+}
+
+/// Solves the formula, returning `SAT_SOLVER_SAT` or `SAT_SOLVER_UNSAT`,
+/// or -1 for a null handle.
+///
+/// # Safety
+///
+/// `handle` must be null or come from `sat_solver_from_dimacs` and not have
+/// been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sat_solver_solve(handle: *mut SatHandle) -> c_int {
+    let Some(handle) = (unsafe { handle.as_mut() }) else {
+        return -1;
+    };
+    handle.model = handle.solver.solve_under(&[]);
+    match handle.model {
+        Some(_) => SAT_SOLVER_SAT,
+        None => SAT_SOLVER_UNSAT,
+    }
+}
+
+/// The value of DIMACS variable `var` in the model of the last solve: 1
+/// for true, 0 for false, and -1 when there is no model or the variable
+/// does not occur in the formula.
+///
+/// # Safety
+///
+/// `handle` must be null or come from `sat_solver_from_dimacs` and not have
+/// been freed.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sat_solver_value(handle: *const SatHandle, var: c_int) -> c_int {
+    let model = unsafe { handle.as_ref() }.and_then(|handle| handle.model.as_ref());
+    match model.and_then(|model| model.get(&var.to_string())) {
+        Some(&value) => value as c_int,
+        None => -1,
+    }
+}
+
+/// Releases a handle. Null is ignored.
+///
+/// # Safety
+///
+/// `handle` must be null or come from `sat_solver_from_dimacs`, and must
+/// not be used afterwards.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn sat_solver_free(handle: *mut SatHandle) {
+    if !handle.is_null() {
+        drop(unsafe { Box::from_raw(handle) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::CString;
+
+    #[test]
+    fn test_ffi() {
+        let input = CString::new("p cnf 3 2\n1 2 0\n-1 0\n").unwrap();
+        unsafe {
+            let handle = sat_solver_from_dimacs(input.as_ptr());
+            assert!(!handle.is_null());
+            assert_eq!(sat_solver_value(handle, 1), -1);
+            assert_eq!(sat_solver_solve(handle), SAT_SOLVER_SAT);
+            assert_eq!(sat_solver_value(handle, 1), 0);
+            assert_eq!(sat_solver_value(handle, 2), 1);
+            assert_eq!(sat_solver_value(handle, 3), -1);
+            sat_solver_free(handle);
+
+            let input = CString::new("p cnf 1 2\n1 0\n-1 0\n").unwrap();
+            let handle = sat_solver_from_dimacs(input.as_ptr());
+            assert_eq!(sat_solver_solve(handle), SAT_SOLVER_UNSAT);
+            assert_eq!(sat_solver_value(handle, 1), -1);
+            sat_solver_free(handle);
+
+            let input = CString::new("1 2 0").unwrap();
+            assert!(sat_solver_from_dimacs(input.as_ptr()).is_null());
+            assert!(sat_solver_from_dimacs(std::ptr::null()).is_null());
+            assert_eq!(sat_solver_solve(std::ptr::null_mut()), -1);
+            sat_solver_free(std::ptr::null_mut());
+        }
+    }
+}
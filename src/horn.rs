@@ -0,0 +1,322 @@
+use crate::dpll::simplify;
+use crate::preprocess::components;
+use crate::{Assignment, CNF, Set, VarMap};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Whether every clause of `cnf` has at most one positive literal.
+pub fn is_horn(cnf: &CNF) -> bool {
+    cnf.iter()
+        .all(|clause| clause.iter().filter(|literal| !literal.negated).count() <= 1)
+}
+
+/// Solves a Horn formula without branching, in time linear in its size.
+/// Every variable starts false and is only made true when a clause forces
+/// it, so the model found is the least one: each of its true variables is
+/// true in every model. `None` means the formula is unsatisfiable.
+///
+/// Panics if `cnf` is not Horn.
+pub fn solve_horn(cnf: &CNF) -> Option<Assignment> {
+    forward_chain(cnf).0
+}
+
+/// The variables whose signs can be flipped everywhere to make `cnf` a
+/// Horn formula, in order of first occurrence, or `None` if no choice
+/// does. A Horn formula needs no flips. Such renamable Horn formulas are
+/// solved as fast as Horn ones, see `solve`.
+///
+/// Whether a variable is flipped is found with 2-SAT: in every clause at
+/// most one literal may end up positive, which the sequential counter
+/// expresses in clauses of two literals, and those are solved through the
+/// components of their implication graph in linear time.
+pub fn is_renamable_horn(cnf: &CNF) -> Option<Vec<String>> {
+    if is_horn(cnf) {
+        return Some(Vec::new());
+    }
+    let vars = VarMap::from_cnf(cnf);
+    // nodes are the literals of the 2-SAT formula, `2 * v` true when
+    // variable `v` is flipped and `2 * v + 1` its negation, followed by
+    // the counter variables
+    let mut edges: Vec<Vec<usize>> = vec![Vec::new(); vars.len() * 2];
+    let implies = |edges: &mut Vec<Vec<usize>>, a: usize, b: usize| {
+        edges[a].push(b);
+        edges[b ^ 1].push(a ^ 1);
+    };
+    for clause in cnf {
+        // a literal ends up positive unless its sign is flipped
+        let positive: Vec<usize> = clause
+            .iter()
+            .map(|literal| {
+                let var = vars.id(&literal.name).expect("interned from cnf") as usize;
+                2 * var + !literal.negated as usize
+            })
+            .collect();
+        let Some((last, rest)) = positive.split_last() else {
+            continue;
+        };
+        // `counter` is true once one of the literals so far is positive
+        let mut counter = None;
+        for &p in rest {
+            let next = edges.len();
+            edges.extend([Vec::new(), Vec::new()]);
+            implies(&mut edges, p, next);
+            if let Some(counter) = counter {
+                implies(&mut edges, counter, next);
+                implies(&mut edges, p, counter ^ 1);
+            }
+            counter = Some(next);
+        }
+        if let Some(counter) = counter {
+            implies(&mut edges, *last, counter ^ 1);
+        }
+    }
+
+    // a literal is true when its component comes after its negation's
+    let component = components(&edges);
+    let mut flips = Vec::new();
+    for (var, name) in vars.names().enumerate() {
+        let (flipped, kept) = (component[2 * var], component[2 * var + 1]);
+        if flipped == kept {
+            return None;
+        }
+        if flipped > kept {
+            flips.push(name.to_string());
+        }
+    }
+    Some(flips)
+}
+
+// solves a formula that `flips` turns into a Horn formula
+pub(crate) fn solve_renamed(cnf: &CNF, flips: &[String]) -> Option<Assignment> {
+    let flips: Set<&str> = flips.iter().map(String::as_str).collect();
+    let flipped = |name: &str| flips.contains(name);
+    let mut renamed = cnf.clone();
+    for literal in renamed.iter_mut().flatten() {
+        literal.negated ^= flipped(&literal.name);
+    }
+    let mut model = solve_horn(&renamed)?;
+    for (name, value) in model.iter_mut() {
+        *value ^= flipped(name);
+    }
+    Some(model)
+}
+
+/// The smallest set of at most `max_size` variables that is a strong
+/// backdoor of `cnf` to Horn or 2-SAT: under every assignment to them,
+/// what is left of the formula is Horn or has no clause longer than two,
+/// both solvable without search. Sets are tried by size and then in order
+/// of first occurrence, so the first smallest one is returned, and `None`
+/// if there is none up to `max_size`. An assignment that falsifies a
+/// clause leaves nothing to solve and counts as well.
+///
+/// Every set of a size is tried with all its assignments, so this takes
+/// time exponential in `max_size` and is only meant for small sizes.
+pub fn find_backdoor(cnf: &CNF, max_size: usize) -> Option<Vec<String>> {
+    let map = VarMap::from_cnf(cnf);
+    let vars: Vec<&str> = map.names().collect();
+    let easy = |cnf: &CNF| is_horn(cnf) || cnf.iter().all(|clause| clause.len() <= 2);
+    let backdoor = |set: &[usize]| {
+        (0..1u64 << set.len()).all(|bits| {
+            let assignment: Assignment = set
+                .iter()
+                .enumerate()
+                .map(|(i, &var)| (vars[var].to_string(), bits >> i & 1 == 1))
+                .collect();
+            simplify(cnf.clone(), &assignment).is_none_or(|residual| easy(&residual))
+        })
+    };
+
+    for size in 0..=max_size.min(vars.len()) {
+        // the indices of the set, increasing, advanced like an odometer
+        let mut set: Vec<usize> = (0..size).collect();
+        loop {
+            if backdoor(&set) {
+                return Some(set.iter().map(|&var| vars[var].to_string()).collect());
+            }
+            let Some(i) = (0..size).rev().find(|&i| set[i] < vars.len() - size + i) else {
+                break;
+            };
+            set[i] += 1;
+            for j in i + 1..size {
+                set[j] = set[j - 1] + 1;
+            }
+        }
+    }
+    None
+}
+
+// the least model if there is one, and how many variables were forced
+// true
+pub(crate) fn forward_chain(cnf: &CNF) -> (Option<Assignment>, u64) {
+    assert!(
+        is_horn(cnf),
+        "the formula has a clause with two positive literals"
+    );
+    let vars = VarMap::from_cnf(cnf);
+    let id = |name: &str| vars.id(name).expect("interned above") as usize;
+
+    // a clause fires once all its negative literals are false, that is
+    // once all their variables are true
+    let mut waiting: Vec<usize> = Vec::with_capacity(cnf.len());
+    let mut watchers: Vec<Vec<usize>> = vec![Vec::new(); vars.len()];
+    let mut fired = Vec::new();
+    for (index, clause) in cnf.iter().enumerate() {
+        let negatives = clause.iter().filter(|literal| literal.negated);
+        waiting.push(negatives.clone().count());
+        for literal in negatives {
+            watchers[id(&literal.name)].push(index);
+        }
+        if waiting[index] == 0 {
+            fired.push(index);
+        }
+    }
+
+    let mut values = vec![false; vars.len()];
+    let mut forced = 0;
+    while let Some(index) = fired.pop() {
+        let Some(head) = cnf[index].iter().find(|literal| !literal.negated) else {
+            return (None, forced); // every literal of a goal clause is false
+        };
+        let var = id(&head.name);
+        if values[var] {
+            continue;
+        }
+        values[var] = true;
+        forced += 1;
+        for &other in &watchers[var] {
+            waiting[other] -= 1;
+            if waiting[other] == 0 {
+                fired.push(other);
+            }
+        }
+    }
+
+    let model = vars
+        .names()
+        .zip(values)
+        .map(|(name, value)| (name.into(), value))
+        .collect();
+    (Some(model), forced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, parse2, solve, solve_with_stats, verify};
+
+    #[test]
+    fn test_is_horn() {
+        assert!(is_horn(&parse2("{-a,-b,c},{a},{-c}")));
+        assert!(is_horn(&CNF::new()));
+        assert!(!is_horn(&parse2("{a,b},{-a}")));
+    }
+
+    #[test]
+    fn test_solve_horn() {
+        // a and b are facts, so c and d follow, and e is never forced
+        let cnf = parse2("{a},{-a,b},{-a,-b,c},{-c,d},{-e,a},{-d,-e}");
+        let model = solve_horn(&cnf).unwrap();
+        assert!(verify(&cnf, &model));
+        assert!(model["a"] && model["b"] && model["c"] && model["d"]);
+        assert!(!model["e"]);
+        assert_eq!(solve_horn(&parse2("{a},{-a,b},{-a,-b}")), None);
+        assert_eq!(
+            solve_horn(&parse2("{-a,a},{-b}")).map(|model| model.len()),
+            Some(2)
+        );
+
+        let (model, stats) = solve_with_stats(cnf.clone());
+        assert!(verify(&cnf, &model.unwrap()));
+        assert_eq!(stats.decisions, 0);
+        assert_eq!(stats.propagations, 4);
+        assert_eq!(solve(parse2("{a},{-a,b},{-b}")), SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_is_renamable_horn() {
+        let renamed = |cnf: &CNF, flips: &[String]| -> CNF {
+            let mut cnf = cnf.clone();
+            for literal in cnf.iter_mut().flatten() {
+                literal.negated ^= flips.contains(&literal.name);
+            }
+            cnf
+        };
+
+        // only flipping `b` leaves a single positive literal in each clause
+        let cnf = parse2("{a,b},{-a,c},{-b,-c,-a},{b,-d}");
+        let flips = is_renamable_horn(&cnf).unwrap();
+        assert_eq!(flips, ["b"]);
+        assert!(is_horn(&renamed(&cnf, &flips)));
+        assert!(!is_horn(&cnf));
+        let SatResult::Sat(model) = solve(cnf.clone()) else {
+            panic!("satisfiable");
+        };
+        assert!(verify(&cnf, &model));
+
+        assert_eq!(is_renamable_horn(&parse2("{-a,-b,c},{a}")), Some(vec![]));
+        // whichever signs flip, one of the clauses keeps two positives
+        assert_eq!(is_renamable_horn(&parse2("{a,b,c},{-a,-b,-c}")), None);
+
+        for seed in 0..100 {
+            let cnf = crate::random_3sat(12, 14, seed);
+            if let Some(flips) = is_renamable_horn(&cnf) {
+                assert!(is_horn(&renamed(&cnf, &flips)), "seed {seed}");
+                let result = solve(cnf.clone());
+                let searched = crate::Solver::new(cnf.clone()).solve_under(&[]);
+                assert_eq!(matches!(result, SatResult::Sat(_)), searched.is_some());
+                if let SatResult::Sat(model) = result {
+                    assert!(verify(&cnf, &model), "seed {seed}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_horn_agrees_with_search() {
+        // random 3-SAT clauses with all but at most one literal negated
+        for seed in 0..50 {
+            let mut cnf = crate::random_3sat(10, 30, seed);
+            for clause in &mut cnf {
+                for literal in clause.iter_mut().skip(1) {
+                    literal.negated = true;
+                }
+            }
+            let horn = solve_horn(&cnf);
+            if let Some(model) = &horn {
+                assert!(verify(&cnf, model), "seed {seed}");
+            }
+            let searched = crate::solver::Solver::new(cnf).solve();
+            assert_eq!(
+                horn.is_some(),
+                matches!(searched, SatResult::Sat(_)),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_find_backdoor() {
+        // true leaves a Horn clause, false two binary ones
+        let gadget = "{x,a,b},{x,c,d},{-x,-a,-b}";
+        let cnf = parse2(gadget);
+        assert_eq!(find_backdoor(&cnf, 0), None);
+        assert_eq!(find_backdoor(&cnf, 3), Some(vec!["x".to_string()]));
+
+        let two = parse2(&format!(
+            "{gadget},{}",
+            gadget.replace('x', "y").replace('a', "e")
+        ));
+        assert_eq!(find_backdoor(&two, 1), None);
+        assert_eq!(
+            find_backdoor(&two, 2),
+            Some(vec!["x".to_string(), "y".to_string()])
+        );
+        assert_eq!(
+            find_backdoor(&parse2("{a,-b,-c},{-a,b}"), 2),
+            Some(Vec::new())
+        );
+    }
+}
@@ -0,0 +1,103 @@
+use crate::parse::dimacs_literal;
+use crate::{Assignment, Clause, ParseError, Solver};
+use alloc::vec::Vec;
+
+/// Runs an incremental DIMACS (`.icnf`) script on one `Solver` and returns
+/// the result of every solve in order, `None` for unsatisfiable.
+///
+/// After an optional `p inccnf` header, clause lines add clauses for good
+/// and `a <lits> 0` lines collect assumptions. An `i` line ends an
+/// increment and solves under the assumptions collected since the previous
+/// one. Scripts without `i` lines, as most benchmark files are, solve at
+/// every `a` line instead.
+pub fn solve_icnf(input: &str) -> Result<Vec<Option<Assignment>>, ParseError> {
+    let explicit_increments = input.lines().any(|line| line.trim() == "i");
+
+    let mut solver = Solver::new(Vec::new());
+    let mut results = Vec::new();
+    let mut clause = Clause::new();
+    let mut assumptions = Clause::new();
+    for (index, line) in input.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+        if line.starts_with('p') {
+            if line.split_whitespace().collect::<Vec<_>>() != ["p", "inccnf"] {
+                return Err(ParseError::InvalidHeader { line: line_no });
+            }
+            continue;
+        }
+        if line == "i" {
+            results.push(solver.solve_under(&assumptions));
+            assumptions.clear();
+            continue;
+        }
+
+        if let Some(lits) = line.strip_prefix("a ") {
+            for token in lits.split_whitespace() {
+                match dimacs_literal(token, line_no, None)? {
+                    Some(literal) => assumptions.push(literal),
+                    None => break,
+                }
+            }
+            if !explicit_increments {
+                results.push(solver.solve_under(&assumptions));
+                assumptions.clear();
+            }
+            continue;
+        }
+
+        // a clause may span several lines, only `0` terminates it
+        for token in line.split_whitespace() {
+            match dimacs_literal(token, line_no, None)? {
+                Some(literal) => clause.push(literal),
+                None => solver.add_clause(core::mem::take(&mut clause)),
+            }
+        }
+    }
+
+    // a clause left open at the end comes after the last solve
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn verdicts(input: &str) -> Vec<bool> {
+        solve_icnf(input)
+            .unwrap()
+            .iter()
+            .map(Option::is_some)
+            .collect()
+    }
+
+    #[test]
+    fn test_solve_icnf() {
+        // every `a` line is a solve
+        let input = "p inccnf\n1 2 0\na -1 0\na -1 -2 0\n-2 0\na 0\n";
+        assert_eq!(verdicts(input), [true, false, true]);
+
+        let results = solve_icnf(input).unwrap();
+        let model = results[2].as_ref().unwrap();
+        assert_eq!((model["1"], model["2"]), (true, false));
+
+        // with `i` lines assumptions add up until the increment ends
+        let input = "1 2 0\na -1 0\na -2 0\ni\ni\n-1 0\ni\n-2 0\ni\n";
+        assert_eq!(verdicts(input), [false, true, true, false]);
+    }
+
+    #[test]
+    fn test_solve_icnf_errors() {
+        assert_eq!(
+            solve_icnf("p cnf 1 1\n1 0\n"),
+            Err(ParseError::InvalidHeader { line: 1 })
+        );
+        assert!(matches!(
+            solve_icnf("1 x 0\n"),
+            Err(ParseError::InvalidLiteral { line: 1, .. })
+        ));
+    }
+}
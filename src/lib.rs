@@ -0,0 +1,680 @@
+//! A small DPLL based SAT solver.
+//!
+//! Formulas are given in conjunctive normal form, either built by hand or
+//! parsed from the `{a,-b},{c}` brace syntax or DIMACS.
+//!
+//! With the `no_std` feature the crate only depends on `alloc`, and
+//! assignments are ordered maps instead of hash maps. Solving with a time
+//! limit needs a clock and is left out.
+//!
+//! The `serde` feature adds serde impls for formulas and models, and lets
+//! `Solver::save_state` checkpoint a solver with bincode.
+
+#![cfg_attr(all(feature = "no_std", not(test)), no_std)]
+
+extern crate alloc;
+
+mod bdd;
+mod builder;
+mod cardinality;
+mod compose;
+mod config;
+mod count;
+mod cube;
+mod deps;
+mod dimacs;
+mod dpll;
+pub mod ffi;
+mod horn;
+mod icnf;
+mod maxsat;
+mod minimal;
+mod observer;
+mod occurs;
+mod parse;
+mod preprocess;
+mod proof;
+mod random;
+mod solver;
+#[cfg(feature = "serde")]
+mod state;
+mod stats;
+mod symmetry;
+mod tseitin;
+mod varmap;
+#[cfg(feature = "wasm")]
+mod wasm;
+mod xor;
+
+#[cfg(feature = "no_std")]
+pub(crate) use alloc::collections::{BTreeMap as Map, BTreeSet as Set};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+#[cfg(not(feature = "no_std"))]
+pub(crate) use std::collections::{HashMap as Map, HashSet as Set};
+
+pub use bdd::{BDD_MAX_VARS, count_bdd, solve_bdd};
+pub use builder::{ClauseBuilder, CnfBuilder, VarFamily, model_bits};
+pub use cardinality::{Cardinality, CardinalityKind, encode_at_most_k, exactly_one};
+pub use compose::disjoint_union;
+pub use config::{
+    Algorithm, ClauseDeletion, Heuristic, Limit, RestartPolicy, SolverConfig, SolverLimits,
+};
+pub use count::{
+    all_models, blocking_clause, covering_models, model_count, sample_models, solve_all,
+    weighted_model_count,
+};
+pub use cube::{generate_cubes, solve_cube};
+pub use deps::{conflicts, requires};
+pub use dimacs::{model_to_value_lines, renumber, to_dimacs};
+pub use dpll::{
+    ApplyResult, DPLL_MAX_DEPTH, TooDeep, apply, dpll, dpll_trail, dpll_with_depth_limit,
+    implied_by, propagate_only, pure_literals, simplify_with_meta, solve_with_order,
+    trace_first_conflict,
+};
+pub use horn::{find_backdoor, is_horn, is_renamable_horn, solve_horn};
+pub use icnf::solve_icnf;
+pub use maxsat::{lex_max_sat, max_sat, max_sat_with_progress};
+pub use minimal::{min_weight_model, minimal_model, prime_implicant};
+pub use observer::SolverObserver;
+#[cfg(not(feature = "no_std"))]
+pub use parse::parse_dimacs_reader;
+pub use parse::{
+    ClauseMeta, ParseError, Warning, parse_dimacs, parse_formula, parse_many, parse_sugar,
+    parse_wcnf, parse_with_diagnostics, parse_with_meta, parse2, parse2_checked,
+};
+pub use preprocess::{
+    Canonical, PreprocessPass, PureLiterals, RemoveTautologies, Subsume, UnitPropagate,
+    canonicalize, eliminate_blocked, eliminate_vars, literal_equivalences, preprocess,
+    preprocess_with, probe, project, remove_tautologies, resolve, subsume,
+};
+pub use proof::{ProofStep, check_drat, proof_to_drat};
+pub use random::random_3sat;
+pub use solver::{
+    Entailment, LimitedResult, Reason, Refutation, Solver, Timeout, entails, redundant_clauses,
+    solve_assuming, solve_with_budget, solve_with_config, solve_with_core, solve_with_core_meta,
+    solve_with_fixed, solve_with_limits, solve_with_observer, solve_with_oracle, solve_with_proof,
+    solve_with_refutation, solve_with_stats, solve_with_xor,
+};
+#[cfg(not(feature = "no_std"))]
+pub use solver::{solve_timed, solve_with_timeout};
+#[cfg(feature = "serde")]
+pub use state::SolverError;
+pub use stats::{FormulaStats, Stats, StructureHint, formula_stats, structure_hint};
+pub use symmetry::add_symmetry_breaking;
+pub use tseitin::{BoolExpr, detect_definitions, tseitin};
+pub use varmap::VarMap;
+#[cfg(feature = "wasm")]
+pub use wasm::solve_json;
+pub use xor::{XorClause, extract_xors};
+
+/// Literals order by name, then the positive one first.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Literal {
+    pub name: String,
+    pub negated: bool,
+}
+impl core::fmt::Display for Literal {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.negated {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.name)
+    }
+}
+
+impl Literal {
+    /// The literal of the same variable with the other sign.
+    pub fn negate(&self) -> Literal {
+        Literal {
+            name: self.name.clone(),
+            negated: !self.negated,
+        }
+    }
+}
+
+/// A DIMACS literal: `k` is the variable named `k` and `-k` its negation,
+/// matching the names `parse_dimacs` gives. Panics on `0`, which DIMACS
+/// uses to end a clause.
+impl From<i32> for Literal {
+    fn from(value: i32) -> Self {
+        assert_ne!(value, 0, "0 is not a DIMACS literal");
+        Literal {
+            name: value.unsigned_abs().to_string(),
+            negated: value < 0,
+        }
+    }
+}
+
+/// A name and whether it is negated, in the order of the fields.
+impl From<(&str, bool)> for Literal {
+    fn from((name, negated): (&str, bool)) -> Self {
+        Literal {
+            name: name.to_string(),
+            negated,
+        }
+    }
+}
+
+pub type Clause = Vec<Literal>;
+#[allow(clippy::upper_case_acronyms)]
+pub type CNF = Vec<Clause>;
+
+pub type Assignment = Map<String, bool>;
+
+/// The verdict of a solver run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SatResult {
+    /// The formula is satisfiable. The model assigns every variable that
+    /// occurs in the formula.
+    Sat(Assignment),
+    /// The formula has no satisfying assignment.
+    Unsat,
+}
+
+/// Solves `cnf`, returning a model if the formula is satisfiable.
+///
+/// The search is deterministic: variables are numbered in the order they
+/// first appear and heuristic ties go to the earlier one, so nothing
+/// depends on hash iteration and the same input always yields the same
+/// model. `Heuristic::FirstUnassigned` additionally makes every decision
+/// follow that order.
+///
+/// A formula without clauses is satisfied by the empty assignment, and one
+/// containing the empty clause is unsatisfiable. Both are answered without
+/// starting a search, and so are Horn formulas, see `solve_horn`, and
+/// formulas that flipping some variables makes Horn, see
+/// `is_renamable_horn`.
+pub fn solve(cnf: CNF) -> SatResult {
+    if cnf.is_empty() {
+        return SatResult::Sat(Assignment::new());
+    }
+    if cnf.iter().any(|clause| clause.is_empty()) {
+        return SatResult::Unsat;
+    }
+    let horn = if is_horn(&cnf) {
+        Some(solve_horn(&cnf))
+    } else {
+        is_renamable_horn(&cnf).map(|flips| horn::solve_renamed(&cnf, &flips))
+    };
+    match horn {
+        Some(Some(model)) => SatResult::Sat(model),
+        Some(None) => SatResult::Unsat,
+        None => solver::Solver::new(cnf).solve(),
+    }
+}
+
+/// Solves `cnf` like `solve`, with a model that also assigns every
+/// variable in `vars`, whether it occurs in a clause or not. Declared
+/// variables the formula does not mention are free and set true, like
+/// free variables of the formula itself.
+pub fn solve_over(cnf: CNF, vars: &[String]) -> SatResult {
+    match solve(cnf) {
+        SatResult::Sat(mut model) => {
+            for name in vars {
+                model.entry(name.clone()).or_insert(true);
+            }
+            SatResult::Sat(model)
+        }
+        SatResult::Unsat => SatResult::Unsat,
+    }
+}
+
+/// Solves `cnf` like `solve` without taking it, so the same formula can be
+/// solved again. `None` means it is unsatisfiable.
+pub fn solve_ref(cnf: &CNF) -> Option<Assignment> {
+    if cnf.iter().any(|clause| clause.is_empty()) {
+        return None;
+    }
+    if cnf.is_empty() {
+        return Some(Assignment::new());
+    }
+    match solver::Solver::from_clauses(cnf, SolverConfig::default()).solve() {
+        SatResult::Sat(model) => Some(model),
+        SatResult::Unsat => None,
+    }
+}
+
+/// Decides whether `cnf` has a model, without building one.
+pub fn is_satisfiable(cnf: &CNF) -> bool {
+    if cnf.iter().any(|clause| clause.is_empty()) {
+        return false;
+    }
+    solver::Solver::from_clauses(cnf, SolverConfig::default()).run() == solver::Status::Sat
+}
+
+/// Solves `cnf` with the chosen algorithm, e.g. to compare the classic DPLL
+/// with CDCL. `Algorithm::Cdcl` is the same as `solve`.
+pub fn solve_with(cnf: CNF, algorithm: Algorithm) -> SatResult {
+    match algorithm {
+        Algorithm::Dpll => dpll(cnf, &mut Assignment::new()),
+        Algorithm::Cdcl => solve(cnf),
+    }
+}
+
+/// Checks that `assignment` satisfies every clause of `cnf`. Variables
+/// missing from the assignment make no literal true.
+pub fn verify(cnf: &CNF, assignment: &Assignment) -> bool {
+    cnf.iter().all(|clause| {
+        clause
+            .iter()
+            .any(|literal| assignment.get(&literal.name) == Some(&!literal.negated))
+    })
+}
+
+/// The variables of `cnf` whose value in `model` does not matter: every
+/// clause they occur in is also satisfied by a variable not in the list,
+/// so any values for all of them together keep `model` a model. Variables
+/// are considered in order of first occurrence, and the list is maximal
+/// in that no other variable could be added, though a different choice
+/// could free more. Empty unless `model` satisfies `cnf`.
+pub fn free_vars(cnf: &CNF, model: &Assignment) -> Vec<String> {
+    if !verify(cnf, model) {
+        return Vec::new();
+    }
+    let is_true = |literal: &Literal| model.get(&literal.name) == Some(&!literal.negated);
+    let mut free: Vec<String> = Vec::new();
+    let mut seen = Set::new();
+    for name in cnf.iter().flatten().map(|literal| &literal.name) {
+        if !seen.insert(name) {
+            continue;
+        }
+        // clauses without `name` are unaffected by freeing it, and those
+        // with it must keep a true literal on a variable still fixed
+        let supported = cnf
+            .iter()
+            .filter(|clause| clause.iter().any(|literal| &literal.name == name))
+            .all(|clause| {
+                clause.iter().any(|literal| {
+                    is_true(literal) && &literal.name != name && !free.contains(&literal.name)
+                })
+            });
+        if supported {
+            free.push(name.clone());
+        }
+    }
+    free
+}
+
+/// The variables of `cnf` whose value in `model` can be flipped while
+/// keeping a model, in order of first occurrence. Only the values unit
+/// propagation forces from the flipped one may change along; every other
+/// variable keeps its value in `model`. A variable missing from the list
+/// can still have both values in different models, just not without
+/// further decisions.
+pub fn robust_flips(cnf: &CNF, model: &Assignment) -> Vec<String> {
+    let mut seen = Set::new();
+    let mut flips = Vec::new();
+    for name in cnf.iter().flatten().map(|literal| &literal.name) {
+        let Some(&value) = model.get(name) else {
+            continue;
+        };
+        if !seen.insert(name) {
+            continue;
+        }
+        let mut flipped = Assignment::new();
+        flipped.insert(name.clone(), !value);
+        let Some(mut candidate) = propagate_only(cnf, &flipped) else {
+            continue;
+        };
+        for (other, &kept) in model {
+            candidate.entry(other.clone()).or_insert(kept);
+        }
+        if verify(cnf, &candidate) {
+            flips.push(name.clone());
+        }
+    }
+    flips
+}
+
+/// Formats `cnf` for humans, e.g. `{a,b} ∧ {-a,c}`. The empty formula
+/// prints as nothing.
+pub fn format_cnf(cnf: &[Clause]) -> String {
+    cnf.iter()
+        .map(|clause| {
+            let literals: Vec<String> = clause.iter().map(Literal::to_string).collect();
+            format!("{{{}}}", literals.join(","))
+        })
+        .collect::<Vec<_>>()
+        .join(" ∧ ")
+}
+
+/// Lists the variables of `assignment` in name order as
+/// `a=true, b=false`. Numeric names, as in DIMACS, are ordered by value.
+pub fn format_assignment(assignment: &Assignment) -> String {
+    let mut names: Vec<&String> = assignment.keys().collect();
+    names.sort_by_key(|name| (name.parse::<u64>().ok(), name.as_str()));
+    names
+        .into_iter()
+        .map(|name| format!("{name}={}", assignment[name]))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The values of `model` for the variables of `order`, in that order,
+/// for code that indexes values by position. Variables of `universe`
+/// missing from `model` get `default`, as solvers leave out variables
+/// that turned out not to matter. A name of `order` outside `universe` is
+/// usually a typo, so the first one is returned as the error.
+pub fn model_as_vec(
+    model: &Assignment,
+    order: &[String],
+    universe: &VarMap,
+    default: bool,
+) -> Result<Vec<bool>, String> {
+    order
+        .iter()
+        .map(|name| match universe.id(name) {
+            Some(_) => Ok(model.get(name).copied().unwrap_or(default)),
+            None => Err(name.clone()),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_literal_conversions() {
+        let literal = Literal::from(-3);
+        assert_eq!(literal, Literal::from(("3", true)));
+        assert_eq!(literal.negate(), Literal::from(3));
+        assert_eq!(literal.negate().negate(), literal);
+        assert_eq!(Literal::from(i32::MIN).name, "2147483648");
+        assert_eq!(parse_dimacs("p cnf 3 1\n-3 1 0\n").unwrap()[0][0], literal);
+        assert_eq!(Literal::from(("a", false)), parse2("{a}")[0][0]);
+    }
+
+    #[test]
+    #[should_panic(expected = "0 is not a DIMACS literal")]
+    fn test_literal_from_zero() {
+        let _ = Literal::from(0);
+    }
+
+    #[test]
+    fn test_solve() {
+        let cnf = parse2("{a,b},{-a}");
+        let SatResult::Sat(model) = solve(cnf.clone()) else {
+            panic!("expected a model");
+        };
+        assert!(verify(&cnf, &model));
+        assert_eq!(model.get("a"), Some(&false));
+        assert_eq!(model.get("b"), Some(&true));
+
+        assert_eq!(solve(parse2("{a},{-a}")), SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_solve_trivial_formulas() {
+        assert_eq!(solve(vec![]), SatResult::Sat(Assignment::new()));
+        assert_eq!(solve(vec![vec![]]), SatResult::Unsat);
+        assert_eq!(
+            solve(vec![parse2("{a}").remove(0), vec![]]),
+            SatResult::Unsat
+        );
+    }
+
+    #[test]
+    fn test_verify() {
+        let cnf = parse2("{a,b},{-a,c}");
+        let mut assignment = Assignment::new();
+        assignment.insert("a".to_string(), true);
+        assert!(!verify(&cnf, &assignment));
+
+        assignment.insert("c".to_string(), true);
+        assert!(verify(&cnf, &assignment));
+
+        assignment.insert("c".to_string(), false);
+        assert!(!verify(&cnf, &assignment));
+    }
+
+    #[test]
+    fn test_display() {
+        let cnf = parse2("{a,b},{-a,c}");
+        assert_eq!(cnf[1][0].to_string(), "-a");
+        assert_eq!(format!("{}", cnf[0][1]), "b");
+        assert_eq!(format_cnf(&cnf), "{a,b} ∧ {-a,c}");
+        assert_eq!(format_cnf(&[vec![]]), "{}");
+        assert_eq!(format_cnf(&CNF::new()), "");
+    }
+
+    #[test]
+    fn test_format_assignment() {
+        let assignment: Assignment = [("b", false), ("a", true), ("c", true)]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        assert_eq!(format_assignment(&assignment), "a=true, b=false, c=true");
+
+        let assignment: Assignment = [("10", true), ("2", false), ("1", true)]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        assert_eq!(format_assignment(&assignment), "1=true, 2=false, 10=true");
+        assert_eq!(format_assignment(&Assignment::new()), "");
+    }
+
+    #[test]
+    fn test_model_as_vec() {
+        let universe = VarMap::from_cnf(&parse2("{a,b},{c,-d}"));
+        let model: Assignment = [("a", true), ("b", false)]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        let order = ["b".to_string(), "a".to_string()];
+        assert_eq!(
+            model_as_vec(&model, &order, &universe, false),
+            Ok(vec![false, true])
+        );
+        assert_eq!(model_as_vec(&model, &[], &universe, false), Ok(vec![]));
+
+        // `c` and `d` are in the formula but not in the model
+        let order = ["c".to_string(), "a".to_string(), "d".to_string()];
+        assert_eq!(
+            model_as_vec(&model, &order, &universe, false),
+            Ok(vec![false, true, false])
+        );
+        assert_eq!(
+            model_as_vec(&model, &order, &universe, true),
+            Ok(vec![true, true, true])
+        );
+
+        let order = ["a".to_string(), "e".to_string(), "f".to_string()];
+        assert_eq!(
+            model_as_vec(&model, &order, &universe, false),
+            Err("e".to_string())
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let literal = Literal {
+            name: "a".to_string(),
+            negated: true,
+        };
+        assert_eq!(
+            serde_json::to_string(&literal).unwrap(),
+            r#"{"name":"a","negated":true}"#
+        );
+
+        let cnf = parse2("{a,-b},{c}");
+        let json = serde_json::to_string(&cnf).unwrap();
+        assert_eq!(serde_json::from_str::<CNF>(&json).unwrap(), cnf);
+
+        let SatResult::Sat(model) = solve(cnf) else {
+            panic!("expected a model");
+        };
+        let json = serde_json::to_string(&model).unwrap();
+        assert_eq!(serde_json::from_str::<Assignment>(&json).unwrap(), model);
+    }
+
+    #[test]
+    fn test_solve_is_deterministic() {
+        let formula = "{a,b,c},{-a,-b},{-b,-c},{-a,-c},{d,e,-c},{-d,-e},{f,g},{-f,-g,a}";
+        let first = solve(parse2(formula));
+        for _ in 0..20 {
+            assert_eq!(solve(parse2(formula)), first);
+            let config = SolverConfig {
+                heuristic: Heuristic::FirstUnassigned,
+                ..SolverConfig::default()
+            };
+            assert_eq!(
+                solve_with_config(parse2(formula), config.clone()),
+                solve_with_config(parse2(formula), config)
+            );
+            assert_eq!(
+                dpll(parse2(formula), &mut Assignment::new()),
+                dpll(parse2(formula), &mut Assignment::new())
+            );
+        }
+    }
+
+    #[test]
+    fn test_models_satisfy_random_formulas() {
+        for seed in 0..300 {
+            let num_vars = 3 + seed as usize % 6;
+            let num_clauses = 1 + seed as usize * 7 % 40;
+            let cnf = random_3sat(num_vars, num_clauses, seed);
+            match solve(cnf.clone()) {
+                SatResult::Sat(model) => {
+                    assert!(verify(&cnf, &model), "seed {seed}");
+                    let mut names = cnf.iter().flatten().map(|literal| &literal.name);
+                    assert!(names.all(|name| model.contains_key(name)));
+                }
+                SatResult::Unsat => assert_eq!(
+                    dpll(cnf, &mut Assignment::new()),
+                    SatResult::Unsat,
+                    "seed {seed}"
+                ),
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_ref() {
+        let cnf = parse2("{a,b},{-a}");
+        let model = solve_ref(&cnf).unwrap();
+        assert!(verify(&cnf, &model));
+        assert_eq!(SatResult::Sat(model), solve(cnf.clone()));
+        assert_eq!(solve_ref(&parse2("{a},{-a}")), None);
+        assert_eq!(solve_ref(&CNF::new()), Some(Assignment::new()));
+        assert_eq!(solve_ref(&vec![vec![]]), None);
+    }
+
+    #[test]
+    fn test_is_satisfiable() {
+        assert!(is_satisfiable(&parse2("{a,b},{-a}")));
+        assert!(!is_satisfiable(&parse2("{a,b},{-a},{-b}")));
+        assert!(is_satisfiable(&CNF::new()));
+        assert!(!is_satisfiable(&vec![vec![]]));
+        for seed in 0..20 {
+            let cnf = random_3sat(20, 90, seed);
+            assert_eq!(is_satisfiable(&cnf), solve_ref(&cnf).is_some());
+        }
+    }
+
+    #[test]
+    fn test_solve_with_algorithms_agree() {
+        for seed in 0..50 {
+            let cnf = random_3sat(12, 52, seed);
+            let classic = solve_with(cnf.clone(), Algorithm::Dpll);
+            let cdcl = solve_with(cnf.clone(), Algorithm::Cdcl);
+            assert_eq!(
+                matches!(classic, SatResult::Sat(_)),
+                matches!(cdcl, SatResult::Sat(_)),
+                "seed {seed}"
+            );
+            for result in [classic, cdcl] {
+                if let SatResult::Sat(model) = result {
+                    assert!(verify(&cnf, &model), "seed {seed}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_assigns_free_variables() {
+        let SatResult::Sat(model) = solve(parse2("{a}")) else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.get("a"), Some(&true));
+
+        let SatResult::Sat(model) = solve(parse2("{a},{a,b}")) else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.get("a"), Some(&true));
+        assert!(model.contains_key("b"));
+
+        let SatResult::Sat(model) = solve(parse2("{a,b},{a}")) else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.len(), 2);
+        assert!(verify(&parse2("{a,b},{a}"), &model));
+    }
+
+    #[test]
+    fn test_solve_over() {
+        let vars = ["a", "out"].map(str::to_string);
+        let SatResult::Sat(model) = solve_over(parse2("{-a},{b}"), &vars) else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.len(), 3);
+        assert!(!model["a"] && model["b"]);
+        assert!(model["out"]);
+
+        let SatResult::Sat(model) = solve_over(CNF::new(), &vars) else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.len(), 2);
+        assert_eq!(solve_over(parse2("{a},{-a}"), &vars), SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_robust_flips() {
+        // a is forced, and flipping b or c propagates to the other
+        let cnf = parse2("{a},{-a,b,c},{-b,-c}");
+        let SatResult::Sat(model) = solve(cnf.clone()) else {
+            panic!("expected a model");
+        };
+        assert_eq!(robust_flips(&cnf, &model), ["b", "c"]);
+
+        // a = false needs b or c to become true, which nothing forces
+        let cnf = parse2("{a,b,c}");
+        let model = [("a", true), ("b", false), ("c", false)]
+            .map(|(name, value)| (name.to_string(), value))
+            .into_iter()
+            .collect();
+        assert_eq!(robust_flips(&cnf, &model), ["b", "c"]);
+        assert!(robust_flips(&parse2("{a},{-a}"), &model).is_empty());
+    }
+
+    #[test]
+    fn test_free_vars() {
+        let cnf = parse2("{a,b},{a}");
+        let SatResult::Sat(model) = solve(cnf.clone()) else {
+            panic!("expected a model");
+        };
+        assert_eq!(free_vars(&cnf, &model), ["b"]);
+
+        // either of a and b could go, but not both
+        let cnf = parse2("{a,b},{c}");
+        let model = [("a", true), ("b", true), ("c", true)]
+            .map(|(name, value)| (name.to_string(), value))
+            .into_iter()
+            .collect();
+        assert_eq!(free_vars(&cnf, &model), ["a"]);
+
+        let cnf = parse2("{a,-b},{b}");
+        let SatResult::Sat(model) = solve(cnf.clone()) else {
+            panic!("expected a model");
+        };
+        assert!(free_vars(&cnf, &model).is_empty());
+        assert!(free_vars(&cnf, &Assignment::new()).is_empty());
+    }
+}
@@ -1,243 +1,348 @@
-#[derive(Debug, Clone)]
-struct Literal {
-    name: String,
-    negated: bool,
+use sat_solver::{
+    CNF, Literal, SatResult, VarMap, format_assignment, parse_dimacs, parse_formula, parse_many,
+    parse2_checked, solve, solve_icnf, to_dimacs,
+};
+use std::io::{BufRead, BufReader, Read};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: sat_solver [--format dimacs|braces|icnf|many] <file | ->
+       sat_solver --convert dimacs|braces <file | ->";
+
+// exit codes used by SAT competition solvers
+const EXIT_SAT: u8 = 10;
+const EXIT_UNSAT: u8 = 20;
+const EXIT_ERROR: u8 = 1;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Dimacs,
+    Braces,
+    // an incremental script with several solves
+    Icnf,
+    // brace syntax formulas separated by blank lines
+    Many,
 }
-type Clause = Vec<Literal>;
-type CNF = Vec<Clause>;
-
-use std::collections::HashMap;
-type Assignment = HashMap<String, bool>;
-
-// DPLL has 4 steps:
-// 1. Unit propagation
-// 2. Pure literal elimination
-// 3. Branching
-// 5. Termination
-
-fn parse2(formula: &str) -> CNF {
-    formula
-        .replace(" ", "")
-        .split("},")
-        .map(|s| {
-            s.replace("{", "")
-                .replace("}", "")
-                .split(",")
-                .map(|mut exp| {
-                    let mut negated = false;
-                    if exp.contains('-') {
-                        exp = exp.trim_matches('-');
-                        negated = true;
-                    }
-                    Literal {
-                        name: exp.to_string(),
-                        negated,
-                    }
-                })
-                .collect()
-        })
-        .collect()
+
+#[derive(Debug, PartialEq, Eq)]
+struct Args<'a> {
+    // `-` for stdin
+    path: &'a str,
+    format: Option<Format>,
+    // the format to write the formula in instead of solving it
+    convert: Option<Format>,
 }
 
-fn main() {
-    // let formula = parse2("{a,b},{b,a},{c,b}");
-    // let formula = parse2("{a}, {b,c}");
-    let formula = parse2("{a,b}");
+fn parse_args(args: &[String]) -> Result<Args<'_>, String> {
+    let mut path = None;
+    let mut format = None;
+    let mut convert = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                format = match args.next().map(String::as_str) {
+                    Some("dimacs") => Some(Format::Dimacs),
+                    Some("braces") => Some(Format::Braces),
+                    Some("icnf") => Some(Format::Icnf),
+                    Some("many") => Some(Format::Many),
+                    Some(other) => return Err(format!("unknown format `{other}`")),
+                    None => return Err("--format needs a value".to_string()),
+                }
+            }
+            "--convert" => {
+                convert = match args.next().map(String::as_str) {
+                    Some("dimacs") => Some(Format::Dimacs),
+                    Some("braces") => Some(Format::Braces),
+                    Some(other) => return Err(format!("cannot convert to `{other}`")),
+                    None => return Err("--convert needs a value".to_string()),
+                }
+            }
+            flag if flag.starts_with("--") => return Err(format!("unknown option `{flag}`")),
+            _ if path.is_some() => return Err("expected a single input".to_string()),
+            _ => path = Some(arg.as_str()),
+        }
+    }
 
-    println!("{:?}", formula);
+    match path {
+        Some(path) => Ok(Args {
+            path,
+            format,
+            convert,
+        }),
+        None => Err("missing input".to_string()),
+    }
+}
 
-    // for clause in formula {
-    //     for literal in clause {
-    //         print!("{}", literal.name);
-    //     }
-    // }
+// every gzip stream starts with these two bytes
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
 
-    let mut assignment = HashMap::new();
+fn read_input(path: &str) -> std::io::Result<String> {
+    let mut input = String::new();
+    if path == "-" {
+        std::io::stdin().read_to_string(&mut input)?;
+        return Ok(input);
+    }
 
-    dpll(formula, &mut assignment);
+    let mut file = BufReader::new(std::fs::File::open(path)?);
+    if file.fill_buf()?.starts_with(&GZIP_MAGIC) {
+        return read_gzip(file);
+    }
+    file.read_to_string(&mut input)?;
+    Ok(input)
+}
 
-    print!("{:?}", assignment)
+// decompresses while reading, the compressed file is never held in memory
+#[cfg(feature = "gzip")]
+fn read_gzip(file: impl Read) -> std::io::Result<String> {
+    let mut input = String::new();
+    flate2::read::GzDecoder::new(file).read_to_string(&mut input)?;
+    Ok(input)
 }
 
-fn dpll(cnf: CNF, assignment: &mut Assignment) -> Option<Assignment> {
-    let cnf = unit_propagate(cnf, assignment)?;
+#[cfg(not(feature = "gzip"))]
+fn read_gzip(_file: impl Read) -> std::io::Result<String> {
+    Err(std::io::Error::other(
+        "gzip compressed input needs the `gzip` feature",
+    ))
+}
 
-    if cnf.is_empty() {
-        return Some(assignment.clone()); // all clauses satisfied
-    }
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let Args {
+        path,
+        format,
+        convert,
+    } = match parse_args(&args) {
+        Ok(parsed) => parsed,
+        Err(message) => {
+            eprintln!("{message}\n{USAGE}");
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
 
-    if cnf.iter().any(|clause| clause.is_empty()) {
-        return None; // conflict
+    let input = match read_input(path) {
+        Ok(input) => input,
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            return ExitCode::from(EXIT_ERROR);
+        }
+    };
+    if let Some(target) = convert {
+        return match convert_formula(&input, target) {
+            Ok(output) => {
+                print!("{output}");
+                ExitCode::SUCCESS
+            }
+            Err(err) => {
+                eprintln!("{path}: {err}");
+                ExitCode::from(EXIT_ERROR)
+            }
+        };
     }
 
-    // find a literal that is not yet assigned
-    let literal = match pick_literal(&cnf, &assignment) {
-        Some(lit) => lit,
-        None => {
-            if cnf.is_empty() {
-                return Some(assignment.clone());
-            } else {
-                // unsatisfiable -> we have
-                return None;
-            }
+    let parsed = match format {
+        Some(Format::Dimacs) => parse_dimacs(&input),
+        Some(Format::Braces) => parse2_checked(&input),
+        Some(Format::Icnf) => return run_icnf(path, &input),
+        Some(Format::Many) => return run_many(&input),
+        None => parse_formula(&input),
+    };
+    let formula = match parsed {
+        Ok(cnf) => cnf,
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            return ExitCode::from(EXIT_ERROR);
         }
     };
 
-    // branch
-    for value in [true, false] {
-        let mut assignment = assignment.clone();
-        assignment.insert(literal.name.clone(), value);
-        if let Some(result) = dpll(cnf.clone(), &mut assignment) {
-            return Some(result);
+    match solve(formula) {
+        SatResult::Sat(model) => {
+            println!("SAT");
+            println!("{}", format_assignment(&model));
+            ExitCode::from(EXIT_SAT)
+        }
+        SatResult::Unsat => {
+            println!("UNSAT");
+            ExitCode::from(EXIT_UNSAT)
         }
     }
-
-    // neither branch was satisfiable
-    None
 }
 
-fn pick_literal(cnf: &CNF, assignment: &Assignment) -> Option<Literal> {
-    for clause in cnf {
-        for literal in clause {
-            if !assignment.contains_key(&literal.name) {
-                return Some(literal.clone());
-            }
+// one verdict line per solve, the exit code follows the last one
+fn run_icnf(path: &str, input: &str) -> ExitCode {
+    let results = match solve_icnf(input) {
+        Ok(results) => results,
+        Err(err) => {
+            eprintln!("{path}: {err}");
+            return ExitCode::from(EXIT_ERROR);
         }
+    };
+    for result in &results {
+        println!("{}", if result.is_some() { "SAT" } else { "UNSAT" });
+    }
+    match results.last() {
+        Some(Some(_)) => ExitCode::from(EXIT_SAT),
+        Some(None) => ExitCode::from(EXIT_UNSAT),
+        None => ExitCode::SUCCESS,
     }
-    None
 }
 
-fn unit_propagate(mut cnf: CNF, assignment: &mut Assignment) -> Option<CNF> {
-    loop {
-        // pick clause
-        let unit = cnf.iter().find_map(|clause| {
-            // pattern match literal with slice representation of clause
-            if let [literal] = &clause[..] {
-                Some(literal)
-            } else {
-                None
-            }
-        });
-
-        // shadow unwrap
-        let unit = match unit {
-            Some(lit) => lit,
-            None => break, // no more unit clauses
-        };
-
-        // value we want to assign
-        let value_to_assign = !unit.negated;
-        if let Some(&existing) = assignment.get(&unit.name) {
-            // if existing value and value_to_assign does not match, it is unsatisfiable
-            if existing != value_to_assign {
-                return None;
+// DIMACS comments recording the original name of a variable id
+const NAME_COMMENT: &str = "c var ";
+
+// reads brace syntax and writes DIMACS, or the other way around
+fn convert_formula(input: &str, target: Format) -> Result<String, sat_solver::ParseError> {
+    if target == Format::Dimacs {
+        let cnf = parse2_checked(input)?;
+        let vars = VarMap::from_cnf(&cnf);
+        // numbered names keep their number only if it is also their id
+        let mut out = String::new();
+        for (id, name) in vars.names().enumerate() {
+            if name != (id + 1).to_string() {
+                out.push_str(&format!("{NAME_COMMENT}{} {name}\n", id + 1));
             }
-        } else {
-            assignment.insert(unit.name.clone(), value_to_assign);
         }
-
-        cnf = simplify(cnf, assignment)?;
+        out.push_str(&to_dimacs(&cnf, &vars));
+        return Ok(out);
     }
 
-    // in the end return the simplified formula
-    Some(cnf)
+    let mut cnf = parse_dimacs(input)?;
+    let names: Vec<(&str, &str)> = input
+        .lines()
+        .filter_map(|line| line.strip_prefix(NAME_COMMENT)?.split_once(' '))
+        .collect();
+    for literal in cnf.iter_mut().flatten() {
+        if let Some(&(_, name)) = names.iter().find(|&&(id, _)| id == literal.name) {
+            literal.name = name.to_string();
+        }
+    }
+    Ok(format!("{}\n", to_braces(&cnf)))
 }
 
-fn simplify(mut cnf: CNF, assignment: &Assignment) -> Option<CNF> {
-    // keep only the clauses that arent satisified
-    cnf.retain(|clause| {
-        !clause.iter().any(|literal| {
-            match assignment.get(&literal.name) {
-                Some(&value) => value != literal.negated, // literal evaluates to true?
-                None => false,
-            }
-        })
-    });
-
-    // remove all literals assigned false
-    for clause in cnf.iter_mut() {
-        clause.retain(|literal| match assignment.get(&literal.name) {
-            Some(&value) => value == literal.negated, // keep if literal is not false
-            None => true,
+// brace syntax that `parse2_checked` reads back, quoting unusual names
+fn to_braces(cnf: &CNF) -> String {
+    let literal = |literal: &Literal| {
+        let sign = if literal.negated { "-" } else { "" };
+        let plain = !literal.name.is_empty()
+            && !literal.name.starts_with(['-', '!', '~'])
+            && !literal
+                .name
+                .contains(|c: char| c.is_whitespace() || "{},\"".contains(c));
+        if plain {
+            format!("{sign}{}", literal.name)
+        } else {
+            format!("{sign}\"{}\"", literal.name)
+        }
+    };
+    cnf.iter()
+        .map(|clause| {
+            let literals: Vec<String> = clause.iter().map(literal).collect();
+            format!("{{{}}}", literals.join(","))
         })
-    }
+        .collect::<Vec<_>>()
+        .join(",")
+}
 
-    // check for empty clauses
-    for clause in &cnf {
-        if clause.is_empty() {
-            return None;
-        }
+// one verdict line per formula, the exit code follows the last one
+fn run_many(input: &str) -> ExitCode {
+    let mut exit = ExitCode::SUCCESS;
+    for formula in parse_many(input) {
+        exit = match solve(formula) {
+            SatResult::Sat(_) => {
+                println!("SAT");
+                ExitCode::from(EXIT_SAT)
+            }
+            SatResult::Unsat => {
+                println!("UNSAT");
+                ExitCode::from(EXIT_UNSAT)
+            }
+        };
     }
-
-    Some(cnf)
+    exit
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::HashMap;
-
-    #[test]
-    fn test_formula_1() {
-        let cnf = parse2("{a}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_some());
-    }
 
-    #[test]
-    fn test_formula_2() {
-        let cnf = parse2("{a},{b}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_some());
-    }
-
-    #[test]
-    fn test_formula_3() {
-        let cnf = parse2("{a,b}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_some());
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| arg.to_string()).collect()
     }
 
     #[test]
-    fn test_formula_4() {
-        let cnf = parse2("{-a,b}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_some());
-    }
-
-    #[test]
-    fn test_formula_5() {
-        let cnf = parse2("{a},{-a}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_none());
-    }
-
-    #[test]
-    fn test_formula_6() {
-        let cnf = parse2("{a,b},{-a,c},{-b,-c}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_some());
+    fn test_parse_args() {
+        let parsed = |path, format, convert| {
+            Ok(Args {
+                path,
+                format,
+                convert,
+            })
+        };
+        assert_eq!(parse_args(&args(&["f.cnf"])), parsed("f.cnf", None, None));
+        assert_eq!(
+            parse_args(&args(&["--format", "braces", "-"])),
+            parsed("-", Some(Format::Braces), None)
+        );
+        assert_eq!(
+            parse_args(&args(&["--format", "icnf", "f.icnf"])),
+            parsed("f.icnf", Some(Format::Icnf), None)
+        );
+        assert_eq!(
+            parse_args(&args(&["--format", "many", "f.txt"])),
+            parsed("f.txt", Some(Format::Many), None)
+        );
+        assert_eq!(
+            parse_args(&args(&["--convert", "dimacs", "f.txt"])),
+            parsed("f.txt", None, Some(Format::Dimacs))
+        );
+        assert!(parse_args(&args(&[])).is_err());
+        assert!(parse_args(&args(&["a", "b"])).is_err());
+        assert!(parse_args(&args(&["--format", "xml", "a"])).is_err());
+        assert!(parse_args(&args(&["a", "--format"])).is_err());
+        assert!(parse_args(&args(&["--convert", "icnf", "a"])).is_err());
     }
 
     #[test]
-    fn test_formula_7() {
-        let cnf = parse2("{a,b},{b,c},{-a,-b},{-c,d}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_some());
+    fn test_convert_round_trip() {
+        // formula 6 of the classic DPLL tests
+        let braces = "{a,b},{-a,c},{-b,-c}";
+        let dimacs = convert_formula(braces, Format::Dimacs).unwrap();
+        assert_eq!(
+            dimacs,
+            "c var 1 a\nc var 2 b\nc var 3 c\np cnf 3 3\n1 2 0\n-1 3 0\n-2 -3 0\n"
+        );
+        assert_eq!(
+            convert_formula(&dimacs, Format::Braces).unwrap(),
+            format!("{braces}\n")
+        );
+
+        // plain DIMACS keeps its numbers, odd names are quoted
+        let dimacs = "p cnf 2 2\n1 -2 0\n2 0\n";
+        let braces = convert_formula(dimacs, Format::Braces).unwrap();
+        assert_eq!(braces, "{1,-2},{2}\n");
+        assert_eq!(convert_formula(&braces, Format::Dimacs).unwrap(), dimacs);
+        let odd = r#"{"a,b",-"-x"},{"y z"}"#;
+        let dimacs = convert_formula(odd, Format::Dimacs).unwrap();
+        assert_eq!(
+            convert_formula(&dimacs, Format::Braces).unwrap(),
+            format!("{odd}\n")
+        );
     }
 
+    #[cfg(feature = "gzip")]
     #[test]
-    fn test_formula_8() {
-        let cnf = parse2("{a,b},{-a,b},{a,-b},{-a,-b}");
-        let mut assignment = HashMap::new();
-        let result = dpll(cnf, &mut assignment);
-        assert!(result.is_none());
+    fn test_read_gzip_input() {
+        use flate2::{Compression, write::GzEncoder};
+        use std::io::Write;
+
+        let dimacs = "p cnf 2 2\n1 -2 0\n2 0\n";
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(dimacs.as_bytes()).unwrap();
+        let path = std::env::temp_dir().join(format!("sat_solver_{}.cnf.gz", std::process::id()));
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let input = read_input(path.to_str().unwrap());
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(input.unwrap(), dimacs);
     }
 }
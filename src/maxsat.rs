@@ -0,0 +1,237 @@
+use crate::cardinality::encode_weighted_at_most;
+use crate::{Assignment, CNF, Cardinality, CardinalityKind, Clause, Literal, Solver};
+use alloc::{format, vec::Vec};
+
+/// Finds an assignment satisfying as many clauses of `cnf` as possible and
+/// returns it with the number of clauses it satisfies.
+///
+/// Every clause gets a relaxation variable that satisfies it when true.
+/// After each model the number of relaxation variables allowed to be true
+/// is bounded below the number of clauses that model falsifies, until the
+/// bound cannot be met.
+pub fn max_sat(cnf: CNF) -> (Assignment, usize) {
+    max_sat_with_progress(cnf, |_, _| {})
+}
+
+/// Solves `cnf` like `max_sat`, calling `on_improved` with each model
+/// found along the way and the number of clauses it falsifies, so with
+/// strictly falling costs. The last call is with the optimum, which is
+/// also returned. Auxiliary variables are left out of the models.
+pub fn max_sat_with_progress(
+    cnf: CNF,
+    mut on_improved: impl FnMut(&Assignment, u64),
+) -> (Assignment, usize) {
+    let relaxations: Vec<Literal> = (0..cnf.len())
+        .map(|i| Literal {
+            name: format!("@r{i}"),
+            negated: false,
+        })
+        .collect();
+    let relaxed: CNF = cnf
+        .iter()
+        .zip(&relaxations)
+        .map(|(clause, relaxation)| {
+            let mut clause = clause.clone();
+            clause.push(relaxation.clone());
+            clause
+        })
+        .collect();
+
+    let mut solver = Solver::new(relaxed);
+    // every bound added so far, so new auxiliary variables stay fresh
+    let mut bounds = CNF::new();
+    let mut best = solver
+        .solve_under(&[])
+        .expect("relaxed clauses are always satisfiable");
+    loop {
+        let falsified = cnf
+            .iter()
+            .filter(|clause| !satisfied(clause, &best))
+            .count();
+        let mut model = best.clone();
+        model.retain(|name, _| !name.starts_with('@'));
+        on_improved(&model, falsified as u64);
+        if falsified == 0 {
+            break;
+        }
+
+        let start = bounds.len();
+        Cardinality {
+            lits: relaxations.clone(),
+            bound: falsified - 1,
+            kind: CardinalityKind::AtMost,
+        }
+        .encode_into(&mut bounds);
+        for clause in &bounds[start..] {
+            solver.add_clause(clause.clone());
+        }
+
+        match solver.solve_under(&[]) {
+            Some(model) => best = model,
+            None => break,
+        }
+    }
+
+    let count = cnf.iter().filter(|clause| satisfied(clause, &best)).count();
+    best.retain(|name, _| !name.starts_with('@'));
+    (best, count)
+}
+
+/// Lexicographic MaxSAT: finds a model of `hard` that minimizes the total
+/// weight of the falsified soft clauses of the first tier, among those the
+/// weight falsified in the second tier, and so on, so each tier dominates
+/// all later ones. `None` means `hard` is unsatisfiable.
+///
+/// Tiers are optimized one after another like in `max_sat`, with the
+/// weights bounded by a generalized totalizer, whose size depends on how
+/// many distinct sums the weights make rather than on how large they are.
+/// Once a tier is optimal its cost is kept as a bound while the later
+/// tiers are optimized.
+pub fn lex_max_sat(hard: CNF, tiers: Vec<Vec<(u64, Clause)>>) -> Option<Assignment> {
+    let mut solver = Solver::new(hard.clone());
+    // starting from `hard` keeps new auxiliary variables fresh
+    let mut bounds = hard;
+    let mut best = solver.solve_under(&[])?;
+
+    for (t, tier) in tiers.iter().enumerate() {
+        let mut weighted = Vec::new();
+        for (i, (weight, clause)) in tier.iter().enumerate() {
+            let relaxation = Literal {
+                name: format!("@r{t}_{i}"),
+                negated: false,
+            };
+            let mut relaxed = clause.clone();
+            relaxed.push(relaxation.clone());
+            solver.add_clause(relaxed);
+            weighted.push((*weight, relaxation));
+        }
+        let cost = |model: &Assignment| -> u64 {
+            tier.iter()
+                .filter(|(_, clause)| !satisfied(clause, model))
+                .map(|(weight, _)| weight)
+                .sum()
+        };
+        let bound = |bound: u64, bounds: &mut CNF| {
+            let start = bounds.len();
+            encode_weighted_at_most(&weighted, bound, bounds);
+            bounds[start..].to_vec()
+        };
+
+        // a tighter bound that fails must not stay, so bounds are only
+        // enforced while assumed
+        loop {
+            let falsified = cost(&best);
+            if falsified == 0 {
+                break;
+            }
+            let controls: Vec<Literal> = bound(falsified - 1, &mut bounds)
+                .into_iter()
+                .map(|clause| solver.add_retractable(clause))
+                .collect();
+            match solver.solve_under(&controls) {
+                Some(model) => best = model,
+                None => break,
+            }
+        }
+        for clause in bound(cost(&best), &mut bounds) {
+            solver.add_clause(clause);
+        }
+    }
+
+    best.retain(|name, _| !name.starts_with('@'));
+    Some(best)
+}
+
+fn satisfied(clause: &[Literal], model: &Assignment) -> bool {
+    clause
+        .iter()
+        .any(|literal| model.get(&literal.name) == Some(&!literal.negated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse2;
+
+    #[test]
+    fn test_contradicting_units() {
+        let (model, count) = max_sat(parse2("{a},{-a}"));
+        assert_eq!(count, 1);
+        assert_eq!(model.len(), 1);
+    }
+
+    #[test]
+    fn test_max_sat() {
+        for (formula, best) in [
+            ("{a,b},{-a,b},{a,-b},{-a,-b}", 3),
+            ("{a},{b},{-a,-b},{-a},{c}", 4),
+            ("{a,b},{-a,c},{-b,-c}", 3),
+            ("{a},{a},{-a}", 2),
+        ] {
+            let cnf = parse2(formula);
+            let (model, count) = max_sat(cnf.clone());
+            assert_eq!(count, best, "{formula}");
+            let satisfied = cnf.iter().filter(|clause| satisfied(clause, &model));
+            assert_eq!(satisfied.count(), best, "{formula}");
+        }
+    }
+
+    #[test]
+    fn test_max_sat_progress() {
+        for seed in 0..10 {
+            let cnf = crate::random_3sat(8, 60, seed);
+            let mut costs = Vec::new();
+            let (model, count) = max_sat_with_progress(cnf.clone(), |model, cost| {
+                assert!(model.keys().all(|name| !name.starts_with('@')));
+                let falsified = cnf.iter().filter(|clause| !satisfied(clause, model));
+                assert_eq!(falsified.count() as u64, cost);
+                costs.push(cost);
+            });
+            assert!(
+                costs.windows(2).all(|pair| pair[1] < pair[0]),
+                "seed {seed}"
+            );
+            assert_eq!(costs.last(), Some(&((cnf.len() - count) as u64)));
+            assert_eq!((model, count), max_sat(cnf));
+        }
+    }
+
+    #[test]
+    fn test_lex_max_sat() {
+        let soft = |weight, clause: &str| (weight, parse2(clause).remove(0));
+        let hard = parse2("{-a,-b}");
+
+        // making `a` true for the first tier rules out `b` in the second
+        let tiers = vec![vec![soft(1, "{a}")], vec![soft(1, "{b}"), soft(1, "{c}")]];
+        let model = lex_max_sat(hard.clone(), tiers).unwrap();
+        assert!(model["a"] && !model["b"] && model["c"]);
+        assert_eq!(model.len(), 3);
+
+        let tiers = vec![vec![soft(1, "{b}")], vec![soft(5, "{a}")]];
+        let model = lex_max_sat(hard.clone(), tiers).unwrap();
+        assert!(!model["a"] && model["b"]);
+
+        // within a tier the heavier clause wins
+        let tiers = vec![vec![soft(2, "{a}"), soft(3, "{b}")]];
+        let model = lex_max_sat(hard.clone(), tiers).unwrap();
+        assert!(!model["a"] && model["b"]);
+
+        // weights are not expanded into copies of their clauses
+        let tiers = vec![vec![
+            soft(1_000_000, "{a}"),
+            soft(999_999, "{b}"),
+            soft(2, "{c}"),
+            soft(3, "{-c}"),
+        ]];
+        let model = lex_max_sat(hard.clone(), tiers).unwrap();
+        assert!(model["a"] && !model["b"] && !model["c"]);
+
+        assert_eq!(lex_max_sat(parse2("{a},{-a}"), vec![]), None);
+    }
+
+    #[test]
+    fn test_empty_clause_is_never_satisfied() {
+        let (_, count) = max_sat(vec![vec![], parse2("{a}").remove(0)]);
+        assert_eq!(count, 1);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::occurs::Occurrences;
+use crate::{Assignment, CNF, Literal, Map, Solver, VarMap, lex_max_sat};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Finds a model of `cnf` whose true variables form a minimal set: making
+/// any one of them false, while keeping the others, falsifies the formula.
+/// This is subset minimal, not necessarily the model with the fewest true
+/// variables overall.
+///
+/// Each variable true in the current model is tried false in turn, with
+/// every variable already false kept false, and a model found that way
+/// replaces the current one.
+pub fn minimal_model(cnf: CNF) -> Option<Assignment> {
+    let mut solver = Solver::new(cnf);
+    let mut best = solver.solve_under(&[])?;
+
+    // hash order would make the result depend on the run
+    let mut names: Vec<String> = best.keys().cloned().collect();
+    names.sort_unstable();
+
+    for candidate in &names {
+        if !best[candidate] {
+            continue; // false already, or made false by an earlier model
+        }
+        let assumptions: Vec<Literal> = names
+            .iter()
+            .filter(|&name| name == candidate || !best[name])
+            .map(|name| Literal {
+                name: name.clone(),
+                negated: true,
+            })
+            .collect();
+        if let Some(model) = solver.solve_under(&assumptions) {
+            best = model;
+        }
+    }
+    Some(best)
+}
+
+/// Finds a model of `cnf` with the least total weight of true variables,
+/// with the weights of `weights` and 1 for every variable not listed, and
+/// returns it with that weight. `None` means `cnf` is unsatisfiable.
+///
+/// Solved as weighted MaxSAT by `lex_max_sat`, with `cnf` hard and a soft
+/// clause per variable asking for it to be false.
+pub fn min_weight_model(cnf: CNF, weights: &Map<String, u64>) -> Option<(Assignment, u64)> {
+    let vars = VarMap::from_cnf(&cnf);
+    let weight = |name: &str| weights.get(name).copied().unwrap_or(1);
+    let soft = vars
+        .names()
+        .map(|name| {
+            let false_literal = Literal {
+                name: name.to_string(),
+                negated: true,
+            };
+            (weight(name), vec![false_literal])
+        })
+        .collect();
+    let model = lex_max_sat(cnf, vec![soft])?;
+    let cost = model
+        .iter()
+        .filter(|&(_, &value)| value)
+        .map(|(name, _)| weight(name))
+        .sum();
+    Some((model, cost))
+}
+
+/// The literals of `model` that alone satisfy `cnf`, a partial assignment
+/// where every extension is a model. Variables are dropped greedily in
+/// order of first occurrence while each clause keeps a true literal, so
+/// the result is prime, no literal can be left out, but not necessarily
+/// the smallest such set. Clauses `model` falsifies do not keep anything.
+pub fn prime_implicant(cnf: &CNF, model: &Assignment) -> Vec<Literal> {
+    let occurrences = Occurrences::from_cnf(cnf);
+    // the literal each variable makes true, if it has a value
+    let literals: Vec<Literal> = occurrences
+        .names()
+        .filter_map(|name| {
+            Some(Literal {
+                name: name.to_string(),
+                negated: !*model.get(name)?,
+            })
+        })
+        .collect();
+
+    // true literals of each clause still in the implicant
+    let mut support = vec![0usize; cnf.len()];
+    for literal in &literals {
+        for &index in occurrences.of_literal(literal) {
+            support[index] += 1;
+        }
+    }
+    literals
+        .into_iter()
+        .filter(|literal| {
+            let clauses = occurrences.of_literal(literal);
+            if clauses.iter().any(|&index| support[index] == 1) {
+                return true;
+            }
+            for &index in clauses {
+                support[index] -= 1;
+            }
+            false
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse2, verify};
+
+    fn true_count(model: &Assignment) -> usize {
+        model.values().filter(|&&value| value).count()
+    }
+
+    #[test]
+    fn test_minimal_model() {
+        let model = minimal_model(parse2("{a,b}")).unwrap();
+        assert_eq!(model.len(), 2);
+        assert_eq!(true_count(&model), 1);
+
+        // free variables end up false
+        let cnf = parse2("{a,b,c},{-a,d},{e,-e}");
+        let model = minimal_model(cnf.clone()).unwrap();
+        assert!(verify(&cnf, &model));
+        assert_eq!(true_count(&model), 1);
+        assert!(!model["e"]);
+
+        assert_eq!(minimal_model(parse2("{a},{-a}")), None);
+    }
+
+    #[test]
+    fn test_min_weight_model() {
+        let weights = |pairs: &[(&str, u64)]| -> Map<String, u64> {
+            pairs
+                .iter()
+                .map(|&(name, weight)| (name.to_string(), weight))
+                .collect()
+        };
+        let (model, cost) = min_weight_model(parse2("{a,b}"), &weights(&[("a", 5)])).unwrap();
+        assert!(!model["a"] && model["b"]);
+        assert_eq!(cost, 1);
+        let (model, cost) = min_weight_model(parse2("{a,b}"), &weights(&[("b", 2)])).unwrap();
+        assert!(model["a"] && !model["b"]);
+        assert_eq!(cost, 1);
+
+        // `a` alone is cheaper than `b` and `c` unless it weighs more
+        let cnf = parse2("{a,b},{a,c}");
+        let (model, cost) = min_weight_model(cnf.clone(), &Map::new()).unwrap();
+        assert_eq!((true_count(&model), cost), (1, 1));
+        let (model, cost) = min_weight_model(cnf.clone(), &weights(&[("a", 3)])).unwrap();
+        assert!(!model["a"] && verify(&cnf, &model));
+        assert_eq!(cost, 2);
+        // a weight of 0 costs nothing either way
+        let (_, cost) = min_weight_model(parse2("{a},{b,-b}"), &weights(&[("b", 0)])).unwrap();
+        assert_eq!(cost, 1);
+
+        // weights this large would not fit as one clause per unit
+        let heavy = weights(&[("a", 1_000_000), ("b", 2_500_000), ("c", 1_999_999)]);
+        let (model, cost) = min_weight_model(cnf.clone(), &heavy).unwrap();
+        assert!(model["a"] && !model["b"] && !model["c"]);
+        assert_eq!(cost, 1_000_000);
+        let (model, cost) = min_weight_model(parse2("{a,b},{a,c},{-a}"), &heavy).unwrap();
+        assert!(model["b"] && model["c"]);
+        assert_eq!(cost, 4_499_999);
+
+        assert_eq!(min_weight_model(parse2("{a},{-a}"), &Map::new()), None);
+    }
+
+    #[test]
+    fn test_prime_implicant() {
+        let cnf = parse2("{a,b}");
+        let model = crate::all_models(cnf.clone()).next().unwrap();
+        assert_eq!(prime_implicant(&cnf, &model).len(), 1);
+
+        // a covers both clauses, so b is not needed even though it comes
+        // first
+        let cnf = parse2("{b,a},{a,-c},{c,d}");
+        let model = [("a", true), ("b", true), ("c", false), ("d", true)]
+            .map(|(name, value)| (name.to_string(), value))
+            .into_iter()
+            .collect();
+        assert_eq!(prime_implicant(&cnf, &model), parse2("{a,d}").remove(0));
+
+        for seed in 0..30 {
+            let cnf = crate::random_3sat(12, 40, seed);
+            let Some(model) = crate::solve_ref(&cnf) else {
+                continue;
+            };
+            let implicant = prime_implicant(&cnf, &model);
+            let covers = |literals: &[Literal]| {
+                cnf.iter()
+                    .all(|clause| clause.iter().any(|literal| literals.contains(literal)))
+            };
+            assert!(covers(&implicant), "seed {seed}");
+            for i in 0..implicant.len() {
+                let mut smaller = implicant.clone();
+                smaller.remove(i);
+                assert!(!covers(&smaller), "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_minimal_model_is_subset_minimal() {
+        for seed in 0..30 {
+            let cnf = crate::random_3sat(12, 40, seed);
+            let Some(model) = minimal_model(cnf.clone()) else {
+                continue;
+            };
+            assert!(verify(&cnf, &model));
+            for (name, _) in model.iter().filter(|&(_, &value)| value) {
+                let mut flipped = model.clone();
+                flipped.insert(name.clone(), false);
+                assert!(!verify(&cnf, &flipped), "seed {seed}: {name}");
+            }
+        }
+    }
+}
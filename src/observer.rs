@@ -0,0 +1,33 @@
+/// Hooks called by the solver as the search goes on, for progress reports
+/// and logging. Every method does nothing unless overridden, and `()` is an
+/// observer that ignores everything.
+///
+/// ```
+/// use sat_solver::{SolverObserver, parse2, solve_with_observer};
+///
+/// // a dot every 1000 conflicts
+/// struct Dots(u64);
+///
+/// impl SolverObserver for Dots {
+///     fn on_conflict(&mut self) {
+///         self.0 += 1;
+///         if self.0 % 1000 == 0 {
+///             eprint!(".");
+///         }
+///     }
+/// }
+///
+/// let mut dots = Dots(0);
+/// solve_with_observer(parse2("{a,b},{-a,b},{a,-b},{-a,-b}"), &mut dots);
+/// assert!(dots.0 > 0);
+/// ```
+pub trait SolverObserver {
+    /// A variable was assigned by branching.
+    fn on_decision(&mut self, _var: &str, _value: bool) {}
+    /// A clause was found false.
+    fn on_conflict(&mut self) {}
+    /// The search started over from the top.
+    fn on_restart(&mut self) {}
+}
+
+impl SolverObserver for () {}
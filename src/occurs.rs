@@ -0,0 +1,86 @@
+use crate::{CNF, Clause, Literal, VarMap};
+use alloc::vec::Vec;
+
+/// The clauses each literal occurs in, by clause index, kept up to date as
+/// clauses are added and removed. Variables are numbered in order of first
+/// occurrence, so iterating over them is deterministic.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Occurrences {
+    vars: VarMap,
+    // clause indices per variable, positive occurrences first
+    lists: Vec<[Vec<usize>; 2]>,
+}
+
+impl Occurrences {
+    pub(crate) fn from_cnf(cnf: &CNF) -> Self {
+        let mut occurrences = Occurrences::default();
+        for (index, clause) in cnf.iter().enumerate() {
+            occurrences.add(index, clause);
+        }
+        occurrences
+    }
+
+    pub(crate) fn add(&mut self, index: usize, clause: &Clause) {
+        for literal in clause {
+            let var = self.vars.intern(&literal.name) as usize;
+            if var == self.lists.len() {
+                self.lists.push([Vec::new(), Vec::new()]);
+            }
+            let list = &mut self.lists[var][literal.negated as usize];
+            // a repeated literal is listed once
+            if list.last() != Some(&index) {
+                list.push(index);
+            }
+        }
+    }
+
+    pub(crate) fn remove(&mut self, index: usize, clause: &Clause) {
+        for literal in clause {
+            if let Some(var) = self.vars.id(&literal.name) {
+                self.lists[var as usize][literal.negated as usize].retain(|&i| i != index);
+            }
+        }
+    }
+
+    /// Clauses containing `name` with the given sign, in the order they
+    /// were added.
+    pub(crate) fn of(&self, name: &str, negated: bool) -> &[usize] {
+        match self.vars.id(name) {
+            Some(var) => &self.lists[var as usize][negated as usize],
+            None => &[],
+        }
+    }
+
+    pub(crate) fn of_literal(&self, literal: &Literal) -> &[usize] {
+        self.of(&literal.name, literal.negated)
+    }
+
+    /// Every variable seen so far, in order of first occurrence, including
+    /// those whose clauses have all been removed.
+    pub(crate) fn names(&self) -> impl Iterator<Item = &str> {
+        self.vars.names()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse2;
+
+    #[test]
+    fn test_occurrences() {
+        let cnf = parse2("{a,-b},{b,c},{-b,a}");
+        let mut occurrences = Occurrences::from_cnf(&cnf);
+        assert_eq!(occurrences.names().collect::<Vec<_>>(), ["a", "b", "c"]);
+        assert_eq!(occurrences.of("a", false), [0, 2]);
+        assert_eq!(occurrences.of("b", true), [0, 2]);
+        assert_eq!(occurrences.of("b", false), [1]);
+        assert!(occurrences.of("a", true).is_empty());
+        assert!(occurrences.of("d", false).is_empty());
+
+        occurrences.remove(0, &cnf[0]);
+        assert_eq!(occurrences.of_literal(&cnf[0][1]), [2]);
+        occurrences.add(3, &parse2("{-a}")[0]);
+        assert_eq!(occurrences.of("a", true), [3]);
+    }
+}
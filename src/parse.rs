@@ -0,0 +1,1091 @@
+use crate::preprocess::dedup_literals;
+use crate::{CNF, Clause, Literal, Map};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::ops::Range;
+
+// `-a`, `!a` and `~a` all negate `a`
+const NEGATIONS: [char; 3] = ['-', '!', '~'];
+
+pub fn parse2(formula: &str) -> CNF {
+    clauses_as_written(formula)
+        .into_iter()
+        .map(dedup_literals)
+        .collect()
+}
+
+// the clauses of `parse2` before duplicate literals are collapsed
+fn clauses_as_written(formula: &str) -> CNF {
+    formula
+        .replace(" ", "")
+        .split("},")
+        .map(|s| {
+            s.replace("{", "")
+                .replace("}", "")
+                .split(",")
+                .map(|exp| {
+                    // only leading markers negate, `x-1` is a name
+                    let name = exp.trim_start_matches(NEGATIONS);
+                    Literal {
+                        name: name.to_string(),
+                        negated: name.len() != exp.len(),
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Parses several brace syntax formulas separated by blank lines, each
+/// like `parse2`, where a formula may also span several lines. Blank lines
+/// at the start, at the end or in a row do not make empty formulas.
+pub fn parse_many(input: &str) -> Vec<CNF> {
+    let mut formulas = Vec::new();
+    let mut block = String::new();
+    for line in input.lines().chain([""]) {
+        if !line.trim().is_empty() {
+            block.extend(line.chars().filter(|c| !c.is_whitespace()));
+        } else if !block.is_empty() {
+            formulas.push(parse2(&block));
+            block.clear();
+        }
+    }
+    formulas
+}
+
+/// Something in a formula that is valid but likely a modeling mistake, see
+/// `parse_with_diagnostics`. Clauses are counted from 0 in the order they
+/// are written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// Clause `index` has the same literals as the earlier clause `first`.
+    DuplicateClause { index: usize, first: usize },
+    /// Clause `index` has `var` both positive and negated, so it always
+    /// holds.
+    Tautology { index: usize, var: String },
+    /// Clause `index` has `literal` more than once.
+    DuplicateLiteral { index: usize, literal: Literal },
+    /// `var` occurs in a single clause, once.
+    SingleOccurrence { var: String },
+}
+
+impl core::fmt::Display for Warning {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Warning::DuplicateClause { index, first } => {
+                write!(f, "clause {index} repeats clause {first}")
+            }
+            Warning::Tautology { index, var } => {
+                write!(f, "clause {index} has both `{var}` and its negation")
+            }
+            Warning::DuplicateLiteral { index, literal } => {
+                write!(f, "clause {index} has `{literal}` more than once")
+            }
+            Warning::SingleOccurrence { var } => write!(f, "`{var}` occurs only once"),
+        }
+    }
+}
+
+/// Parses brace syntax as leniently as `parse2`, also returning warnings
+/// about what is likely unintended: repeated clauses, clauses that always
+/// hold, literals repeated within a clause and variables that occur only
+/// once. None of them reject the input, the formula is what `parse2`
+/// returns.
+///
+/// Warnings come in the order of the clauses they are about, each clause's
+/// in the order above, followed by the variables occurring once in order
+/// of first occurrence.
+pub fn parse_with_diagnostics(input: &str) -> (CNF, Vec<Warning>) {
+    let mut warnings = Vec::new();
+    let mut cnf = CNF::new();
+    let mut seen: Map<Vec<Literal>, usize> = Map::new();
+    for (index, written) in clauses_as_written(input).into_iter().enumerate() {
+        let clause = dedup_literals(written.clone());
+        let mut key = clause.clone();
+        key.sort();
+        match seen.get(&key) {
+            Some(&first) => warnings.push(Warning::DuplicateClause { index, first }),
+            None => {
+                seen.insert(key, index);
+            }
+        }
+        if let Some(literal) = clause
+            .iter()
+            .find(|literal| clause.contains(&literal.negate()))
+        {
+            warnings.push(Warning::Tautology {
+                index,
+                var: literal.name.clone(),
+            });
+        }
+        for literal in &clause {
+            if written.iter().filter(|other| *other == literal).count() > 1 {
+                warnings.push(Warning::DuplicateLiteral {
+                    index,
+                    literal: literal.clone(),
+                });
+            }
+        }
+        cnf.push(clause);
+    }
+
+    let mut occurrences: Map<&str, usize> = Map::new();
+    let mut order = Vec::new();
+    for literal in cnf.iter().flatten() {
+        let count = occurrences.entry(literal.name.as_str()).or_insert(0);
+        if *count == 0 {
+            order.push(literal.name.as_str());
+        }
+        *count += 1;
+    }
+    warnings.extend(
+        order
+            .into_iter()
+            .filter(|var| occurrences[var] == 1)
+            .map(|var| Warning::SingleOccurrence {
+                var: var.to_string(),
+            }),
+    );
+    (cnf, warnings)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    MissingHeader,
+    InvalidHeader {
+        line: usize,
+    },
+    InvalidWeight {
+        line: usize,
+        token: String,
+    },
+    InvalidLiteral {
+        line: usize,
+        token: String,
+    },
+    VariableOutOfRange {
+        line: usize,
+        var: usize,
+        declared: usize,
+    },
+    ClauseCountMismatch {
+        declared: usize,
+        found: usize,
+    },
+    EmptyInput,
+    EmptyLiteral {
+        span: Range<usize>,
+    },
+    UnbalancedBrace {
+        span: Range<usize>,
+    },
+    UnterminatedQuote {
+        span: Range<usize>,
+    },
+    Unexpected {
+        span: Range<usize>,
+        found: String,
+    },
+    /// Reading the input failed, see `parse_dimacs_reader`.
+    Io {
+        message: String,
+    },
+}
+
+impl core::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::MissingHeader => write!(f, "missing `p cnf` header"),
+            ParseError::InvalidHeader { line } => write!(f, "line {line}: invalid `p` header"),
+            ParseError::InvalidWeight { line, token } => {
+                write!(f, "line {line}: invalid weight `{token}`")
+            }
+            ParseError::InvalidLiteral { line, token } => {
+                write!(f, "line {line}: invalid literal `{token}`")
+            }
+            ParseError::VariableOutOfRange {
+                line,
+                var,
+                declared,
+            } => write!(
+                f,
+                "line {line}: variable {var} exceeds declared variable count {declared}"
+            ),
+            ParseError::EmptyInput => write!(f, "empty formula"),
+            ParseError::EmptyLiteral { span } => {
+                write!(f, "{}..{}: empty literal", span.start, span.end)
+            }
+            ParseError::UnbalancedBrace { span } => {
+                write!(f, "{}: unbalanced brace", span.start)
+            }
+            ParseError::UnterminatedQuote { span } => {
+                write!(f, "{}: unterminated quote", span.start)
+            }
+            ParseError::Unexpected { span, found } => {
+                write!(f, "{}: unexpected `{found}`", span.start)
+            }
+            ParseError::Io { message } => write!(f, "read error: {message}"),
+            ParseError::ClauseCountMismatch { declared, found } => {
+                write!(
+                    f,
+                    "header declares {declared} clauses but {found} were found"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Open,
+    Close,
+    Comma,
+    Name,
+    Implies,
+    Iff,
+}
+
+// the operator starting `rest`, when implications are recognized
+fn operator(rest: &str, sugar: bool) -> Option<(Token, usize)> {
+    if !sugar {
+        None
+    } else if rest.starts_with("<->") {
+        Some((Token::Iff, 3))
+    } else if rest.starts_with("->") {
+        Some((Token::Implies, 2))
+    } else {
+        None
+    }
+}
+
+// splits brace syntax into tokens with their byte spans, names run until
+// the next brace, comma, whitespace or operator outside of quotes
+fn tokenize(input: &str, sugar: bool) -> Vec<(Token, Range<usize>)> {
+    let mut tokens = Vec::new();
+    let mut chars = input.char_indices().peekable();
+
+    while let Some((start, c)) = chars.next() {
+        if let Some((token, len)) = operator(&input[start..], sugar) {
+            tokens.push((token, start..start + len));
+            // operators are ASCII
+            for _ in 1..len {
+                chars.next();
+            }
+            continue;
+        }
+
+        let token = match c {
+            '{' => Token::Open,
+            '}' => Token::Close,
+            ',' => Token::Comma,
+            c if c.is_whitespace() => continue,
+            _ => {
+                let mut end = start + c.len_utf8();
+                let mut quoted = c == '"';
+                while let Some(&(i, c)) = chars.peek() {
+                    if !quoted
+                        && (c.is_whitespace()
+                            || "{},".contains(c)
+                            || operator(&input[i..], sugar).is_some())
+                    {
+                        break;
+                    }
+                    if c == '"' {
+                        quoted = !quoted;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push((Token::Name, start..end));
+                continue;
+            }
+        };
+        tokens.push((token, start..start + 1));
+    }
+
+    tokens
+}
+
+/// Parses the `{a,-b},{c}` syntax like `parse2`, but rejects malformed
+/// input instead of guessing. `{}` is accepted as the empty clause.
+///
+/// A name in double quotes may contain any character but `"`, so
+/// `{"pin.3/out", -"a,b"}` has the variables `pin.3/out` and `a,b`.
+/// Negation markers inside the quotes are part of the name.
+pub fn parse2_checked(input: &str) -> Result<CNF, ParseError> {
+    parse_braces(input, false).map(|(cnf, _)| cnf)
+}
+
+/// Parses brace syntax like `parse2_checked`, where a clause may also be an
+/// implication `{a -> b}`, standing for `{-a,b}`, or an equivalence
+/// `{a <-> b}`, standing for `{-a,b},{a,-b}`. Both sides are single
+/// literals.
+pub fn parse_sugar(input: &str) -> Result<CNF, ParseError> {
+    parse_braces(input, true).map(|(cnf, _)| cnf)
+}
+
+// also returns where each clause starts, the offset of its opening brace
+fn parse_braces(input: &str, sugar: bool) -> Result<(CNF, Vec<usize>), ParseError> {
+    let tokens = tokenize(input, sugar);
+    if tokens.is_empty() {
+        return Err(ParseError::EmptyInput);
+    }
+
+    let unexpected = |span: &Range<usize>| ParseError::Unexpected {
+        span: span.clone(),
+        found: input[span.clone()].to_string(),
+    };
+
+    // the literal that should follow a brace, comma or operator ending at
+    // `gap`, inside the clause opened at `open`
+    let literal =
+        |token: Option<&(Token, Range<usize>)>, gap: usize, open: &Range<usize>| match token {
+            Some((Token::Name, span)) => {
+                let text = &input[span.clone()];
+                let name = text.trim_start_matches(NEGATIONS);
+                let negated = name.len() != text.len();
+                let name = match name.matches('"').count() {
+                    0 => name,
+                    // everything between the quotes is the name, markers
+                    // included
+                    2 if name.starts_with('"') && name.ends_with('"') => &name[1..name.len() - 1],
+                    1 => return Err(ParseError::UnterminatedQuote { span: span.clone() }),
+                    _ => return Err(unexpected(span)),
+                };
+                if name.is_empty() {
+                    return Err(ParseError::EmptyLiteral { span: span.clone() });
+                }
+                Ok(Literal {
+                    name: name.to_string(),
+                    negated,
+                })
+            }
+            Some((Token::Comma | Token::Close, span)) => Err(ParseError::EmptyLiteral {
+                span: gap..span.start,
+            }),
+            Some((Token::Open, span)) => Err(ParseError::UnbalancedBrace { span: span.clone() }),
+            Some((Token::Implies | Token::Iff, span)) => Err(unexpected(span)),
+            None => Err(ParseError::UnbalancedBrace { span: open.clone() }),
+        };
+    let negate = |literal: &Literal| Literal {
+        name: literal.name.clone(),
+        negated: !literal.negated,
+    };
+
+    let mut cnf = CNF::new();
+    let mut starts = Vec::new();
+    let mut tokens = tokens.iter().peekable();
+    'clauses: loop {
+        // clauses are separated by commas
+        if !cnf.is_empty() {
+            match tokens.next() {
+                None => break,
+                Some((Token::Comma, _)) => {}
+                Some((Token::Close, span)) => {
+                    return Err(ParseError::UnbalancedBrace { span: span.clone() });
+                }
+                Some((_, span)) => return Err(unexpected(span)),
+            }
+        }
+
+        let open = match tokens.next() {
+            Some((Token::Open, span)) => span,
+            Some((Token::Close, span)) => {
+                return Err(ParseError::UnbalancedBrace { span: span.clone() });
+            }
+            Some((_, span)) => return Err(unexpected(span)),
+            None => return Err(ParseError::EmptyInput),
+        };
+
+        let mut clause = Clause::new();
+        if let Some((Token::Close, _)) = tokens.peek() {
+            tokens.next();
+            cnf.push(clause);
+            starts.push(open.start);
+            continue;
+        }
+
+        // the position right after the previous brace or comma, where an
+        // empty literal would start
+        let mut gap = open.end;
+        loop {
+            clause.push(literal(tokens.next(), gap, open)?);
+
+            match tokens.next() {
+                Some((Token::Comma, span)) => gap = span.end,
+                Some((Token::Close, _)) => break,
+                Some((op @ (Token::Implies | Token::Iff), span)) if clause.len() == 1 => {
+                    let lhs = clause.remove(0);
+                    let rhs = literal(tokens.next(), span.end, open)?;
+                    match tokens.next() {
+                        Some((Token::Close, _)) => {}
+                        Some((Token::Open, span)) => {
+                            return Err(ParseError::UnbalancedBrace { span: span.clone() });
+                        }
+                        Some((_, span)) => return Err(unexpected(span)),
+                        None => return Err(ParseError::UnbalancedBrace { span: open.clone() }),
+                    }
+
+                    cnf.push(dedup_literals(vec![negate(&lhs), rhs.clone()]));
+                    starts.push(open.start);
+                    if *op == Token::Iff {
+                        cnf.push(dedup_literals(vec![lhs, negate(&rhs)]));
+                        starts.push(open.start);
+                    }
+                    continue 'clauses;
+                }
+                Some((Token::Name | Token::Implies | Token::Iff, span)) => {
+                    return Err(unexpected(span));
+                }
+                Some((Token::Open, span)) => {
+                    return Err(ParseError::UnbalancedBrace { span: span.clone() });
+                }
+                None => return Err(ParseError::UnbalancedBrace { span: open.clone() }),
+            }
+        }
+        cnf.push(dedup_literals(clause));
+        starts.push(open.start);
+    }
+
+    Ok((cnf, starts))
+}
+
+/// Parses either syntax: brace syntax when the input starts with a clause,
+/// DIMACS otherwise, where it starts with a comment or the header.
+pub fn parse_formula(input: &str) -> Result<CNF, ParseError> {
+    if input.trim_start().starts_with('{') {
+        parse2_checked(input)
+    } else {
+        parse_dimacs(input)
+    }
+}
+
+pub fn parse_dimacs(input: &str) -> Result<CNF, ParseError> {
+    dimacs_with_lines(input).map(|(cnf, _)| cnf)
+}
+
+fn dimacs_with_lines(input: &str) -> Result<(CNF, Vec<usize>), ParseError> {
+    let mut state = DimacsState::default();
+    for (index, line) in input.lines().enumerate() {
+        if !state.line(index + 1, line)? {
+            break;
+        }
+    }
+    state.finish()
+}
+
+/// Where a clause came from in the input, kept next to the formula in a
+/// `Vec` of the same length, see `parse_with_meta`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClauseMeta {
+    /// The position of the clause in the parsed formula.
+    pub index: usize,
+    /// The line the clause starts on, counting from 1.
+    pub line: usize,
+}
+
+/// Parses either syntax like `parse_formula`, also returning where each
+/// clause came from. A brace syntax implication gives two clauses from
+/// the same line.
+pub fn parse_with_meta(input: &str) -> Result<(CNF, Vec<ClauseMeta>), ParseError> {
+    let (cnf, lines) = if input.trim_start().starts_with('{') {
+        let (cnf, starts) = parse_braces(input, false)?;
+        let lines = starts
+            .into_iter()
+            .map(|start| input[..start].matches('\n').count() + 1)
+            .collect();
+        (cnf, lines)
+    } else {
+        dimacs_with_lines(input)?
+    };
+    let meta = lines
+        .into_iter()
+        .enumerate()
+        .map(|(index, line)| ClauseMeta { index, line })
+        .collect();
+    Ok((cnf, meta))
+}
+
+/// Parses DIMACS like `parse_dimacs`, reading `reader` one line at a time,
+/// so only the formula is kept in memory and never the whole text. Read
+/// errors, including invalid UTF-8, are reported as `ParseError::Io`.
+#[cfg(not(feature = "no_std"))]
+pub fn parse_dimacs_reader<R: std::io::BufRead>(mut reader: R) -> Result<CNF, ParseError> {
+    let mut state = DimacsState::default();
+    let mut line = String::new();
+    for line_no in 1.. {
+        line.clear();
+        let read = reader.read_line(&mut line).map_err(|err| ParseError::Io {
+            message: err.to_string(),
+        })?;
+        if read == 0 || !state.line(line_no, &line)? {
+            break;
+        }
+    }
+    state.finish().map(|(cnf, _)| cnf)
+}
+
+// a DIMACS parse so far, fed one line at a time
+#[derive(Default)]
+struct DimacsState {
+    header: Option<(usize, usize)>,
+    cnf: CNF,
+    clause: Clause,
+    // the line each clause of `cnf` starts on, and where `clause` started
+    lines: Vec<usize>,
+    start: Option<usize>,
+}
+
+impl DimacsState {
+    // false once the rest of the input is to be ignored
+    fn line(&mut self, line_no: usize, line: &str) -> Result<bool, ParseError> {
+        let line = line.trim();
+
+        // comments and blank lines carry no clauses
+        if line.is_empty() || line.starts_with('c') {
+            return Ok(true);
+        }
+        // SATLIB files end with a `%` marker followed by junk
+        if line.starts_with('%') {
+            return Ok(false);
+        }
+
+        if line.starts_with('p') {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            self.header = match fields[..] {
+                ["p", "cnf", vars, clauses] => match (vars.parse(), clauses.parse()) {
+                    (Ok(vars), Ok(clauses)) if self.header.is_none() => Some((vars, clauses)),
+                    _ => return Err(ParseError::InvalidHeader { line: line_no }),
+                },
+                _ => return Err(ParseError::InvalidHeader { line: line_no }),
+            };
+            return Ok(true);
+        }
+
+        let (declared_vars, _) = self.header.ok_or(ParseError::MissingHeader)?;
+
+        // a clause may span several lines, only `0` terminates it
+        for token in line.split_whitespace() {
+            let literal = dimacs_literal(token, line_no, Some(declared_vars))?;
+            let start = *self.start.get_or_insert(line_no);
+            match literal {
+                Some(literal) => self.clause.push(literal),
+                None => {
+                    self.cnf
+                        .push(dedup_literals(core::mem::take(&mut self.clause)));
+                    self.lines.push(start);
+                    self.start = None;
+                }
+            }
+        }
+        Ok(true)
+    }
+
+    fn finish(mut self) -> Result<(CNF, Vec<usize>), ParseError> {
+        let (_, declared_clauses) = self.header.ok_or(ParseError::MissingHeader)?;
+
+        // tolerate a missing `0` after the last clause
+        if let Some(start) = self.start {
+            self.cnf.push(dedup_literals(self.clause));
+            self.lines.push(start);
+        }
+
+        if self.cnf.len() != declared_clauses {
+            return Err(ParseError::ClauseCountMismatch {
+                declared: declared_clauses,
+                found: self.cnf.len(),
+            });
+        }
+
+        Ok((self.cnf, self.lines))
+    }
+}
+
+// a DIMACS literal, `None` for the `0` ending a clause. literals are
+// `i32`, as solvers and `solve_under_ints` take them, so the name of a
+// variable always parses back into a `u32`
+pub(crate) fn dimacs_literal(
+    token: &str,
+    line: usize,
+    declared_vars: Option<usize>,
+) -> Result<Option<Literal>, ParseError> {
+    let value: i32 = token.parse().map_err(|_| ParseError::InvalidLiteral {
+        line,
+        token: token.to_string(),
+    })?;
+    if value == 0 {
+        return Ok(None);
+    }
+
+    let var = value.unsigned_abs() as usize;
+    if let Some(declared) = declared_vars
+        && var > declared
+    {
+        return Err(ParseError::VariableOutOfRange {
+            line,
+            var,
+            declared,
+        });
+    }
+    Ok(Some(Literal {
+        name: var.to_string(),
+        negated: value < 0,
+    }))
+}
+
+/// Parses weighted MaxSAT instances in the WCNF format into the hard
+/// clauses and the soft clauses with their weights. Every clause starts
+/// with its weight. Both the old format, with a `p wcnf <vars> <clauses>
+/// [top]` header where clauses weighing at least `top` are hard, and the
+/// newer one without header, where hard clauses start with `h`, are
+/// accepted. Under an old header without `top` every clause is soft.
+pub fn parse_wcnf(input: &str) -> Result<(CNF, Vec<(u64, Clause)>), ParseError> {
+    // declared variables, declared clauses and top
+    let mut header: Option<(usize, usize, Option<u64>)> = None;
+    let mut hard = CNF::new();
+    let mut soft = Vec::new();
+    // the weight of the clause being read, `None` for a hard clause, and
+    // its literals so far
+    let mut current: Option<(Option<u64>, Clause)> = None;
+
+    for (index, line) in input.lines().enumerate() {
+        let line_no = index + 1;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('c') {
+            continue;
+        }
+
+        if line.starts_with('p') {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let parsed = match fields[..] {
+                ["p", "wcnf", vars, clauses] => match (vars.parse(), clauses.parse()) {
+                    (Ok(vars), Ok(clauses)) => Some((vars, clauses, None)),
+                    _ => None,
+                },
+                ["p", "wcnf", vars, clauses, top] => {
+                    match (vars.parse(), clauses.parse(), top.parse()) {
+                        (Ok(vars), Ok(clauses), Ok(top)) => Some((vars, clauses, Some(top))),
+                        _ => None,
+                    }
+                }
+                _ => None,
+            };
+            match parsed {
+                Some(parsed) if header.is_none() && hard.is_empty() && soft.is_empty() => {
+                    header = Some(parsed)
+                }
+                _ => return Err(ParseError::InvalidHeader { line: line_no }),
+            }
+            continue;
+        }
+
+        let declared_vars = header.map(|(vars, _, _)| vars);
+        for token in line.split_whitespace() {
+            let Some((weight, clause)) = &mut current else {
+                let weight = match (token, header) {
+                    ("h", None) => None,
+                    _ => {
+                        let weight: u64 = token.parse().map_err(|_| ParseError::InvalidWeight {
+                            line: line_no,
+                            token: token.to_string(),
+                        })?;
+                        match header {
+                            Some((_, _, Some(top))) if weight >= top => None,
+                            _ => Some(weight),
+                        }
+                    }
+                };
+                current = Some((weight, Clause::new()));
+                continue;
+            };
+
+            match dimacs_literal(token, line_no, declared_vars)? {
+                Some(literal) => clause.push(literal),
+                None => {
+                    let clause = dedup_literals(core::mem::take(clause));
+                    match *weight {
+                        Some(weight) => soft.push((weight, clause)),
+                        None => hard.push(clause),
+                    }
+                    current = None;
+                }
+            }
+        }
+    }
+
+    // tolerate a missing `0` after the last clause
+    if let Some((weight, clause)) = current
+        && !clause.is_empty()
+    {
+        let clause = dedup_literals(clause);
+        match weight {
+            Some(weight) => soft.push((weight, clause)),
+            None => hard.push(clause),
+        }
+    }
+
+    if let Some((_, declared, _)) = header {
+        let found = hard.len() + soft.len();
+        if found != declared {
+            return Err(ParseError::ClauseCountMismatch { declared, found });
+        }
+    }
+
+    Ok((hard, soft))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Assignment, SatResult, dpll};
+
+    #[test]
+    fn test_parse2_negation_markers() {
+        let cnf = parse2("{!a,-b,~c},{d}");
+        assert_eq!(cnf[0].len(), 3);
+        for (literal, name) in cnf[0].iter().zip(["a", "b", "c"]) {
+            assert_eq!(literal.name, name);
+            assert!(literal.negated);
+        }
+        assert!(!cnf[1][0].negated);
+    }
+
+    #[test]
+    fn test_parse_many() {
+        let input = "\n{a,-b},{c}\n\n  \n{a},\n{-a}\n\n{x}\n\n\n";
+        let formulas = parse_many(input);
+        assert_eq!(
+            formulas,
+            vec![parse2("{a,-b},{c}"), parse2("{a},{-a}"), parse2("{x}")]
+        );
+        assert!(parse_many("").is_empty());
+        assert!(parse_many("\n \n").is_empty());
+    }
+
+    #[test]
+    fn test_parse_with_diagnostics() {
+        let (cnf, warnings) = parse_with_diagnostics("{a,-a,b},{-b,c},{b,-c}");
+        assert_eq!(cnf, parse2("{a,-a,b},{-b,c},{b,-c}"));
+        assert_eq!(
+            warnings,
+            [Warning::Tautology {
+                index: 0,
+                var: "a".to_string()
+            }]
+        );
+
+        let (cnf, warnings) = parse_with_diagnostics("{a,b,a},{b,a},{-b,-c},{-a}");
+        assert_eq!(cnf, parse2("{a,b},{b,a},{-b,-c},{-a}"));
+        assert_eq!(
+            warnings,
+            [
+                Warning::DuplicateLiteral {
+                    index: 0,
+                    literal: Literal::from(("a", false))
+                },
+                Warning::DuplicateClause { index: 1, first: 0 },
+                Warning::SingleOccurrence {
+                    var: "c".to_string()
+                },
+            ]
+        );
+        assert!(parse_with_diagnostics("{a,b},{-a,-b}").1.is_empty());
+    }
+
+    #[test]
+    fn test_parse_dashes_inside_names() {
+        for cnf in [parse2("{x-1,-y}"), parse2_checked("{x-1,-y}").unwrap()] {
+            assert_eq!(cnf[0][0].name, "x-1");
+            assert!(!cnf[0][0].negated);
+            assert_eq!(cnf[0][1].name, "y");
+            assert!(cnf[0][1].negated);
+        }
+        assert_eq!(parse2("{!a-b~}")[0][0].name, "a-b~");
+    }
+
+    #[test]
+    fn test_parse_collapses_duplicate_literals() {
+        assert_eq!(parse2("{a,a,-b,-b}"), parse2("{a,-b}"));
+        assert_eq!(parse2("{a,a,-b,-b}")[0].len(), 2);
+        assert_eq!(parse2_checked("{a, a, -b, -b}").unwrap()[0].len(), 2);
+        assert_eq!(
+            parse_dimacs("p cnf 2 1\n1 1 -2 -2 0\n").unwrap()[0].len(),
+            2
+        );
+        // a clause with both polarities is left to `remove_tautologies`
+        assert_eq!(parse2("{a,-a,a}")[0].len(), 2);
+    }
+
+    #[test]
+    fn test_parse2_checked() {
+        let cnf = parse2_checked(" {a, -b}, {!c},{}").unwrap();
+        assert_eq!(cnf.len(), 3);
+        assert_eq!(cnf[..2], parse2("{a,-b},{!c}"));
+        assert!(cnf[2].is_empty());
+    }
+
+    #[test]
+    fn test_parse2_checked_errors() {
+        assert_eq!(parse2_checked(""), Err(ParseError::EmptyInput));
+        assert_eq!(parse2_checked("  "), Err(ParseError::EmptyInput));
+        assert_eq!(
+            parse2_checked("{a,,b}"),
+            Err(ParseError::EmptyLiteral { span: 3..3 })
+        );
+        assert_eq!(
+            parse2_checked("{a, }"),
+            Err(ParseError::EmptyLiteral { span: 3..4 })
+        );
+        assert_eq!(
+            parse2_checked("{a,-}"),
+            Err(ParseError::EmptyLiteral { span: 3..4 })
+        );
+        assert_eq!(
+            parse2_checked("{a},{b"),
+            Err(ParseError::UnbalancedBrace { span: 4..5 })
+        );
+        assert_eq!(
+            parse2_checked("{a}},{b}"),
+            Err(ParseError::UnbalancedBrace { span: 3..4 })
+        );
+        assert_eq!(
+            parse2_checked("{a}{b}"),
+            Err(ParseError::Unexpected {
+                span: 3..4,
+                found: "{".to_string()
+            })
+        );
+        assert_eq!(
+            parse2_checked("{a b}"),
+            Err(ParseError::Unexpected {
+                span: 3..4,
+                found: "b".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_quoted_names() {
+        let cnf = parse2_checked(r#"{"a,b", c},{-"pin.3/out", "-x"}"#).unwrap();
+        let names: Vec<(&str, bool)> = cnf
+            .iter()
+            .flatten()
+            .map(|literal| (literal.name.as_str(), literal.negated))
+            .collect();
+        assert_eq!(
+            names,
+            [
+                ("a,b", false),
+                ("c", false),
+                ("pin.3/out", true),
+                ("-x", false)
+            ]
+        );
+
+        // spaces, braces and operators are kept inside quotes
+        let cnf = parse_sugar(r#"{"a -> b" -> "{c}"}"#).unwrap();
+        assert_eq!(cnf[0][0].name, "a -> b");
+        assert!(cnf[0][0].negated);
+        assert_eq!(cnf[0][1].name, "{c}");
+
+        assert_eq!(
+            parse2_checked(r#"{"a,b}"#),
+            Err(ParseError::UnterminatedQuote { span: 1..6 })
+        );
+        assert_eq!(
+            parse2_checked(r#"{a,""}"#),
+            Err(ParseError::EmptyLiteral { span: 3..5 })
+        );
+        assert!(matches!(
+            parse2_checked(r#"{a"b"c}"#),
+            Err(ParseError::Unexpected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_sugar() {
+        assert_eq!(parse_sugar("{a -> b}"), Ok(parse2("{-a,b}")));
+        assert_eq!(parse_sugar("{a <-> b}"), Ok(parse2("{-a,b},{a,-b}")));
+        assert_eq!(
+            parse_sugar("{c},{-a->b},{x-1<->-y},{d,e}"),
+            Ok(parse2("{c},{a,b},{-x-1,-y},{x-1,y},{d,e}"))
+        );
+        assert_eq!(
+            parse_sugar("{a,b -> c}"),
+            Err(ParseError::Unexpected {
+                span: 5..7,
+                found: "->".to_string()
+            })
+        );
+        assert_eq!(
+            parse_sugar("{a -> }"),
+            Err(ParseError::EmptyLiteral { span: 5..6 })
+        );
+        assert!(parse_sugar("{a -> b, c}").is_err());
+        assert!(parse_sugar("{a -> b").is_err());
+
+        // without the sugar `->` is part of a name
+        assert_eq!(parse2_checked("{a->b}").unwrap()[0][0].name, "a->b");
+    }
+
+    #[test]
+    fn test_parse_formula() {
+        assert_eq!(parse_formula("  {a,-b},{c}"), Ok(parse2("{a,-b},{c}")));
+        assert_eq!(
+            parse_formula("c comment\np cnf 2 1\n1 -2 0\n"),
+            parse_dimacs("p cnf 2 1\n1 -2 0\n")
+        );
+        assert_eq!(parse_formula("1 2 0"), Err(ParseError::MissingHeader));
+        assert!(parse_formula("{a,}").is_err());
+    }
+
+    #[test]
+    fn test_parse_with_meta() {
+        let lines = |input| {
+            let (cnf, meta) = parse_with_meta(input).unwrap();
+            assert_eq!(cnf.len(), meta.len());
+            assert!(meta.iter().enumerate().all(|(i, meta)| meta.index == i));
+            meta.iter().map(|meta| meta.line).collect::<Vec<_>>()
+        };
+        // a clause spanning lines starts where its first literal is
+        assert_eq!(
+            lines("c example\np cnf 3 3\n1 -3 0\n2 3\n-1 0 0\n"),
+            [3, 4, 5]
+        );
+        assert_eq!(lines("p cnf 1 1\n\n1"), [3]);
+        assert_eq!(lines("{a,b},\n{-a},\n\n{c,\n-b}"), [1, 2, 4]);
+    }
+
+    #[test]
+    fn test_parse_dimacs() {
+        let input = "c example\np cnf 3 2\n1 -3 0\n2 3\n-1 0\n";
+        let cnf = parse_dimacs(input).unwrap();
+        assert_eq!(cnf.len(), 2);
+        assert_eq!(cnf[0].len(), 2);
+        assert_eq!(cnf[0][1].name, "3");
+        assert!(cnf[0][1].negated);
+        assert_eq!(cnf[1].len(), 3);
+        assert!(matches!(
+            dpll(cnf, &mut Assignment::new()),
+            SatResult::Sat(_)
+        ));
+    }
+
+    #[cfg(not(feature = "no_std"))]
+    #[test]
+    fn test_parse_dimacs_reader() {
+        let input = "c fixture\np cnf 3 3\n1 -2 0\n2\n3 0\r\n\n-1 0\n%\n0\n";
+        let cnf = parse_dimacs_reader(std::io::Cursor::new(input)).unwrap();
+        assert_eq!(cnf, parse_dimacs(input).unwrap());
+        assert_eq!(cnf.len(), 3);
+        assert_eq!(cnf[1].len(), 2);
+
+        let reader = std::io::BufReader::with_capacity(4, "p cnf 2 1\n1 3 0\n".as_bytes());
+        assert_eq!(
+            parse_dimacs_reader(reader),
+            Err(ParseError::VariableOutOfRange {
+                line: 2,
+                var: 3,
+                declared: 2
+            })
+        );
+        let invalid: &[u8] = b"p cnf 1 1\n\xff 0\n";
+        assert!(matches!(
+            parse_dimacs_reader(invalid),
+            Err(ParseError::Io { .. })
+        ));
+    }
+
+    #[test]
+    fn test_parse_dimacs_satlib_trailer() {
+        let input = "p cnf 2 2\n 1 2 0\n-1 0\n%\n0\n";
+        let cnf = parse_dimacs(input).unwrap();
+        assert_eq!(cnf.len(), 2);
+    }
+
+    #[test]
+    fn test_parse_wcnf() {
+        let input = "c old format\np wcnf 3 4 10\n10 1 -2 0\n3 2 0\n1 -1 3 0\n10 -3 0\n";
+        let (hard, soft) = parse_wcnf(input).unwrap();
+        assert_eq!(hard, parse2("{1,-2},{-3}"));
+        assert_eq!(
+            soft,
+            vec![
+                (3, parse2("{2}").remove(0)),
+                (1, parse2("{-1,3}").remove(0))
+            ]
+        );
+
+        let input = "c new format\nh 1 -2 0\n3 2 0\n1 -1 3 0\nh -3 0\n";
+        assert_eq!(parse_wcnf(input).unwrap(), (hard, soft));
+
+        // without `top` every clause is soft
+        let (hard, soft) = parse_wcnf("p wcnf 2 2\n5 1 2 0\n7 -1\n").unwrap();
+        assert!(hard.is_empty());
+        assert_eq!(soft.iter().map(|(w, _)| *w).collect::<Vec<_>>(), [5, 7]);
+    }
+
+    #[test]
+    fn test_parse_wcnf_errors() {
+        assert_eq!(
+            parse_wcnf("x 1 0\n"),
+            Err(ParseError::InvalidWeight {
+                line: 1,
+                token: "x".to_string()
+            })
+        );
+        // `h` only marks hard clauses without a header
+        assert!(matches!(
+            parse_wcnf("p wcnf 1 1 5\nh 1 0\n"),
+            Err(ParseError::InvalidWeight { line: 2, .. })
+        ));
+        assert_eq!(
+            parse_wcnf("p wcnf 1\n"),
+            Err(ParseError::InvalidHeader { line: 1 })
+        );
+        assert!(matches!(
+            parse_wcnf("p wcnf 1 1 5\n5 2 0\n"),
+            Err(ParseError::VariableOutOfRange { line: 2, .. })
+        ));
+        assert_eq!(
+            parse_wcnf("p wcnf 1 2 5\n5 1 0\n"),
+            Err(ParseError::ClauseCountMismatch {
+                declared: 2,
+                found: 1
+            })
+        );
+    }
+
+    #[test]
+    fn test_parse_dimacs_errors() {
+        assert_eq!(parse_dimacs("1 2 0"), Err(ParseError::MissingHeader));
+        assert_eq!(
+            parse_dimacs("p cnf x 1\n1 0"),
+            Err(ParseError::InvalidHeader { line: 1 })
+        );
+        assert_eq!(
+            parse_dimacs("p cnf 2 1\n1 3 0"),
+            Err(ParseError::VariableOutOfRange {
+                line: 2,
+                var: 3,
+                declared: 2
+            })
+        );
+        assert_eq!(
+            parse_dimacs("p cnf 2 2\n1 2 0"),
+            Err(ParseError::ClauseCountMismatch {
+                declared: 2,
+                found: 1
+            })
+        );
+        assert!(matches!(
+            parse_dimacs("p cnf 2 1\n1 a 0"),
+            Err(ParseError::InvalidLiteral { line: 2, .. })
+        ));
+    }
+}
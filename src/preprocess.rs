@@ -0,0 +1,783 @@
+use crate::dpll::{propagate_only, pure_literal_eliminate, simplify, unit_propagate};
+use crate::occurs::Occurrences;
+use crate::varmap::Lit;
+use crate::{Assignment, CNF, Clause, Literal, VarMap};
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Runs the cheap simplifications until none of them changes anything:
+/// tautologies and repeated literals are removed, unit clauses are
+/// propagated, pure literals are set true, and subsumed clauses are
+/// dropped. The result is satisfiable exactly when `cnf` is, and a formula
+/// found unsatisfiable on the way comes back as a single empty clause.
+///
+/// Variables that get fixed or only occur in removed clauses do not appear
+/// in the result, so its models do not say anything about them. The same
+/// as `preprocess_with` and the built-in passes in that order.
+pub fn preprocess(cnf: CNF) -> CNF {
+    preprocess_with(
+        cnf,
+        vec![
+            Box::new(RemoveTautologies),
+            Box::new(UnitPropagate),
+            Box::new(PureLiterals),
+            Box::new(Subsume),
+        ],
+    )
+}
+
+/// A step of `preprocess_with`. `None` means the pass found the formula
+/// unsatisfiable.
+pub trait PreprocessPass {
+    fn run(&mut self, cnf: CNF) -> Option<CNF>;
+}
+
+/// Drops tautologies and repeated literals, see `remove_tautologies`.
+pub struct RemoveTautologies;
+
+impl PreprocessPass for RemoveTautologies {
+    fn run(&mut self, cnf: CNF) -> Option<CNF> {
+        Some(
+            remove_tautologies(cnf)
+                .into_iter()
+                .map(dedup_literals)
+                .collect(),
+        )
+    }
+}
+
+/// Sets the literals of unit clauses true until there are none left.
+pub struct UnitPropagate;
+
+impl PreprocessPass for UnitPropagate {
+    fn run(&mut self, cnf: CNF) -> Option<CNF> {
+        unit_propagate(cnf, &mut Assignment::new())
+    }
+}
+
+/// Sets the literals true whose variable occurs with one sign only.
+pub struct PureLiterals;
+
+impl PreprocessPass for PureLiterals {
+    fn run(&mut self, cnf: CNF) -> Option<CNF> {
+        pure_literal_eliminate(&cnf, &mut Assignment::new())
+    }
+}
+
+/// Drops subsumed clauses, see `subsume`.
+pub struct Subsume;
+
+impl PreprocessPass for Subsume {
+    fn run(&mut self, cnf: CNF) -> Option<CNF> {
+        Some(subsume(cnf))
+    }
+}
+
+/// Runs `passes` in order, over and over until a round leaves the formula
+/// the same size. A pass returning `None` ends preprocessing with a single
+/// empty clause, like `preprocess` does for an unsatisfiable formula.
+pub fn preprocess_with(mut cnf: CNF, mut passes: Vec<Box<dyn PreprocessPass>>) -> CNF {
+    let size = |cnf: &CNF| cnf.len() + cnf.iter().map(Vec::len).sum::<usize>();
+    loop {
+        let before = size(&cnf);
+        for pass in &mut passes {
+            match pass.run(cnf) {
+                Some(next) => cnf = next,
+                None => return vec![vec![]],
+            }
+        }
+        if size(&cnf) == before {
+            return cnf;
+        }
+    }
+}
+
+/// Sorts the literals of every clause and then the clauses, dropping
+/// repeated literals and repeated clauses. Formulas that only differ in
+/// these ways come out equal, see `Canonical`.
+pub fn canonicalize(cnf: CNF) -> CNF {
+    let mut cnf: CNF = cnf
+        .into_iter()
+        .map(|mut clause| {
+            clause.sort_unstable();
+            clause.dedup();
+            clause
+        })
+        .collect();
+    cnf.sort_unstable();
+    cnf.dedup();
+    cnf
+}
+
+/// A formula in the form `canonicalize` gives it, so comparing or hashing
+/// two of them ignores the order of literals and clauses.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Canonical(CNF);
+
+impl Canonical {
+    pub fn new(cnf: CNF) -> Self {
+        Canonical(canonicalize(cnf))
+    }
+
+    pub fn cnf(&self) -> &CNF {
+        &self.0
+    }
+
+    pub fn into_cnf(self) -> CNF {
+        self.0
+    }
+}
+
+impl From<CNF> for Canonical {
+    fn from(cnf: CNF) -> Self {
+        Canonical::new(cnf)
+    }
+}
+
+/// Drops every clause containing both a literal and its negation. Such
+/// clauses are satisfied by any assignment.
+pub fn remove_tautologies(cnf: CNF) -> CNF {
+    cnf.into_iter()
+        .filter(|clause| !is_tautology(clause))
+        .collect()
+}
+
+/// Drops every clause that contains all the literals of another clause.
+/// Whatever satisfies the smaller clause satisfies the larger one too. Of
+/// several equal clauses only the first is kept.
+pub fn subsume(cnf: CNF) -> CNF {
+    // a clause can only be subsumed by one that is no longer, and of equal
+    // clauses the first one wins
+    let mut order: Vec<usize> = (0..cnf.len()).collect();
+    order.sort_by_key(|&i| cnf[i].len());
+    let mut rank = vec![0; cnf.len()];
+    for (position, &i) in order.iter().enumerate() {
+        rank[i] = position;
+    }
+
+    let occurrences = Occurrences::from_cnf(&cnf);
+    let mut subsumed = vec![false; cnf.len()];
+    for &i in &order {
+        if subsumed[i] {
+            continue;
+        }
+        // every clause containing `i` contains its rarest literal
+        let candidates: Vec<usize> = match cnf[i]
+            .iter()
+            .min_by_key(|literal| occurrences.of_literal(literal).len())
+        {
+            Some(literal) => occurrences.of_literal(literal).to_vec(),
+            None => (0..cnf.len()).collect(),
+        };
+        for j in candidates {
+            if rank[j] > rank[i]
+                && !subsumed[j]
+                && cnf[i].iter().all(|literal| cnf[j].contains(literal))
+            {
+                subsumed[j] = true;
+            }
+        }
+    }
+
+    cnf.into_iter()
+        .zip(subsumed)
+        .filter_map(|(clause, subsumed)| (!subsumed).then_some(clause))
+        .collect()
+}
+
+/// Drops blocked clauses until none are left. A clause is blocked on one
+/// of its literals if resolving it on that literal with any other clause
+/// gives a tautology. Removing a blocked clause keeps the formula
+/// satisfiable or unsatisfiable, but a model of the result may falsify the
+/// removed clause, so this is meant for deciding satisfiability rather than
+/// for finding models of the original formula.
+pub fn eliminate_blocked(cnf: CNF) -> CNF {
+    let mut kept = vec![true; cnf.len()];
+    // removing a clause can block others, so repeat until nothing changes
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for i in 0..cnf.len() {
+            if kept[i] && is_blocked(&cnf, &kept, i) {
+                kept[i] = false;
+                changed = true;
+            }
+        }
+    }
+    cnf.into_iter()
+        .zip(kept)
+        .filter_map(|(clause, kept)| kept.then_some(clause))
+        .collect()
+}
+
+fn is_blocked(cnf: &CNF, kept: &[bool], i: usize) -> bool {
+    let clause = &cnf[i];
+    clause.iter().any(|literal| {
+        cnf.iter()
+            .zip(kept)
+            .filter(|&(other, &kept)| kept && other.iter().any(|l| is_negation(l, literal)))
+            .all(|(other, _)| {
+                // the resolvent is a tautology if some other literal of the
+                // clause clashes with the other clause too
+                clause.iter().any(|mine| {
+                    mine.name != literal.name && other.iter().any(|l| is_negation(l, mine))
+                })
+            })
+    })
+}
+
+fn is_negation(a: &Literal, b: &Literal) -> bool {
+    a.name == b.name && a.negated != b.negated
+}
+
+/// Bounded variable elimination. Variables are tried in order of first
+/// occurrence, and a variable is eliminated by replacing every clause that
+/// contains it with the non-tautological resolvents on it, as long as that
+/// adds at most `growth_limit` clauses. The result is satisfiable exactly
+/// when `cnf` is, and the returned `VarMap` holds the eliminated variables
+/// in the order they were removed.
+pub fn eliminate_vars(cnf: CNF, growth_limit: usize) -> (CNF, VarMap) {
+    let mut occurrences = Occurrences::from_cnf(&cnf);
+    let candidates: Vec<String> = occurrences.names().map(str::to_string).collect();
+    // removed clauses are left as `None` so indices stay valid
+    let mut clauses: Vec<Option<Clause>> = cnf.into_iter().map(Some).collect();
+    let mut eliminated = VarMap::new();
+
+    for name in &candidates {
+        if eliminate(&mut clauses, &mut occurrences, name, Some(growth_limit)) {
+            eliminated.intern(name);
+        }
+    }
+    (clauses.into_iter().flatten().collect(), eliminated)
+}
+
+/// Existentially quantifies away every variable not in `keep`, by
+/// eliminating them one after another with resolution however many
+/// clauses that takes. The result only mentions kept variables, and its
+/// models are exactly the restrictions of the models of `cnf` to them, as
+/// needed for projected model counting. It is satisfiable exactly when
+/// `cnf` is.
+///
+/// The formula can grow exponentially in the number of eliminated
+/// variables, subsumed clauses are dropped at the end.
+pub fn project(cnf: CNF, keep: &[String]) -> CNF {
+    let mut occurrences = Occurrences::from_cnf(&cnf);
+    let forgotten: Vec<String> = occurrences
+        .names()
+        .filter(|name| !keep.iter().any(|kept| kept == name))
+        .map(str::to_string)
+        .collect();
+    let mut clauses: Vec<Option<Clause>> = cnf.into_iter().map(Some).collect();
+    for name in &forgotten {
+        eliminate(&mut clauses, &mut occurrences, name, None);
+    }
+    subsume(clauses.into_iter().flatten().collect())
+}
+
+// replaces the clauses containing `name` by their resolvents on it, unless
+// that adds more than `growth_limit` clauses. returns whether it did
+fn eliminate(
+    clauses: &mut Vec<Option<Clause>>,
+    occurrences: &mut Occurrences,
+    name: &str,
+    growth_limit: Option<usize>,
+) -> bool {
+    let positive = occurrences.of(name, false).to_vec();
+    let negative = occurrences.of(name, true).to_vec();
+    if positive.is_empty() && negative.is_empty() {
+        return false;
+    }
+
+    let clause = |i: usize| clauses[i].as_ref().expect("listed clauses are kept");
+    let resolvents: Vec<Clause> = positive
+        .iter()
+        .flat_map(|&p| {
+            negative
+                .iter()
+                .filter_map(move |&n| resolve(clause(p), clause(n), name))
+        })
+        .collect();
+    if let Some(limit) = growth_limit
+        && resolvents.len() > positive.len() + negative.len() + limit
+    {
+        return false;
+    }
+
+    for i in positive.into_iter().chain(negative) {
+        let clause = clauses[i].take().expect("listed clauses are kept");
+        occurrences.remove(i, &clause);
+    }
+    for resolvent in resolvents {
+        occurrences.add(clauses.len(), &resolvent);
+        clauses.push(Some(resolvent));
+    }
+    true
+}
+
+/// Failed literal probing: every variable is set both ways in turn, and a
+/// value under which unit propagation runs into a false clause is a failed
+/// literal, so the variable must take the other value. Forced values are
+/// propagated and probing starts over until no variable fails, checking
+/// variables in order of first occurrence. `None` means both values of
+/// some variable fail, so `cnf` is unsatisfiable.
+///
+/// The result has a unit clause for every value forced by probing or by
+/// the unit clauses of `cnf`, in order of first occurrence, followed by
+/// what is left of the other clauses once those values are applied. Its
+/// models are those of `cnf`, where variables it no longer mentions, which
+/// only occurred in clauses the forced values satisfy, take either value.
+pub fn probe(cnf: CNF) -> Option<CNF> {
+    let names: Vec<String> = VarMap::from_cnf(&cnf).names().map(String::from).collect();
+    let mut assignment = Assignment::new();
+    let mut cnf = unit_propagate(cnf, &mut assignment)?;
+    'probing: loop {
+        for name in VarMap::from_cnf(&cnf).names() {
+            for value in [true, false] {
+                let probe = Assignment::from([(name.to_string(), value)]);
+                if propagate_only(&cnf, &probe).is_none() {
+                    let forced = Assignment::from([(name.to_string(), !value)]);
+                    assignment.extend(forced.clone());
+                    cnf = unit_propagate(simplify(cnf, &forced)?, &mut assignment)?;
+                    continue 'probing;
+                }
+            }
+        }
+        break;
+    }
+
+    let units = names.into_iter().filter_map(|name| {
+        let value = *assignment.get(&name)?;
+        Some(vec![Literal {
+            name,
+            negated: !value,
+        }])
+    });
+    Some(units.chain(cnf).collect())
+}
+
+/// The resolvent of `c1` and `c2` on `pivot`: every literal of both but
+/// those of the pivot, each once. `None` unless `pivot` occurs in one
+/// clause and its negation in the other, and `None` as well when the
+/// resolvent is a tautology, which any assignment satisfies and so is
+/// never worth keeping.
+pub fn resolve(c1: &Clause, c2: &Clause, pivot: &str) -> Option<Clause> {
+    let signs = |clause: &Clause| {
+        let mut signs = clause.iter().filter(|literal| literal.name == pivot);
+        let first = signs.next()?.negated;
+        // a clause with the pivot both ways is a tautology itself
+        signs
+            .all(|literal| literal.negated == first)
+            .then_some(first)
+    };
+    if signs(c1)? == signs(c2)? {
+        return None;
+    }
+
+    let resolvent: Clause = c1
+        .iter()
+        .chain(c2)
+        .filter(|literal| literal.name != pivot)
+        .cloned()
+        .collect();
+    let resolvent = dedup_literals(resolvent);
+    (!is_tautology(&resolvent)).then_some(resolvent)
+}
+
+/// The literals that must all have the same value, found as the strongly
+/// connected components of the implication graph of the binary clauses,
+/// where `{a,b}` gives the edges `-a -> b` and `-b -> a`. So `{-a,b},{-b,a}`
+/// makes `a` and `b` equivalent, and `{a,b},{-a,-b}` makes `a` and `-b`
+/// equivalent. Longer clauses are not used.
+///
+/// Every class has a mirror image of negated literals, only the one whose
+/// smallest literal is positive is returned. Classes are sorted, single
+/// literals are left out, and a class with both signs of a variable means
+/// `cnf` is unsatisfiable.
+pub fn literal_equivalences(cnf: &CNF) -> Vec<Vec<Literal>> {
+    let vars = VarMap::from_cnf(cnf);
+    let mut edges = vec![Vec::new(); vars.len() * 2];
+    let lit = |literal: &Literal| {
+        let var = vars.id(&literal.name).expect("interned from cnf");
+        Lit::new(var, literal.negated).index()
+    };
+    for clause in cnf.iter().filter(|clause| clause.len() == 2) {
+        let (a, b) = (lit(&clause[0]), lit(&clause[1]));
+        // the negation of a literal is its neighbour
+        edges[a ^ 1].push(b);
+        edges[b ^ 1].push(a);
+    }
+
+    let component = components(&edges);
+    let mut classes = vec![Vec::new(); edges.len()];
+    for (node, &class) in component.iter().enumerate() {
+        classes[class].push(Literal {
+            name: vars.name(node as u32 / 2).expect("interned").to_string(),
+            negated: node % 2 == 1,
+        });
+    }
+    for class in &mut classes {
+        class.sort();
+    }
+
+    classes.retain(|class| class.len() > 1 && !class[0].negated);
+    classes.sort();
+    classes
+}
+
+// the strongly connected component of every node of the graph given by
+// its `edges`, numbered so that no edge leads to a lower number
+pub(crate) fn components(edges: &[Vec<usize>]) -> Vec<usize> {
+    let mut reverse = vec![Vec::new(); edges.len()];
+    for (from, targets) in edges.iter().enumerate() {
+        for &to in targets {
+            reverse[to].push(from);
+        }
+    }
+
+    // Kosaraju: order by finishing time, then collect components on the
+    // reversed graph in reverse of that order
+    let mut finished = Vec::with_capacity(edges.len());
+    let mut visited = vec![false; edges.len()];
+    for root in 0..edges.len() {
+        if visited[root] {
+            continue;
+        }
+        visited[root] = true;
+        let mut stack = vec![(root, 0)];
+        while let Some((node, next)) = stack.pop() {
+            match edges[node].get(next) {
+                Some(&child) => {
+                    stack.push((node, next + 1));
+                    if !visited[child] {
+                        visited[child] = true;
+                        stack.push((child, 0));
+                    }
+                }
+                None => finished.push(node),
+            }
+        }
+    }
+
+    let mut component = vec![usize::MAX; edges.len()];
+    let mut count = 0;
+    for &root in finished.iter().rev() {
+        if component[root] != usize::MAX {
+            continue;
+        }
+        component[root] = count;
+        let mut stack = vec![root];
+        while let Some(node) = stack.pop() {
+            for &parent in &reverse[node] {
+                if component[parent] == usize::MAX {
+                    component[parent] = count;
+                    stack.push(parent);
+                }
+            }
+        }
+        count += 1;
+    }
+    component
+}
+
+// keeps the first occurrence of every literal, which leaves the clause's
+// meaning unchanged
+pub(crate) fn dedup_literals(clause: Clause) -> Clause {
+    let mut unique = Clause::with_capacity(clause.len());
+    for literal in clause {
+        if !unique.contains(&literal) {
+            unique.push(literal);
+        }
+    }
+    unique
+}
+
+pub(crate) fn is_tautology(clause: &Clause) -> bool {
+    clause
+        .iter()
+        .any(|literal| clause.iter().any(|other| is_negation(other, literal)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, parse2, solve, verify};
+
+    #[test]
+    fn test_remove_tautologies() {
+        assert_eq!(remove_tautologies(parse2("{a,-a,b},{c}")), parse2("{c}"));
+        assert_eq!(
+            remove_tautologies(parse2("{a,b},{-b,c,b},{a,-c}")),
+            parse2("{a,b},{a,-c}")
+        );
+        assert_eq!(remove_tautologies(parse2("{a,a}")), parse2("{a,a}"));
+    }
+
+    #[test]
+    fn test_subsume() {
+        assert_eq!(subsume(parse2("{a},{a,b},{a,b,c}")), parse2("{a}"));
+        assert_eq!(
+            subsume(parse2("{a,b,c},{-a,b},{b,-a,d},{c,a}")),
+            parse2("{-a,b},{c,a}")
+        );
+        assert_eq!(subsume(parse2("{a,b},{b,a}")), parse2("{a,b}"));
+        assert_eq!(subsume(vec![vec![], parse2("{a}").remove(0)]), vec![vec![]]);
+    }
+
+    #[test]
+    fn test_eliminate_blocked() {
+        // {a,b} is blocked on a, its only resolvent on a is {b,-b}. Then a
+        // is pure in {-a,-b}, which is removed as well.
+        let cnf = parse2("{a,b},{-a,-b},{b,c},{-b,c},{-c,b},{-c,-b}");
+        let reduced = eliminate_blocked(cnf.clone());
+        assert_eq!(reduced, parse2("{b,c},{-b,c},{-c,b},{-c,-b}"));
+        assert_eq!(solve(cnf), SatResult::Unsat);
+        assert_eq!(solve(reduced), SatResult::Unsat);
+
+        // every clause of a chain of implications is blocked
+        let cnf = parse2("{-a,b},{-b,c},{a}");
+        assert!(matches!(solve(cnf.clone()), SatResult::Sat(_)));
+        assert!(matches!(solve(eliminate_blocked(cnf)), SatResult::Sat(_)));
+        assert_eq!(eliminate_blocked(parse2("{a},{-a}")), parse2("{a},{-a}"));
+    }
+
+    #[test]
+    fn test_eliminate_vars() {
+        // resolving b away leaves {a,c}, one clause instead of two
+        let (cnf, eliminated) = eliminate_vars(parse2("{a,b},{-b,c}"), 0);
+        assert!(eliminated.id("b").is_some());
+        assert!(cnf.iter().flatten().all(|literal| literal.name != "b"));
+
+        // eliminating a pure variable drops its clauses
+        let (cnf, _) = eliminate_vars(parse2("{a,b},{a,-c}"), 0);
+        assert!(cnf.is_empty());
+
+        // x has 3 x 2 non-tautological resolvents, one more clause than
+        // before, so only a limit of 1 eliminates it
+        let cnf = parse2("{x,a},{x,b},{x,c},{-x,d},{-x,e}");
+        let (_, eliminated) = eliminate_vars(cnf.clone(), 0);
+        assert!(eliminated.id("x").is_none());
+        let (_, eliminated) = eliminate_vars(cnf, 1);
+        assert_eq!(eliminated.id("x"), Some(0));
+    }
+
+    #[test]
+    fn test_eliminate_vars_keeps_satisfiability() {
+        let (cnf, _) = eliminate_vars(parse2("{a},{-a,b},{-b}"), 0);
+        assert_eq!(solve(cnf), SatResult::Unsat);
+
+        for seed in 0..50 {
+            let cnf = crate::random_3sat(10, 43, seed);
+            let (reduced, _) = eliminate_vars(cnf.clone(), 2);
+            assert_eq!(
+                matches!(solve(cnf), SatResult::Sat(_)),
+                matches!(solve(reduced), SatResult::Sat(_)),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_preprocess() {
+        // a and b are fixed, the tautology goes, {c,d,e} is subsumed and
+        // {a,b,-d} satisfied. the 4 models of the input are c xor d times
+        // both values of e, which the result no longer mentions
+        let cnf = parse2("{a},{-a,b},{b,c,-c},{c,d},{-c,-d},{c,d,e},{a,b,-d}");
+        let reduced = preprocess(cnf.clone());
+        assert_eq!(reduced, parse2("{c,d},{-c,-d}"));
+        assert_eq!(crate::model_count(cnf), 4);
+        assert_eq!(crate::model_count(reduced) << 1, 4);
+
+        // x is pure, and setting it satisfies everything
+        assert!(preprocess(parse2("{x,y},{x,-y},{x,x}")).is_empty());
+        assert_eq!(preprocess(parse2("{a},{-a,b},{-b}")), vec![vec![]]);
+        assert_eq!(preprocess(vec![vec![]]), vec![vec![]]);
+
+        for seed in 0..50 {
+            let cnf = crate::random_3sat(10, 43, seed);
+            assert_eq!(
+                matches!(solve(cnf.clone()), SatResult::Sat(_)),
+                matches!(solve(preprocess(cnf)), SatResult::Sat(_)),
+                "seed {seed}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_preprocess_with_custom_pass() {
+        // forgets clauses longer than its bound
+        struct DropLong(usize);
+        impl PreprocessPass for DropLong {
+            fn run(&mut self, mut cnf: CNF) -> Option<CNF> {
+                cnf.retain(|clause| clause.len() <= self.0);
+                Some(cnf)
+            }
+        }
+
+        // {-b,c,d} is only short enough once b is propagated
+        let cnf = parse2("{a,b,c},{a},{-a,b},{-b,c,d},{c,-d}");
+        let passes: Vec<Box<dyn PreprocessPass>> =
+            vec![Box::new(UnitPropagate), Box::new(DropLong(2))];
+        assert_eq!(preprocess_with(cnf.clone(), passes), parse2("{c,d},{c,-d}"));
+        let passes: Vec<Box<dyn PreprocessPass>> =
+            vec![Box::new(DropLong(2)), Box::new(UnitPropagate)];
+        assert_eq!(preprocess_with(cnf.clone(), passes), parse2("{c,-d}"));
+
+        struct Refute;
+        impl PreprocessPass for Refute {
+            fn run(&mut self, _: CNF) -> Option<CNF> {
+                None
+            }
+        }
+        assert_eq!(preprocess_with(cnf, vec![Box::new(Refute)]), vec![vec![]]);
+    }
+
+    #[test]
+    fn test_project() {
+        let keep = ["a".to_string()];
+        assert_eq!(project(parse2("{a,b},{a,-b}"), &keep), parse2("{a}"));
+        assert_eq!(project(parse2("{a,b},{-b}"), &keep), parse2("{a}"));
+        assert!(project(parse2("{b,c},{-b}"), &keep).is_empty());
+        assert_eq!(project(parse2("{b},{-b}"), &keep), vec![vec![]]);
+
+        // the models of the projection are those of the original cut down
+        // to the kept variables
+        let keep: Vec<String> = ["1", "2", "3"].map(str::to_string).to_vec();
+        for seed in 0..20 {
+            let cnf = crate::random_3sat(6, 20, seed);
+            let projected = project(cnf.clone(), &keep);
+            assert!(
+                projected
+                    .iter()
+                    .flatten()
+                    .all(|lit| keep.contains(&lit.name))
+            );
+
+            for model in crate::all_models(cnf.clone()) {
+                assert!(verify(&projected, &model), "seed {seed}");
+            }
+            for model in crate::all_models(projected) {
+                let mut extended = cnf.clone();
+                for name in &keep {
+                    let value = model.get(name).copied().unwrap_or(false);
+                    extended.push(vec![Literal {
+                        name: name.clone(),
+                        negated: !value,
+                    }]);
+                }
+                assert!(matches!(solve(extended), SatResult::Sat(_)), "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_canonicalize() {
+        assert_eq!(canonicalize(parse2("{b,a}")), canonicalize(parse2("{a,b}")));
+        assert_eq!(
+            canonicalize(parse2("{c,-a},{b,a,-b},{-a,c}")),
+            parse2("{a,b,-b},{-a,c}")
+        );
+        assert_eq!(
+            Canonical::new(parse2("{b,a},{c}")),
+            Canonical::from(parse2("{c},{a,b}"))
+        );
+        assert_ne!(
+            Canonical::new(parse2("{a}")),
+            Canonical::new(parse2("{-a}"))
+        );
+        assert_eq!(Canonical::new(parse2("{a,b}")).into_cnf(), parse2("{a,b}"));
+    }
+
+    #[test]
+    fn test_literal_equivalences() {
+        let lit = |name: &str| Literal {
+            name: name.trim_start_matches('-').to_string(),
+            negated: name.starts_with('-'),
+        };
+        let class = |names: &[&str]| names.iter().map(|name| lit(name)).collect::<Vec<_>>();
+
+        assert_eq!(
+            literal_equivalences(&parse2("{-a,b},{-b,a}")),
+            [class(&["a", "b"])]
+        );
+        // a -> -b -> c -> a, the longer clause adds nothing
+        assert_eq!(
+            literal_equivalences(&parse2("{-a,-b},{b,c},{-c,a},{a,b,d},{d,e}")),
+            [class(&["a", "-b", "c"])]
+        );
+        assert_eq!(
+            literal_equivalences(&parse2("{-a,b},{c,d}")),
+            Vec::<Vec<Literal>>::new()
+        );
+        // a <-> -a
+        assert_eq!(
+            literal_equivalences(&parse2("{a,a2},{-a2,-a},{-a,a2},{-a2,a}"))[0][..2],
+            [lit("a"), lit("-a")]
+        );
+    }
+
+    #[test]
+    fn test_probe() {
+        // setting `a` true forces both `c` and `-c`, so `a` is a failed
+        // literal and `-a` is forced, which leaves `{b},{-b}`
+        let cnf = parse2("{a,b},{a,-b},{-a,c},{-a,-c}");
+        assert_eq!(
+            crate::implied_by(&cnf, &[Literal::from(("a", false))]),
+            None
+        );
+        assert_eq!(
+            probe(parse2("{-a},{-a,c},{-a,-c},{d,e}")),
+            Some(parse2("{-a},{d,e}"))
+        );
+        assert_eq!(probe(cnf), None);
+
+        // `-a` fails, propagation then forces `c`, after which `-d` fails
+        assert_eq!(
+            probe(parse2("{a,b},{a,-b},{-a,c},{d,e,-c},{d,-e}")),
+            Some(parse2("{a},{c},{d}"))
+        );
+        let cnf = parse2("{a,b},{c,d}");
+        assert_eq!(probe(cnf.clone()), Some(cnf));
+
+        for seed in 0..30 {
+            let cnf = crate::random_3sat(10, 45, seed);
+            let sat = matches!(solve(cnf.clone()), SatResult::Sat(_));
+            match probe(cnf.clone()) {
+                Some(probed) => {
+                    assert!(sat || solve(probed.clone()) == SatResult::Unsat);
+                    // the variables left out can take either value
+                    let dropped = VarMap::from_cnf(&cnf).len() - VarMap::from_cnf(&probed).len();
+                    let count = crate::model_count(probed) << dropped;
+                    assert_eq!(count, crate::model_count(cnf), "seed {seed}");
+                }
+                None => assert!(!sat, "seed {seed}"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_resolve() {
+        let clause = |text: &str| parse2(text).remove(0);
+        assert_eq!(
+            resolve(&clause("{a,b}"), &clause("{-a,c}"), "a"),
+            Some(clause("{b,c}"))
+        );
+        assert_eq!(
+            resolve(&clause("{-a,c,b}"), &clause("{a,b}"), "a"),
+            Some(clause("{c,b}"))
+        );
+        assert_eq!(resolve(&clause("{a}"), &clause("{-a}"), "a"), Some(vec![]));
+        assert_eq!(resolve(&clause("{a,b}"), &clause("{a,c}"), "a"), None);
+        assert_eq!(resolve(&clause("{a,b}"), &clause("{-a,c}"), "b"), None);
+        assert_eq!(resolve(&clause("{a,b}"), &clause("{-a,-b}"), "a"), None);
+    }
+
+    #[test]
+    fn test_dedup_literals() {
+        let clause = dedup_literals(parse2("{a,-b,a,b,-b}").remove(0));
+        assert_eq!(clause, parse2("{a,-b,b}").remove(0));
+    }
+}
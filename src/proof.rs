@@ -0,0 +1,183 @@
+use crate::dimacs::write_clause;
+use crate::dpll::implied_by;
+use crate::parse::dimacs_literal;
+use crate::{CNF, Clause, Literal, VarMap};
+use alloc::{string::String, vec::Vec};
+
+/// One step of a clausal proof of unsatisfiability.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ProofStep {
+    /// A clause that follows from the formula and the clauses added before
+    /// it. A proof ends by adding the empty clause.
+    Add(Clause),
+    /// A clause that is no longer used.
+    Delete(Clause),
+}
+
+/// Writes `steps` in the textual DRAT format read by checkers such as
+/// drat-trim, numbering variables like `to_dimacs` does with the same
+/// `vars`. Panics if a variable of a step is missing from `vars`.
+pub fn proof_to_drat(steps: &[ProofStep], vars: &VarMap) -> String {
+    let mut out = String::new();
+    for step in steps {
+        match step {
+            ProofStep::Add(clause) => write_clause(&mut out, clause, vars),
+            ProofStep::Delete(clause) => {
+                out.push_str("d ");
+                write_clause(&mut out, clause, vars);
+            }
+        }
+    }
+    out
+}
+
+/// Checks `proof`, in the textual DRAT format `proof_to_drat` writes, as a
+/// refutation of `cnf`. Numbers name the variables of
+/// `VarMap::from_cnf(cnf)`, larger ones are new variables, as an added
+/// clause may introduce them.
+///
+/// Every added clause must follow from the clauses before it by reverse
+/// unit propagation, or be a resolution asymmetric tautology on its first
+/// literal. A deletion drops one copy of its clause. The proof checks once
+/// it adds the empty clause; a malformed proof or one that stops short of
+/// the empty clause does not.
+pub fn check_drat(cnf: &CNF, proof: &str) -> bool {
+    let vars = VarMap::from_cnf(cnf);
+    let mut clauses: CNF = cnf.iter().cloned().map(sorted).collect();
+    let mut clause = Clause::new();
+    let mut delete = false;
+    for (index, line) in proof.lines().enumerate() {
+        if line.trim_start().starts_with('c') {
+            continue;
+        }
+        for token in line.split_whitespace() {
+            if token == "d" && clause.is_empty() && !delete {
+                delete = true;
+                continue;
+            }
+            let Ok(literal) = dimacs_literal(token, index + 1, None) else {
+                return false;
+            };
+            let Some(mut literal) = literal else {
+                let step = core::mem::take(&mut clause);
+                if core::mem::take(&mut delete) {
+                    let step = sorted(step);
+                    if let Some(i) = clauses.iter().position(|clause| *clause == step) {
+                        clauses.swap_remove(i);
+                    }
+                    continue;
+                }
+                if !redundant(&clauses, &step) {
+                    return false;
+                }
+                if step.is_empty() {
+                    return true;
+                }
+                clauses.push(sorted(step));
+                continue;
+            };
+            let Some(id) = literal
+                .name
+                .parse::<u32>()
+                .ok()
+                .and_then(|id| id.checked_sub(1))
+            else {
+                return false;
+            };
+            if let Some(name) = vars.name(id) {
+                literal.name = name.into();
+            }
+            clause.push(literal);
+        }
+    }
+    false
+}
+
+fn sorted(mut clause: Clause) -> Clause {
+    clause.sort_unstable();
+    clause.dedup();
+    clause
+}
+
+// whether adding `clause` to `clauses` keeps them satisfiable, as RUP or
+// as RAT on its first literal
+fn redundant(clauses: &CNF, clause: &Clause) -> bool {
+    // there is a conflict once every literal of the clause is false
+    let rup = |clause: &[Literal]| {
+        let negation: Vec<Literal> = clause.iter().map(negate).collect();
+        implied_by(clauses, &negation).is_none()
+    };
+    if rup(clause) {
+        return true;
+    }
+    let Some(pivot) = clause.first() else {
+        return false;
+    };
+    let complement = negate(pivot);
+    clauses
+        .iter()
+        .filter(|other| other.contains(&complement))
+        .all(|other| {
+            let resolvent: Clause = clause
+                .iter()
+                .chain(other.iter().filter(|&literal| *literal != complement))
+                .cloned()
+                .collect();
+            rup(&resolvent)
+        })
+}
+
+fn negate(literal: &Literal) -> Literal {
+    Literal {
+        name: literal.name.clone(),
+        negated: !literal.negated,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse2, solve_with_proof};
+
+    #[test]
+    fn test_proof_to_drat() {
+        let vars = VarMap::from_cnf(&parse2("{a,b}"));
+        let steps = [
+            ProofStep::Add(parse2("{-a,b}").remove(0)),
+            ProofStep::Delete(parse2("{a,b}").remove(0)),
+            ProofStep::Add(Clause::new()),
+        ];
+        assert_eq!(proof_to_drat(&steps, &vars), "-1 2 0\nd 1 2 0\n0\n");
+    }
+
+    #[test]
+    fn test_check_drat() {
+        let cnf = parse2("{a},{-a}");
+        assert!(check_drat(&cnf, "0\n"));
+        assert!(check_drat(&cnf, "c the empty clause\n1 0\n0\n"));
+        // deleting `{a}` first leaves nothing to refute
+        assert!(!check_drat(&cnf, "d 1 0\n0\n"));
+        assert!(!check_drat(&cnf, "1 0\n"));
+        assert!(!check_drat(&cnf, "1 x 0\n0\n"));
+        assert!(!check_drat(&cnf, "5000000000 0\n0\n"));
+
+        // `-a` is neither RUP nor RAT
+        assert!(!check_drat(&parse2("{a,b}"), "-1 0\n0\n"));
+        // a new variable is RAT on itself
+        let cnf = parse2("{a,b},{-a,b},{a,-b},{-a,-b}");
+        assert!(check_drat(&cnf, "3 0\n2 0\n0\n"));
+        assert!(!check_drat(&cnf, "d 1 2 0\n d -1 2 0\n0\n"));
+    }
+
+    #[test]
+    fn test_check_drat_of_solver_proofs() {
+        for seed in 0..20 {
+            let cnf = crate::random_3sat(10, 60, seed);
+            if let Err(steps) = solve_with_proof(cnf.clone()) {
+                let drat = proof_to_drat(&steps, &VarMap::from_cnf(&cnf));
+                assert!(check_drat(&cnf, &drat), "seed {seed}");
+            }
+        }
+    }
+}
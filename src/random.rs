@@ -0,0 +1,81 @@
+use crate::{CNF, Literal};
+use alloc::{string::ToString, vec::Vec};
+
+/// A random formula of `num_clauses` clauses over the variables `1` to
+/// `num_vars`, each with three distinct variables negated at random. The
+/// same seed always gives the same formula. Near 4.26 clauses per variable
+/// about half of these formulas are satisfiable, which makes them hardest.
+///
+/// Panics if there are fewer than three variables.
+pub fn random_3sat(num_vars: usize, num_clauses: usize, seed: u64) -> CNF {
+    assert!(num_vars >= 3, "3-SAT clauses need three variables");
+    let mut rng = XorShift::new(seed);
+
+    (0..num_clauses)
+        .map(|_| {
+            let mut vars: Vec<usize> = Vec::with_capacity(3);
+            while vars.len() < 3 {
+                let var = rng.below(num_vars) + 1;
+                if !vars.contains(&var) {
+                    vars.push(var);
+                }
+            }
+            vars.into_iter()
+                .map(|var| Literal {
+                    name: var.to_string(),
+                    negated: rng.below(2) == 0,
+                })
+                .collect()
+        })
+        .collect()
+}
+
+// xorshift64, good enough for generating test instances
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct XorShift(pub(crate) u64);
+
+impl XorShift {
+    pub(crate) fn new(seed: u64) -> Self {
+        // an all zero state would stay zero forever
+        XorShift(if seed == 0 {
+            0x9e37_79b9_7f4a_7c15
+        } else {
+            seed
+        })
+    }
+
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    // a number in 0..n
+    pub(crate) fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_random_3sat() {
+        let cnf = random_3sat(20, 85, 7);
+        assert_eq!(cnf.len(), 85);
+        for clause in &cnf {
+            assert_eq!(clause.len(), 3);
+            for literal in clause {
+                let var: usize = literal.name.parse().unwrap();
+                assert!((1..=20).contains(&var));
+                assert_eq!(clause.iter().filter(|l| l.name == literal.name).count(), 1);
+            }
+        }
+
+        assert_eq!(random_3sat(20, 85, 7), cnf);
+        assert_ne!(random_3sat(20, 85, 8), cnf);
+        assert_eq!(random_3sat(3, 2, 0).len(), 2);
+    }
+}
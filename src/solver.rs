@@ -0,0 +1,3768 @@
+use crate::config::{ClauseDeletion, Heuristic, Limit, RestartPolicy, SolverConfig, SolverLimits};
+use crate::horn::{forward_chain, is_horn};
+use crate::observer::SolverObserver;
+use crate::parse::{ClauseMeta, ParseError, dimacs_literal};
+use crate::preprocess::{is_tautology, remove_tautologies, subsume};
+use crate::proof::ProofStep;
+use crate::random::XorShift;
+#[cfg(feature = "serde")]
+use crate::state::SolverError;
+use crate::stats::Stats;
+use crate::varmap::{Lit, VarMap};
+use crate::xor::{Deduction, XorClause, XorSystem};
+use crate::{Assignment, CNF, Clause, Literal, SatResult, format_cnf};
+use alloc::{
+    collections::VecDeque,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::cmp::Reverse;
+#[cfg(not(feature = "no_std"))]
+use std::time::{Duration, Instant};
+
+// reading the clock on every conflict would dominate short propagations
+#[cfg(not(feature = "no_std"))]
+const CLOCK_INTERVAL: u64 = 256;
+
+// the activity increment grows by this factor per conflict, which decays
+// all earlier bumps relative to new ones
+const ACTIVITY_GROWTH: f64 = 1.0 / 0.95;
+
+// conflicts before the first restart under `RestartPolicy::Geometric`
+const GEOMETRIC_FIRST_RESTART: f64 = 100.0;
+
+// how much longer than average the trail has to be to block a restart,
+// glucose's `R`
+const BLOCKING_FACTOR: f64 = 1.4;
+
+// bounds on a round of `SolverConfig::inprocessing`: subsumption checks,
+// variables probed, and occurrences of a variable that is eliminated
+const SUBSUMPTION_CHECKS: usize = 100_000;
+const PROBED_VARS: usize = 64;
+const ELIMINATION_OCCURRENCES: usize = 16;
+
+// the search core works on interned variables instead of names and clauses
+// are never copied or rebuilt. assignments are recorded on a trail so
+// backtracking only has to pop the variables assigned since the decision
+// being undone. every conflict is analysed into a learned clause that is
+// added to the clause database, and the search jumps back to the level
+// where that clause becomes unit instead of flipping the last decision.
+
+// outcome of a search that may be cut short
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Status {
+    Sat,
+    Unsat,
+    Unknown,
+}
+
+// what is needed to report an unsatisfiable subset of the input
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Core {
+    // the clauses as loaded, since watching reorders their literals
+    clauses: Vec<Vec<Lit>>,
+    // clauses the final conflict was derived from
+    marked: Vec<bool>,
+    // for each learned clause, the clauses it was resolved from
+    antecedents: Vec<Vec<usize>>,
+}
+
+/// Why a variable has its current value, see `Solver::explain` and
+/// `dpll_trail`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reason {
+    /// Chosen by branching, or forced by an assumption.
+    Decision,
+    /// Forced by the clause with this index. For `Solver::explain` that
+    /// counts the clauses of the formula that were kept (tautologies are
+    /// dropped), then added and learned clauses in the order they came.
+    Propagated(usize),
+    /// Set because the variable occurs with one sign only in the clauses
+    /// not yet satisfied. Only the classic DPLL does this, so only
+    /// `dpll_trail` reports it.
+    PureLiteral,
+}
+
+/// A clause database that can be solved repeatedly, adding clauses and
+/// trying different assumptions in between. What is learned in one solve
+/// is kept for the next.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Solver {
+    config: SolverConfig,
+    vars: VarMap,
+    // the input clauses followed by the learned ones. a forgotten learned
+    // clause is left empty so the indices of later ones stay valid.
+    clauses: Vec<Vec<Lit>>,
+    // the LBD of each learned clause still kept, `None` for the others
+    lbds: Vec<Option<u32>>,
+    // clauses watching each literal, indexed by `Lit::index`. a clause
+    // watches its first two literals, and while neither of them is false it
+    // can be neither unit nor false, so only clauses watching a literal
+    // that just became false need to be looked at.
+    watches: Vec<Vec<usize>>,
+    // binary clauses are not watched but listed for both their literals,
+    // with the other literal and the clause, so a literal becoming false
+    // forces the others without looking at the clauses
+    binaries: Vec<Vec<(Lit, usize)>>,
+    xors: Option<XorSystem>,
+    values: Vec<Option<bool>>,
+    // clause that forced each variable, `None` for decisions
+    reasons: Vec<Option<usize>>,
+    // number of decisions in effect when each variable was assigned
+    levels: Vec<usize>,
+    trail: Vec<u32>,
+    // trail length before each decision still in effect
+    decisions: Vec<usize>,
+    // literals decided first, one per level, for the current solve
+    assumptions: Vec<Lit>,
+    // assumptions that together made the last solve fail
+    failed: Vec<Lit>,
+    // trail entries before this index have been propagated
+    queue_head: usize,
+    // a clause found false while loading, an empty clause or a unit
+    // contradicting an earlier one
+    conflict: Option<usize>,
+    core: Option<Core>,
+    // learned clauses in the order they were derived, when a proof is
+    // requested
+    proof: Option<Vec<ProofStep>>,
+    // scratch space for conflict analysis, all false between conflicts
+    seen: Vec<bool>,
+    stats: Stats,
+    conflicts_since_restart: u64,
+    // trail lengths at the most recent conflicts and their sum, for
+    // `SolverConfig::restart_blocking`
+    trail_sizes: VecDeque<usize>,
+    trail_sum: usize,
+    // limits are not saved, they belong to the solve they were set for
+    #[cfg(not(feature = "no_std"))]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    deadline: Option<Instant>,
+    // `stats.conflicts` at which to give up
+    #[cfg_attr(feature = "serde", serde(skip))]
+    conflict_limit: Option<u64>,
+    // `stats.propagations` at which to give up
+    #[cfg_attr(feature = "serde", serde(skip))]
+    propagation_limit: Option<u64>,
+    // the limit that ended the last solve early
+    #[cfg_attr(feature = "serde", serde(skip))]
+    stopped: Option<Limit>,
+    activity: Vec<f64>,
+    activity_inc: f64,
+    // `config.branch_priority` by variable id
+    priority: Vec<f64>,
+    // the value each variable had when it was last unassigned
+    phases: Vec<bool>,
+    // picks branching values when `config.rand_polarity` is set
+    rng: Option<XorShift>,
+    // variables removed by inprocessing, in the order they were removed,
+    // with the input clauses they occurred in. the clauses give their
+    // values in models, and come back once the variable is needed again.
+    eliminated: Vec<(u32, Vec<Vec<Lit>>)>,
+}
+
+impl Solver {
+    pub fn new(cnf: CNF) -> Self {
+        Self::with_config(cnf, SolverConfig::default())
+    }
+
+    pub(crate) fn with_config(cnf: CNF, config: SolverConfig) -> Self {
+        Self::from_clauses(&cnf, config)
+    }
+
+    // loads the formula without taking it, clauses are only copied when
+    // subsumption needs to rearrange them
+    pub(crate) fn from_clauses(cnf: &[Clause], config: SolverConfig) -> Self {
+        let mut solver = Solver {
+            rng: config.rand_polarity.map(XorShift::new),
+            config,
+            vars: VarMap::new(),
+            clauses: Vec::with_capacity(cnf.len()),
+            lbds: Vec::with_capacity(cnf.len()),
+            watches: Vec::new(),
+            binaries: Vec::new(),
+            xors: None,
+            values: Vec::new(),
+            reasons: Vec::new(),
+            levels: Vec::new(),
+            trail: Vec::new(),
+            decisions: Vec::new(),
+            assumptions: Vec::new(),
+            failed: Vec::new(),
+            queue_head: 0,
+            conflict: None,
+            core: None,
+            proof: None,
+            seen: Vec::new(),
+            stats: Stats::default(),
+            conflicts_since_restart: 0,
+            trail_sizes: VecDeque::new(),
+            trail_sum: 0,
+            #[cfg(not(feature = "no_std"))]
+            deadline: None,
+            conflict_limit: None,
+            propagation_limit: None,
+            stopped: None,
+            activity: Vec::new(),
+            activity_inc: 1.0,
+            priority: Vec::new(),
+            phases: Vec::new(),
+            eliminated: Vec::new(),
+        };
+
+        // variables only in tautologies still get a value in the model
+        for literal in cnf.iter().flatten() {
+            solver.intern(&literal.name);
+        }
+
+        // nothing is assigned yet, so any two literals can be watched
+        if solver.config.subsumption {
+            for clause in &subsume(remove_tautologies(cnf.to_vec())) {
+                let lits = solver.intern_clause(clause);
+                solver.attach(lits, None);
+            }
+        } else {
+            for clause in cnf.iter().filter(|clause| !is_tautology(clause)) {
+                let lits = solver.intern_clause(clause);
+                solver.attach(lits, None);
+            }
+        }
+
+        // empty and unit clauses have nothing to watch
+        for index in 0..solver.clauses.len() {
+            let found_false = match solver.clauses[index][..] {
+                [] => true,
+                [lit] => !solver.enqueue(lit, Some(index)),
+                _ => false,
+            };
+            if found_false && solver.conflict.is_none() {
+                solver.conflict = Some(index);
+            }
+        }
+
+        solver
+    }
+
+    /// Adds a clause that stays part of the formula for every later solve.
+    pub fn add_clause(&mut self, clause: Clause) {
+        self.add_with_lbd(&clause, None);
+    }
+
+    // adds a clause of the formula, or a learned one with an LBD
+    fn add_with_lbd(&mut self, clause: &Clause, lbd: Option<u32>) {
+        self.backjump(0);
+        let lits = self.intern_clause(clause);
+        if lits.iter().any(|lit| self.is_eliminated(lit.var)) {
+            self.restore_eliminated();
+        }
+        self.add_lits(lits, lbd);
+    }
+
+    // adds interned literals at the top level
+    fn add_lits(&mut self, mut lits: Vec<Lit>, lbd: Option<u32>) {
+        // literals already false are never looked at again, so watching one
+        // would miss the clause becoming unit
+        lits.sort_by_key(|&lit| self.lit_value(lit) == Some(false));
+        let found_false = lits
+            .first()
+            .is_none_or(|&lit| self.lit_value(lit) == Some(false));
+        let unit = match lits[..] {
+            [_] => true,
+            [_, second, ..] => self.lit_value(second) == Some(false),
+            [] => false,
+        };
+
+        let index = self.attach(lits, lbd);
+        if found_false {
+            self.conflict.get_or_insert(index);
+        } else if unit {
+            self.enqueue(self.clauses[index][0], Some(index));
+        }
+    }
+
+    /// Adds the clauses of `text` as if they had been learned here, in
+    /// DIMACS without a header, the way other solvers export what they
+    /// learned. They must follow from the formula, since like learned
+    /// clauses they may be forgotten and show up in `learned_clauses`.
+    /// Numbers map to variables like in `solve_under_ints`. The number one
+    /// past the known variables adds a variable named by the number, or,
+    /// if a variable already has that name, by the number followed by as
+    /// many `'` as it takes to be new. After it the next one may follow,
+    /// while numbers further on are rejected with
+    /// `ParseError::VariableOutOfRange`. Tautologies are skipped, though
+    /// their new variables are still added so the numbers stay in step,
+    /// and the number of clauses added is returned. Nothing is added if
+    /// `text` does not parse.
+    pub fn import_dimacs_clauses(&mut self, text: &str) -> Result<usize, ParseError> {
+        // names for the numbers past the known variables, in order
+        let mut fresh: Vec<String> = Vec::new();
+        let mut clauses = CNF::new();
+        let mut clause = Clause::new();
+        for (index, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('c') {
+                continue;
+            }
+            for token in line.split_whitespace() {
+                let Some(mut literal) = dimacs_literal(token, index + 1, None)? else {
+                    clauses.push(core::mem::take(&mut clause));
+                    continue;
+                };
+                let var: usize = literal.name.parse().expect("DIMACS literals are numbered");
+                let known = self.vars.len() + fresh.len();
+                if var > known + 1 {
+                    return Err(ParseError::VariableOutOfRange {
+                        line: index + 1,
+                        var,
+                        declared: known,
+                    });
+                }
+                if let Some(name) = self.vars.name(var as u32 - 1) {
+                    literal.name = name.to_string();
+                } else if let Some(name) = fresh.get(var - self.vars.len() - 1) {
+                    literal.name = name.clone();
+                } else {
+                    // the number may already name another variable, which
+                    // would then take over this one
+                    while self.vars.id(&literal.name).is_some() || fresh.contains(&literal.name) {
+                        literal.name.push('\'');
+                    }
+                    fresh.push(literal.name.clone());
+                }
+                clause.push(literal);
+            }
+        }
+        // tolerate a missing `0` after the last clause
+        if !clause.is_empty() {
+            clauses.push(clause);
+        }
+
+        let mut added = 0;
+        for clause in &clauses {
+            if is_tautology(clause) {
+                for literal in clause {
+                    self.intern(&literal.name);
+                }
+                continue;
+            }
+            // no clause connects more levels than it has literals
+            let lbd = clause.len() as u32;
+            self.add_with_lbd(clause, Some(lbd));
+            added += 1;
+        }
+        Ok(added)
+    }
+
+    /// Adds every clause of `cnf` like `add_clause`. Variables seen before
+    /// keep their ids and new ones are numbered after them, so a problem can
+    /// be built from several fragments, solving in between.
+    pub fn add_cnf(&mut self, cnf: CNF) {
+        for clause in cnf {
+            self.add_clause(clause);
+        }
+    }
+
+    /// Adds `clause` guarded by a new control variable and returns the
+    /// literal that enables it. Solving with the literal among the
+    /// assumptions enforces the clause, solving with its negation or
+    /// without it leaves the clause out. Adding the negation as a unit
+    /// clause removes it for good.
+    ///
+    /// Control variables are named `@a` followed by a number and show up in
+    /// models like any other variable.
+    pub fn add_retractable(&mut self, mut clause: Clause) -> Literal {
+        let name = (self.vars.len()..)
+            .map(|n| format!("@a{n}"))
+            .find(|name| self.vars.id(name).is_none())
+            .expect("some name is unused");
+        clause.push(Literal {
+            name: name.clone(),
+            negated: true,
+        });
+        self.add_clause(clause);
+        Literal {
+            name,
+            negated: false,
+        }
+    }
+
+    /// Solves the formula with `assumptions` forced true, without keeping
+    /// them for later solves. `None` means the formula is unsatisfiable
+    /// under these assumptions.
+    pub fn solve_under(&mut self, assumptions: &[Literal]) -> Option<Assignment> {
+        self.assumptions = assumptions
+            .iter()
+            .map(|literal| Lit::new(self.intern(&literal.name), literal.negated))
+            .collect();
+        let status = self.run();
+        self.assumptions.clear();
+
+        match status {
+            Status::Sat => Some(self.model()),
+            Status::Unsat | Status::Unknown => None,
+        }
+    }
+
+    /// Solves like `solve_under` with assumptions given as DIMACS literals,
+    /// where `k` and `-k` stand for the variable with id `k - 1` in the
+    /// solver's `VarMap`. Variables are numbered in order of first
+    /// occurrence, like `to_dimacs` with `VarMap::from_cnf` does, so for
+    /// a formula read from DIMACS the ids are those of the file when every
+    /// variable occurs in order.
+    ///
+    /// Panics on `0` or on an id past the last variable.
+    pub fn solve_under_ints(&mut self, assumptions: &[i32]) -> Option<Assignment> {
+        let assumptions: Vec<Literal> = assumptions
+            .iter()
+            .map(|&int| {
+                let name = (int.unsigned_abs() as usize)
+                    .checked_sub(1)
+                    .and_then(|var| self.vars.name(var as u32))
+                    .unwrap_or_else(|| panic!("no variable has the DIMACS id {int}"));
+                Literal {
+                    name: name.to_string(),
+                    negated: int < 0,
+                }
+            })
+            .collect();
+        self.solve_under(&assumptions)
+    }
+
+    /// The assumptions of the last `solve_under` that together contradict
+    /// the formula, if it failed. Empty if the formula is unsatisfiable
+    /// under any assumptions.
+    pub fn failed_assumptions(&self) -> Vec<Literal> {
+        self.failed
+            .iter()
+            .map(|&lit| to_literal(&self.vars, lit))
+            .collect()
+    }
+
+    /// The clauses learned from conflicts so far that are still kept, in
+    /// the order they were learned. Clauses forgotten to make room, see
+    /// `ClauseDeletion`, are left out. Each follows from the formula.
+    pub fn learned_clauses(&self) -> Vec<Clause> {
+        self.clauses
+            .iter()
+            .zip(&self.lbds)
+            .filter(|(_, lbd)| lbd.is_some())
+            .map(|(lits, _)| {
+                lits.iter()
+                    .map(|&lit| to_literal(&self.vars, lit))
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Why `name` has the value it has after the last solve, or `None` if
+    /// the variable is unknown or unassigned. Never `Reason::PureLiteral`,
+    /// see `dpll_trail` for that.
+    pub fn explain(&self, name: &str) -> Option<Reason> {
+        let var = self.vars.id(name)? as usize;
+        self.values[var]?;
+        Some(match self.reasons[var] {
+            Some(clause) => Reason::Propagated(clause),
+            None => Reason::Decision,
+        })
+    }
+
+    /// Saves everything the solver knows with bincode, so `load_state` can
+    /// continue from here, possibly on another machine: the clauses
+    /// including learned ones, the trail, activities, saved phases,
+    /// statistics, and XOR constraints, core tracking and proof logging if
+    /// they are in use. Limits on the current solve are not saved.
+    #[cfg(feature = "serde")]
+    pub fn save_state(&self) -> Vec<u8> {
+        crate::state::encode(self)
+    }
+
+    /// Rebuilds a solver from what `save_state` returned. Solving it goes
+    /// on exactly like solving the saved one would have. Input that was not
+    /// written by `save_state` is rejected as far as it can be told apart
+    /// cheaply.
+    #[cfg(feature = "serde")]
+    pub fn load_state(bytes: &[u8]) -> Result<Solver, SolverError> {
+        let solver: Solver = crate::state::decode(bytes)?;
+        if !solver.is_consistent() {
+            return Err(SolverError::Corrupt);
+        }
+        Ok(solver)
+    }
+
+    // whether every index in the solver points at something that exists,
+    // so a loaded state cannot make the search index out of bounds
+    #[cfg(feature = "serde")]
+    fn is_consistent(&self) -> bool {
+        let num_vars = self.vars.len();
+        let num_clauses = self.clauses.len();
+        let known = |lit: &Lit| (lit.var as usize) < num_vars;
+        let names_match = self
+            .vars
+            .names()
+            .enumerate()
+            .all(|(id, name)| self.vars.id(name) == Some(id as u32));
+        let per_var = [self.values.len(), self.reasons.len(), self.levels.len()]
+            .into_iter()
+            .chain([self.seen.len(), self.activity.len()])
+            .chain([self.priority.len(), self.phases.len()])
+            .all(|len| len == num_vars);
+        let core = self.core.as_ref().is_none_or(|core| {
+            core.clauses.len() <= num_clauses
+                && core.marked.len() == core.clauses.len()
+                && core.antecedents.len() == num_clauses - core.clauses.len()
+                && core.clauses.iter().flatten().all(known)
+                && core.antecedents.iter().flatten().all(|&i| i < num_clauses)
+        });
+        let xors = self
+            .xors
+            .as_ref()
+            .is_none_or(|xors| xors.is_consistent(num_vars));
+        names_match
+            && per_var
+            && core
+            && xors
+            && self.watches.len() == 2 * num_vars
+            && self.binaries.len() == 2 * num_vars
+            && self.lbds.len() == num_clauses
+            && self
+                .clauses
+                .iter()
+                .chain(self.eliminated.iter().flat_map(|(_, clauses)| clauses))
+                .flatten()
+                .chain(&self.assumptions)
+                .chain(&self.failed)
+                .all(known)
+            && self.watches.iter().flatten().all(|&i| i < num_clauses)
+            && self
+                .binaries
+                .iter()
+                .flatten()
+                .all(|(other, i)| known(other) && *i < num_clauses)
+            && self
+                .reasons
+                .iter()
+                .flatten()
+                .chain(&self.conflict)
+                .all(|&i| i < num_clauses)
+            && self
+                .trail
+                .iter()
+                .all(|&var| self.values.get(var as usize).is_some_and(Option::is_some))
+            && self.values.iter().flatten().count() == self.trail.len()
+            && self.queue_head <= self.trail.len()
+            && self.decisions.is_sorted()
+            && self
+                .decisions
+                .last()
+                .is_none_or(|&mark| mark <= self.trail.len())
+            && self
+                .eliminated
+                .iter()
+                .all(|&(var, _)| (var as usize) < num_vars)
+    }
+
+    // the id of `name`, making room for it in the per-variable tables when
+    // it is new
+    fn intern(&mut self, name: &str) -> u32 {
+        let var = self.vars.intern(name);
+        if var as usize == self.values.len() {
+            self.values.push(None);
+            self.reasons.push(None);
+            self.levels.push(0);
+            self.seen.push(false);
+            self.activity.push(0.0);
+            let priority = self.config.branch_priority.get(name).copied();
+            self.priority.push(priority.unwrap_or(0.0));
+            let phase = self.polarity();
+            self.phases.push(phase);
+            self.watches.push(Vec::new());
+            self.watches.push(Vec::new());
+            self.binaries.push(Vec::new());
+            self.binaries.push(Vec::new());
+        }
+        var
+    }
+
+    // a repeated literal could end up watched twice
+    fn intern_clause(&mut self, clause: &Clause) -> Vec<Lit> {
+        let mut lits = Vec::with_capacity(clause.len());
+        for literal in clause {
+            let lit = Lit::new(self.intern(&literal.name), literal.negated);
+            if !lits.contains(&lit) {
+                lits.push(lit);
+            }
+        }
+        lits
+    }
+
+    // parity constraints to reason about alongside the clauses
+    pub(crate) fn add_xors(&mut self, xors: &[XorClause]) {
+        self.restore_eliminated();
+        self.xors = Some(XorSystem::new(xors, |name| self.intern(name)));
+    }
+
+    // record what each learned clause was derived from so an
+    // unsatisfiable subset can be reported
+    pub(crate) fn track_core(&mut self) {
+        self.restore_eliminated();
+        self.core = Some(Core {
+            clauses: self.clauses.clone(),
+            marked: vec![false; self.clauses.len()],
+            antecedents: Vec::new(),
+        });
+    }
+
+    // log every learned clause, and the empty clause once the formula is
+    // refuted
+    pub(crate) fn track_proof(&mut self) {
+        self.restore_eliminated();
+        self.proof = Some(Vec::new());
+    }
+
+    // give up once `limit` has passed, checked every few conflicts
+    #[cfg(not(feature = "no_std"))]
+    pub(crate) fn set_time_limit(&mut self, limit: Duration) {
+        self.deadline = Some(Instant::now() + limit);
+    }
+
+    // give up after `max` more conflicts
+    pub(crate) fn set_conflict_limit(&mut self, max: u64) {
+        self.conflict_limit = Some(self.stats.conflicts.saturating_add(max));
+    }
+
+    // give up after `max` more propagations, checked at conflicts
+    pub(crate) fn set_propagation_limit(&mut self, max: u64) {
+        self.propagation_limit = Some(self.stats.propagations.saturating_add(max));
+    }
+
+    pub(crate) fn solve(&mut self) -> SatResult {
+        match self.run() {
+            Status::Sat => SatResult::Sat(self.model()),
+            Status::Unsat => SatResult::Unsat,
+            Status::Unknown => unreachable!("search without limits always finishes"),
+        }
+    }
+
+    pub(crate) fn run(&mut self) -> Status {
+        self.run_observed(&mut ())
+    }
+
+    pub(crate) fn run_observed(&mut self, observer: &mut dyn SolverObserver) -> Status {
+        self.failed.clear();
+        self.backjump(0);
+        if self
+            .assumptions
+            .iter()
+            .any(|lit| self.is_eliminated(lit.var))
+        {
+            self.restore_eliminated();
+        }
+        if let Some(conflict) = self.conflict {
+            return self.refuted(conflict);
+        }
+        self.search(observer)
+    }
+
+    // names are only needed again once the search is over
+    pub(crate) fn model(&self) -> Assignment {
+        self.vars
+            .names()
+            .zip(&self.values)
+            .map(|(name, value)| (name.to_string(), value.unwrap_or(true)))
+            .collect()
+    }
+
+    // decide / propagate / learn until every variable is assigned or a
+    // conflict is found that does not depend on any decision
+    fn search(&mut self, observer: &mut dyn SolverObserver) -> Status {
+        loop {
+            let conflict = self.propagate().or_else(|| self.propagate_xors());
+            if let Some(conflict) = conflict {
+                observer.on_conflict();
+                self.stats.conflicts += 1;
+                self.conflicts_since_restart += 1;
+                if self.decisions.is_empty() {
+                    return self.refuted(conflict);
+                }
+                self.block_restart();
+
+                let (learnt, level) = self.analyze(conflict);
+                self.log_step(&learnt);
+                let lbd = self.lbd(&learnt);
+                self.backjump(level);
+                let asserting = learnt[0];
+                let index = self.attach(learnt, Some(lbd));
+                self.enqueue(asserting, Some(index));
+                self.decay_activity();
+
+                if let ClauseDeletion::Lbd { limit } = self.config.clause_deletion
+                    && self.stats.learned_clauses > limit
+                {
+                    self.reduce_clauses(limit / 2);
+                }
+
+                if self.out_of_budget() {
+                    return Status::Unknown;
+                }
+                continue;
+            }
+            if self.queue_head < self.trail.len() {
+                continue; // the parity constraints forced more
+            }
+
+            if self.restart_due() {
+                observer.on_restart();
+                self.stats.restarts += 1;
+                self.conflicts_since_restart = 0;
+                self.backjump(0);
+                if self.inprocessing_due()
+                    && let Some(conflict) = self.inprocess()
+                {
+                    return self.refuted(conflict);
+                }
+            }
+
+            // assumptions take the first levels, one each
+            if let Some(&lit) = self.assumptions.get(self.decisions.len()) {
+                if self.lit_value(lit) == Some(false) {
+                    self.failed = self.analyze_final(lit);
+                    return Status::Unsat;
+                }
+                // an assumption that already holds gets an empty level
+                self.decisions.push(self.trail.len());
+                self.enqueue(lit, None);
+                continue;
+            }
+
+            let decision = match self.config.heuristic {
+                Heuristic::Lookahead => self.lookahead(),
+                Heuristic::JeroslowWang => self.jeroslow_wang(),
+                Heuristic::Mom => self.mom(),
+                _ => self.pick_var().map(|var| {
+                    let value = if self.config.phase_saving {
+                        self.phases[var as usize]
+                    } else {
+                        self.polarity()
+                    };
+                    (var, value)
+                }),
+            };
+            let Some((var, value)) = decision else {
+                // every variable assigned without conflict
+                self.extend_model();
+                return Status::Sat;
+            };
+            self.stats.decisions += 1;
+
+            self.decisions.push(self.trail.len());
+            self.stats.max_depth = self.stats.max_depth.max(self.decisions.len());
+            observer.on_decision(self.vars.name(var).unwrap(), value);
+            self.enqueue(Lit::new(var, !value), None);
+        }
+    }
+
+    // `conflict` is false without any decision, so every later solve fails
+    // too
+    fn refuted(&mut self, conflict: usize) -> Status {
+        self.conflict = Some(conflict);
+        self.mark_core(conflict);
+        self.log_step(&[]);
+        Status::Unsat
+    }
+
+    fn log_step(&mut self, lits: &[Lit]) {
+        if let Some(proof) = &mut self.proof {
+            let clause = lits
+                .iter()
+                .map(|&lit| to_literal(&self.vars, lit))
+                .collect();
+            proof.push(ProofStep::Add(clause));
+        }
+    }
+
+    fn log_deletion(&mut self, lits: &[Lit]) {
+        if let Some(proof) = &mut self.proof {
+            let clause = lits
+                .iter()
+                .map(|&lit| to_literal(&self.vars, lit))
+                .collect();
+            proof.push(ProofStep::Delete(clause));
+        }
+    }
+
+    // first UIP: resolve the conflicting clause with the reasons of its
+    // literals from the current level, latest first, until one literal of
+    // that level is left. the result is false now, and after undoing the
+    // levels above the highest of its other literals it becomes unit.
+    // returns the clause with that literal first, and the level to jump to.
+    fn analyze(&mut self, conflict: usize) -> (Vec<Lit>, usize) {
+        let level = self.decisions.len();
+        let mut antecedents = self.core.as_ref().map(|_| vec![conflict]);
+        // placeholder for the asserting literal
+        let mut learnt = vec![Lit::new(0, false)];
+        let mut pending = 0;
+        let mut clause = conflict;
+        let mut pivot = None;
+        let mut index = self.trail.len();
+
+        loop {
+            for &lit in &self.clauses[clause] {
+                let var = lit.var as usize;
+                // literals fixed without any decision can never be true
+                if Some(lit.var) == pivot || self.seen[var] || self.levels[var] == 0 {
+                    continue;
+                }
+                self.seen[var] = true;
+                self.activity[var] += self.activity_inc;
+                if self.levels[var] == level {
+                    pending += 1;
+                } else {
+                    learnt.push(lit);
+                }
+            }
+
+            // the latest assignment taking part in the conflict
+            loop {
+                index -= 1;
+                if self.seen[self.trail[index] as usize] {
+                    break;
+                }
+            }
+            let var = self.trail[index];
+            self.seen[var as usize] = false;
+            pending -= 1;
+            if pending == 0 {
+                learnt[0] = Lit::new(var, self.values[var as usize].unwrap());
+                break;
+            }
+
+            clause = self.reasons[var as usize].unwrap();
+            pivot = Some(var);
+            if let Some(antecedents) = &mut antecedents {
+                antecedents.push(clause);
+            }
+        }
+
+        // variables whose reasons were found to be implied by the clause,
+        // also left marked as seen
+        let mut implied = Vec::new();
+        if self.config.minimize {
+            let mut kept = 1;
+            for k in 1..learnt.len() {
+                let lit = learnt[k];
+                if self.reasons[lit.var as usize].is_some() && self.redundant(lit.var, &mut implied)
+                {
+                    implied.push(lit.var);
+                } else {
+                    learnt[kept] = lit;
+                    kept += 1;
+                }
+            }
+            learnt.truncate(kept);
+        }
+
+        for &var in &implied {
+            self.seen[var as usize] = false;
+            if let Some(antecedents) = &mut antecedents {
+                antecedents.push(self.reasons[var as usize].unwrap());
+            }
+        }
+        for lit in &learnt[1..] {
+            self.seen[lit.var as usize] = false;
+        }
+        if let (Some(core), Some(antecedents)) = (&mut self.core, antecedents) {
+            core.antecedents.push(antecedents);
+        }
+
+        if learnt.len() == 1 {
+            return (learnt, 0);
+        }
+
+        // the second watch goes on the literal that stays false longest
+        let level_of = |lit: Lit| self.levels[lit.var as usize];
+        let mut latest = 1;
+        for k in 2..learnt.len() {
+            if level_of(learnt[k]) > level_of(learnt[latest]) {
+                latest = k;
+            }
+        }
+        learnt.swap(1, latest);
+        let level = level_of(learnt[1]);
+        (learnt, level)
+    }
+
+    // whether `var`, forced by a clause, is false whenever the literals
+    // marked seen are, because every literal of its reason is either seen,
+    // fixed without decisions or redundant in turn. the variables found
+    // redundant on the way are marked seen and pushed to `implied`, so later
+    // checks can stop at them.
+    fn redundant(&mut self, var: u32, implied: &mut Vec<u32>) -> bool {
+        let start = implied.len();
+        let mut stack = vec![var];
+        while let Some(var) = stack.pop() {
+            let reason = self.reasons[var as usize].expect("only forced variables are checked");
+            for k in 0..self.clauses[reason].len() {
+                let other = self.clauses[reason][k].var;
+                if other == var || self.seen[other as usize] || self.levels[other as usize] == 0 {
+                    continue;
+                }
+                if self.reasons[other as usize].is_none() {
+                    // a decision outside the clause, so `var` can be true
+                    for var in implied.drain(start..) {
+                        self.seen[var as usize] = false;
+                    }
+                    return false;
+                }
+                self.seen[other as usize] = true;
+                implied.push(other);
+                stack.push(other);
+            }
+        }
+        true
+    }
+
+    // the assumption `lit` is false, follow the reasons back to the earlier
+    // assumptions that forced it. every decision so far is an assumption.
+    fn analyze_final(&mut self, lit: Lit) -> Vec<Lit> {
+        let mut failed = vec![lit];
+        let Some(&start) = self.decisions.first() else {
+            return failed; // false without any assumption
+        };
+
+        self.seen[lit.var as usize] = true;
+        for index in (start..self.trail.len()).rev() {
+            let var = self.trail[index];
+            if !self.seen[var as usize] {
+                continue;
+            }
+            self.seen[var as usize] = false;
+            match self.reasons[var as usize] {
+                None => failed.push(Lit::new(var, self.values[var as usize] == Some(false))),
+                Some(reason) => {
+                    for &other in &self.clauses[reason] {
+                        if other.var != var && self.levels[other.var as usize] > 0 {
+                            self.seen[other.var as usize] = true;
+                        }
+                    }
+                }
+            }
+        }
+        // unvisited if `lit` was already false before the first assumption
+        self.seen[lit.var as usize] = false;
+        failed
+    }
+
+    // add a clause to the database watching its first two literals,
+    // returns its index. learned clauses come with their LBD.
+    fn attach(&mut self, lits: Vec<Lit>, lbd: Option<u32>) -> usize {
+        let index = self.clauses.len();
+        match lits[..] {
+            [first, second] => {
+                self.binaries[first.index()].push((second, index));
+                self.binaries[second.index()].push((first, index));
+            }
+            [first, second, ..] => {
+                self.watches[first.index()].push(index);
+                self.watches[second.index()].push(index);
+            }
+            _ => {}
+        }
+        if lbd.is_some() {
+            self.stats.learned_clauses += 1;
+            self.stats.learned_literals += lits.len() as u64;
+        }
+        self.clauses.push(lits);
+        self.lbds.push(lbd);
+        index
+    }
+
+    // the number of decision levels among the assigned literals of `lits`
+    fn lbd(&self, lits: &[Lit]) -> u32 {
+        let mut levels: Vec<usize> = lits
+            .iter()
+            .filter(|lit| self.values[lit.var as usize].is_some())
+            .map(|lit| self.levels[lit.var as usize])
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+        levels.len() as u32
+    }
+
+    // forget learned clauses, highest LBD and then oldest first, until
+    // `keep` are left. clauses that are the reason for a current assignment
+    // stay, and so does everything while tracking a core, which is traced
+    // back through the learned clauses.
+    fn reduce_clauses(&mut self, keep: usize) {
+        if self.core.is_some() {
+            return;
+        }
+
+        let locked = self.locked();
+        let mut candidates: Vec<usize> = (0..self.clauses.len())
+            .filter(|&index| self.lbds[index].is_some() && !locked[index])
+            .collect();
+        candidates.sort_by_key(|&index| (Reverse(self.lbds[index]), index));
+        candidates.truncate(self.stats.learned_clauses.saturating_sub(keep));
+
+        let mut deleted = vec![false; self.clauses.len()];
+        for index in candidates {
+            deleted[index] = true;
+        }
+        self.remove_clauses(&deleted);
+    }
+
+    // the clauses that are the reason for a current assignment
+    fn locked(&self) -> Vec<bool> {
+        let mut locked = vec![false; self.clauses.len()];
+        for &var in &self.trail {
+            if let Some(reason) = self.reasons[var as usize] {
+                locked[reason] = true;
+            }
+        }
+        locked
+    }
+
+    // empties the clauses marked in `removed` and takes them off the watch
+    // lists. clauses added after `removed` was made are kept.
+    fn remove_clauses(&mut self, removed: &[bool]) {
+        let removed = |index: usize| removed.get(index) == Some(&true);
+        for index in (0..self.clauses.len()).filter(|&index| removed(index)) {
+            let lits = core::mem::take(&mut self.clauses[index]);
+            self.log_deletion(&lits);
+            if self.lbds[index].take().is_some() {
+                self.stats.learned_clauses -= 1;
+                self.stats.deleted_clauses += 1;
+            }
+        }
+        for watchers in &mut self.watches {
+            watchers.retain(|&index| !removed(index));
+        }
+        for implied in &mut self.binaries {
+            implied.retain(|&(_, index)| !removed(index));
+        }
+    }
+
+    // undo every decision above `level` and what it implied
+    fn backjump(&mut self, level: usize) {
+        if let Some(&mark) = self.decisions.get(level) {
+            self.decisions.truncate(level);
+            self.backtrack(mark);
+        }
+    }
+
+    // start counting towards the next restart over while the trail is much
+    // longer than it recently was
+    fn block_restart(&mut self) {
+        let Some(window) = self.config.restart_blocking else {
+            return;
+        };
+        let size = self.trail.len();
+        let full = self.trail_sizes.len() >= window;
+        if full && size as f64 > BLOCKING_FACTOR * self.trail_sum as f64 / window as f64 {
+            self.conflicts_since_restart = 0;
+        }
+
+        self.trail_sizes.push_back(size);
+        self.trail_sum += size;
+        if self.trail_sizes.len() > window {
+            self.trail_sum -= self.trail_sizes.pop_front().expect("just pushed");
+        }
+    }
+
+    fn restart_due(&self) -> bool {
+        let limit = match self.config.restarts {
+            RestartPolicy::None => return false,
+            RestartPolicy::Luby(base) => base as f64 * luby(self.stats.restarts + 1) as f64,
+            RestartPolicy::Geometric(factor) => {
+                GEOMETRIC_FIRST_RESTART * powi(factor, self.stats.restarts)
+            }
+        };
+        self.conflicts_since_restart as f64 >= limit
+    }
+
+    fn inprocessing_due(&self) -> bool {
+        let Some(every) = self.config.inprocessing else {
+            return false;
+        };
+        self.stats.restarts.is_multiple_of(every.max(1))
+            && self.xors.is_none()
+            && self.core.is_none()
+            && self.proof.is_none()
+    }
+
+    // a bounded round of simplification at the top level, keeping every
+    // learned clause a consequence of the clauses that stay. returns a
+    // clause found false, which refutes the formula.
+    fn inprocess(&mut self) -> Option<usize> {
+        self.subsume_clauses();
+        if let Some(conflict) = self.probe() {
+            return Some(conflict);
+        }
+        self.eliminate_vars();
+        self.conflict.or_else(|| self.propagate())
+    }
+
+    // removes clauses containing all the literals of a shorter or equal
+    // earlier one. when a learned clause subsumes an input clause it takes
+    // its place among the input clauses, so it is never forgotten.
+    fn subsume_clauses(&mut self) {
+        let locked = self.locked();
+        let mut order: Vec<usize> = (0..self.clauses.len())
+            .filter(|&index| !self.clauses[index].is_empty())
+            .collect();
+        order.sort_by_key(|&index| self.clauses[index].len());
+        let mut occurs = vec![Vec::new(); self.watches.len()];
+        for &index in &order {
+            for lit in &self.clauses[index] {
+                occurs[lit.index()].push(index);
+            }
+        }
+
+        let mut removed = vec![false; self.clauses.len()];
+        let mut checks = 0;
+        for &index in &order {
+            if removed[index] {
+                continue;
+            }
+            // every clause containing this one contains its rarest literal
+            let rarest = *self.clauses[index]
+                .iter()
+                .min_by_key(|lit| occurs[lit.index()].len())
+                .expect("empty clauses are left out");
+            for &other in &occurs[rarest.index()] {
+                checks += 1;
+                if other == index
+                    || removed[other]
+                    || locked[other]
+                    || !self.clauses[index]
+                        .iter()
+                        .all(|lit| self.clauses[other].contains(lit))
+                {
+                    continue;
+                }
+                removed[other] = true;
+                if self.lbds[other].is_none() && self.lbds[index].take().is_some() {
+                    self.stats.learned_clauses -= 1;
+                }
+            }
+            if checks >= SUBSUMPTION_CHECKS {
+                break;
+            }
+        }
+        self.remove_clauses(&removed);
+    }
+
+    // tries both values of the most active unassigned variables. a value
+    // that propagates into a conflict is learned false, as the unit the
+    // conflict analyses into. returns a clause found false at the top
+    // level. probes are not counted as propagations.
+    fn probe(&mut self) -> Option<usize> {
+        let mut vars: Vec<u32> = (0..self.values.len() as u32)
+            .filter(|&var| self.values[var as usize].is_none())
+            .collect();
+        vars.sort_by(|&a, &b| self.activity[b as usize].total_cmp(&self.activity[a as usize]));
+        vars.truncate(PROBED_VARS);
+
+        for var in vars {
+            for negated in [false, true] {
+                if self.values[var as usize].is_some() {
+                    break; // forced by an earlier probe
+                }
+                let propagations = self.stats.propagations;
+                let mark = self.trail.len();
+                self.decisions.push(mark);
+                self.enqueue(Lit::new(var, negated), None);
+                let learnt = self.propagate().map(|conflict| self.analyze(conflict).0);
+                let lbd = learnt.as_deref().map(|learnt| self.lbd(learnt));
+                self.decisions.pop();
+                self.undo_probe(mark);
+                self.stats.propagations = propagations;
+
+                if let Some(learnt) = learnt {
+                    self.log_step(&learnt);
+                    let asserting = learnt[0];
+                    let index = self.attach(learnt, lbd);
+                    self.enqueue(asserting, Some(index));
+                    if let Some(conflict) = self.propagate() {
+                        return Some(conflict);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    // bounded variable elimination over the input clauses: a variable in
+    // few of them is replaced by their resolvents on it when there are no
+    // more of those than clauses removed. learned clauses with the variable
+    // are forgotten. resolvents that are units are propagated by the
+    // caller, an empty one sets `conflict`.
+    fn eliminate_vars(&mut self) {
+        let mut occurs = vec![Vec::new(); self.watches.len()];
+        for (index, clause) in self.clauses.iter().enumerate() {
+            for lit in clause {
+                occurs[lit.index()].push(index);
+            }
+        }
+
+        let mut removed = vec![false; self.clauses.len()];
+        for var in 0..self.values.len() as u32 {
+            if self.values[var as usize].is_some()
+                || self.assumptions.iter().any(|lit| lit.var == var)
+            {
+                continue;
+            }
+            let [pos, neg] = [false, true].map(|negated| -> Vec<usize> {
+                occurs[Lit::new(var, negated).index()]
+                    .iter()
+                    .copied()
+                    .filter(|&index| !removed[index] && self.lbds[index].is_none())
+                    .collect()
+            });
+            let limit = pos.len() + neg.len();
+            if limit == 0 || limit > ELIMINATION_OCCURRENCES {
+                continue;
+            }
+            let mut resolvents = Vec::new();
+            for &p in &pos {
+                for &n in &neg {
+                    if let Some(resolvent) = self.resolvent(p, n, var) {
+                        resolvents.push(resolvent);
+                    }
+                }
+            }
+            if resolvents.len() > limit {
+                continue;
+            }
+
+            for negated in [false, true] {
+                for &index in &occurs[Lit::new(var, negated).index()] {
+                    removed[index] = true;
+                }
+            }
+            let clauses = pos.iter().chain(&neg);
+            let clauses = clauses.map(|&index| self.clauses[index].clone()).collect();
+            self.eliminated.push((var, clauses));
+            for resolvent in resolvents {
+                self.add_lits(resolvent, None);
+                let index = self.clauses.len() - 1;
+                for lit in &self.clauses[index] {
+                    occurs[lit.index()].push(index);
+                }
+                removed.push(false);
+            }
+            if self.conflict.is_some() {
+                break;
+            }
+        }
+        self.remove_clauses(&removed);
+    }
+
+    // the resolvent of two clauses on `var` without the literals false at
+    // the top level, `None` if it is a tautology or already satisfied
+    fn resolvent(&self, pos: usize, neg: usize, var: u32) -> Option<Vec<Lit>> {
+        let mut lits: Vec<Lit> = Vec::new();
+        for &lit in self.clauses[pos].iter().chain(&self.clauses[neg]) {
+            if lit.var == var || lits.contains(&lit) {
+                continue;
+            }
+            match self.lit_value(lit) {
+                Some(true) => return None,
+                Some(false) => continue,
+                None => {}
+            }
+            if lits.contains(&Lit::new(lit.var, !lit.negated)) {
+                return None;
+            }
+            lits.push(lit);
+        }
+        Some(lits)
+    }
+
+    fn is_eliminated(&self, var: u32) -> bool {
+        self.eliminated
+            .iter()
+            .any(|&(eliminated, _)| eliminated == var)
+    }
+
+    // gives the eliminated variables values satisfying their removed
+    // clauses, the latest eliminated first, once the others are assigned.
+    // a variable is only made true if one of its positive clauses needs it.
+    fn extend_model(&mut self) {
+        for (var, clauses) in self.eliminated.iter().rev() {
+            let positive = Lit::new(*var, false);
+            let value = clauses.iter().any(|clause| {
+                clause.contains(&positive)
+                    && clause
+                        .iter()
+                        .all(|&lit| lit.var == *var || lit_value(&self.values, lit) == Some(false))
+            });
+            self.values[*var as usize] = Some(value);
+        }
+    }
+
+    // puts the removed clauses of every eliminated variable back, since a
+    // clause or assumption about one of them is coming. resolvents stay,
+    // they follow from the clauses.
+    fn restore_eliminated(&mut self) {
+        if self.eliminated.is_empty() {
+            return;
+        }
+        self.backjump(0);
+        for (_, clauses) in core::mem::take(&mut self.eliminated) {
+            for lits in clauses {
+                self.add_lits(lits, None);
+            }
+        }
+    }
+
+    fn out_of_budget(&mut self) -> bool {
+        let reached = |limit: Option<u64>, count| limit.is_some_and(|limit| count >= limit);
+        self.stopped = if reached(self.conflict_limit, self.stats.conflicts) {
+            Some(Limit::Conflicts)
+        } else if reached(self.propagation_limit, self.stats.propagations) {
+            Some(Limit::Propagations)
+        } else if self.out_of_time() {
+            Some(Limit::Time)
+        } else {
+            None
+        };
+        self.stopped.is_some()
+    }
+
+    #[cfg(not(feature = "no_std"))]
+    fn out_of_time(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => {
+                self.stats.conflicts.is_multiple_of(CLOCK_INTERVAL) && Instant::now() >= deadline
+            }
+            None => false,
+        }
+    }
+
+    // without a clock there are no time limits
+    #[cfg(feature = "no_std")]
+    fn out_of_time(&self) -> bool {
+        false
+    }
+
+    fn pick_var(&self) -> Option<u32> {
+        let unassigned = (0..self.values.len()).filter(|&var| self.values[var].is_none());
+        // the heuristic only chooses among the highest priority
+        let top = unassigned
+            .clone()
+            .map(|var| self.priority[var])
+            .fold(f64::NEG_INFINITY, f64::max);
+        let mut unassigned = unassigned.filter(|&var| self.priority[var] == top);
+        let var = match self.config.heuristic {
+            // the heuristics scoring clauses break their ties the same way
+            Heuristic::FirstUnassigned
+            | Heuristic::Lookahead
+            | Heuristic::JeroslowWang
+            | Heuristic::Mom => unassigned.next(),
+            // ties go to the earlier variable, so before the first conflict
+            // this behaves like `FirstUnassigned`
+            Heuristic::Vsids => unassigned.reduce(|best, var| {
+                if self.activity[var] > self.activity[best] {
+                    var
+                } else {
+                    best
+                }
+            }),
+        };
+        var.map(|var| var as u32)
+    }
+
+    // the value to branch on without a saved phase: true, or random when
+    // a seed was given
+    fn polarity(&mut self) -> bool {
+        self.rng.as_mut().is_none_or(|rng| rng.below(2) == 0)
+    }
+
+    // the unassigned literal with the highest Jeroslow-Wang score, ties
+    // going to the earlier variable and then to true. only the input
+    // clauses count, learned ones would skew the scores towards the
+    // current conflicts
+    fn jeroslow_wang(&self) -> Option<(u32, bool)> {
+        let mut scores = vec![[0.0f64; 2]; self.values.len()];
+        for free in self.open_clauses() {
+            let weight = 1.0 / (1u64 << free.len().min(63)) as f64;
+            for lit in free {
+                scores[lit.var as usize][lit.negated as usize] += weight;
+            }
+        }
+
+        let mut best: Option<(u32, bool, f64)> = None;
+        for var in (0..self.values.len()).filter(|&var| self.values[var].is_none()) {
+            for negated in [false, true] {
+                let score = scores[var][negated as usize];
+                if best.is_none_or(|(_, _, best)| score > best) {
+                    best = Some((var as u32, !negated, score));
+                }
+            }
+        }
+        best.map(|(var, value, _)| (var, value))
+    }
+
+    // the unassigned variable occurring most in the shortest open input
+    // clauses, ties going to the earlier variable, with the value it
+    // occurs with more there, true on a tie
+    fn mom(&self) -> Option<(u32, bool)> {
+        let open: Vec<Vec<Lit>> = self.open_clauses().collect();
+        let shortest = open.iter().map(Vec::len).min().unwrap_or(0);
+        let mut counts = vec![[0usize; 2]; self.values.len()];
+        for lit in open.iter().filter(|free| free.len() == shortest).flatten() {
+            counts[lit.var as usize][lit.negated as usize] += 1;
+        }
+
+        let mut best: Option<(usize, usize)> = None;
+        for var in (0..self.values.len()).filter(|&var| self.values[var].is_none()) {
+            let count = counts[var][0] + counts[var][1];
+            if best.is_none_or(|(_, best)| count > best) {
+                best = Some((var, count));
+            }
+        }
+        best.map(|(var, _)| (var as u32, counts[var][0] >= counts[var][1]))
+    }
+
+    // the unassigned literals of every input clause no literal satisfies
+    // yet, which the scoring heuristics look at
+    fn open_clauses(&self) -> impl Iterator<Item = Vec<Lit>> + '_ {
+        self.clauses
+            .iter()
+            .zip(&self.lbds)
+            .filter(|(clause, lbd)| {
+                lbd.is_none() && !clause.iter().any(|&lit| self.lit_value(lit) == Some(true))
+            })
+            .map(|(clause, _)| {
+                clause
+                    .iter()
+                    .copied()
+                    .filter(|&lit| self.lit_value(lit).is_none())
+                    .collect::<Vec<_>>()
+            })
+            .filter(|free| !free.is_empty())
+    }
+
+    // tentatively assigns both values of every unassigned variable and
+    // propagates. returns the variable with the most forced assignments,
+    // ties going to the earlier one, and the value forcing more. a value
+    // that leads to a conflict on its own decides right away for the other
+    // one. the probes are not counted as propagations.
+    fn lookahead(&mut self) -> Option<(u32, bool)> {
+        let propagations = self.stats.propagations;
+        let mut best: Option<(u32, bool, usize)> = None;
+        let mut decided = None;
+        for var in 0..self.values.len() as u32 {
+            if self.values[var as usize].is_some() {
+                continue;
+            }
+            let mut forced = [0; 2];
+            let mut failed = [false; 2];
+            for (k, value) in [true, false].into_iter().enumerate() {
+                let mark = self.trail.len();
+                self.enqueue(Lit::new(var, !value), None);
+                failed[k] = self.propagate().is_some();
+                forced[k] = self.trail.len() - mark;
+                self.undo_probe(mark);
+            }
+            match failed {
+                [false, false] => {
+                    let score = (forced[0] + 1) * (forced[1] + 1);
+                    if best.is_none_or(|(_, _, best)| score > best) {
+                        best = Some((var, forced[0] >= forced[1], score));
+                    }
+                }
+                // when both fail the conflict is found on the next step
+                [true, _] => decided = Some((var, false)),
+                [false, true] => decided = Some((var, true)),
+            }
+            if decided.is_some() {
+                break;
+            }
+        }
+        self.stats.propagations = propagations;
+        decided.or(best.map(|(var, value, _)| (var, value)))
+    }
+
+    // undo a probe, unlike `backtrack` without touching the saved phases
+    fn undo_probe(&mut self, mark: usize) {
+        for var in self.trail.drain(mark..) {
+            self.values[var as usize] = None;
+        }
+        self.queue_head = self.queue_head.min(mark);
+    }
+
+    fn decay_activity(&mut self) {
+        self.activity_inc *= ACTIVITY_GROWTH;
+
+        // keep the scores finite, only their order matters
+        if self.activity_inc > 1e100 {
+            for activity in &mut self.activity {
+                *activity *= 1e-100;
+            }
+            self.activity_inc *= 1e-100;
+        }
+    }
+
+    fn lit_value(&self, lit: Lit) -> Option<bool> {
+        lit_value(&self.values, lit)
+    }
+
+    // make `lit` true, returns false if it is already false
+    fn enqueue(&mut self, lit: Lit, reason: Option<usize>) -> bool {
+        match self.lit_value(lit) {
+            Some(value) => value,
+            None => {
+                self.values[lit.var as usize] = Some(!lit.negated);
+                self.reasons[lit.var as usize] = reason;
+                self.levels[lit.var as usize] = self.decisions.len();
+                if reason.is_some() {
+                    self.stats.propagations += 1;
+                }
+                self.trail.push(lit.var);
+                true
+            }
+        }
+    }
+
+    fn backtrack(&mut self, mark: usize) {
+        for var in self.trail.drain(mark..) {
+            if let Some(value) = self.values[var as usize].take() {
+                self.phases[var as usize] = value;
+            }
+        }
+        self.queue_head = self.queue_head.min(mark);
+    }
+
+    // returns the clause that became false, if any
+    fn propagate(&mut self) -> Option<usize> {
+        while self.queue_head < self.trail.len() {
+            let var = self.trail[self.queue_head];
+            self.queue_head += 1;
+
+            let value = self.values[var as usize].unwrap();
+            let falsified = Lit::new(var, value);
+
+            for k in 0..self.binaries[falsified.index()].len() {
+                let (other, index) = self.binaries[falsified.index()][k];
+                match lit_value(&self.values, other) {
+                    Some(true) => {}
+                    Some(false) => return Some(index),
+                    None => {
+                        self.enqueue(other, Some(index));
+                    }
+                }
+            }
+
+            // the list is taken out while clauses move their watches to
+            // other literals, none of which can be `falsified` itself
+            let mut watchers = core::mem::take(&mut self.watches[falsified.index()]);
+            let mut conflict = None;
+            let mut i = 0;
+            while i < watchers.len() {
+                let index = watchers[i];
+                let clause = &mut self.clauses[index];
+                if clause[0] == falsified {
+                    clause.swap(0, 1);
+                }
+
+                let other = clause[0];
+                if lit_value(&self.values, other) == Some(true) {
+                    i += 1;
+                    continue; // satisfied, keep watching
+                }
+
+                let replacement =
+                    (2..clause.len()).find(|&k| lit_value(&self.values, clause[k]) != Some(false));
+                if let Some(k) = replacement {
+                    clause.swap(1, k);
+                    self.watches[clause[1].index()].push(index);
+                    watchers.swap_remove(i);
+                    continue;
+                }
+
+                // every literal but `other` is false
+                i += 1;
+                if lit_value(&self.values, other) == Some(false) {
+                    conflict = Some(index);
+                    break;
+                }
+                self.enqueue(other, Some(index));
+            }
+            self.watches[falsified.index()] = watchers;
+
+            if conflict.is_some() {
+                return conflict;
+            }
+        }
+
+        #[cfg(any(test, feature = "debug_invariants"))]
+        self.check_watches();
+        None
+    }
+
+    // after propagating without conflict, every clause not yet satisfied
+    // watches two literals that are not false, and is in the watch lists
+    // of both. a formula found false while loading breaks this on purpose
+    #[cfg(any(test, feature = "debug_invariants"))]
+    fn check_watches(&self) {
+        if self.conflict.is_some() {
+            return;
+        }
+        // every watch list entry is one of the first two literals of its
+        // clause, and each clause is listed twice. binary clauses are
+        // listed with their other literal instead
+        let mut listed = vec![0; self.clauses.len()];
+        for (lit_index, watchers) in self.watches.iter().enumerate() {
+            for &index in watchers {
+                let watched = &self.clauses[index][..self.clauses[index].len().min(2)];
+                assert!(
+                    self.clauses[index].len() > 2
+                        && watched.iter().any(|lit| lit.index() == lit_index),
+                    "clause {index} is listed for a literal it does not watch"
+                );
+                listed[index] += 1;
+            }
+        }
+        for (lit_index, implied) in self.binaries.iter().enumerate() {
+            for &(other, index) in implied {
+                let clause = &self.clauses[index];
+                assert!(
+                    clause.len() == 2
+                        && clause.contains(&other)
+                        && clause
+                            .iter()
+                            .any(|lit| lit.index() == lit_index && *lit != other),
+                    "clause {index} is listed wrongly as a binary clause"
+                );
+                listed[index] += 1;
+            }
+        }
+        for (index, clause) in self.clauses.iter().enumerate() {
+            let [first, second, ..] = clause[..] else {
+                continue; // units and forgotten clauses are not watched
+            };
+            assert_eq!(
+                listed[index], 2,
+                "clause {index} is not in both watch lists"
+            );
+            let satisfied = clause.iter().any(|&lit| self.lit_value(lit) == Some(true));
+            assert!(
+                satisfied
+                    || [first, second]
+                        .iter()
+                        .all(|&lit| self.lit_value(lit).is_none()),
+                "clause {index} is unsatisfied but watches a false literal"
+            );
+        }
+    }
+
+    // every deduction from the parity constraints is added as a clause, so
+    // conflict analysis can use it like any other reason. returns the
+    // clause that is false, if any.
+    fn propagate_xors(&mut self) -> Option<usize> {
+        let deductions = self.xors.as_ref()?.deduce(&self.values);
+        for deduction in deductions {
+            let (implied, assigned) = match deduction {
+                Deduction::Conflict(assigned) => (None, assigned),
+                Deduction::Implied(var, value, assigned) => (Some(Lit::new(var, !value)), assigned),
+            };
+
+            // the literal each assigned variable makes false, the latest
+            // first so the watches are the first to be unassigned again
+            let mut lits: Vec<Lit> = assigned
+                .into_iter()
+                .map(|var| Lit::new(var, self.values[var as usize].unwrap()))
+                .collect();
+            lits.sort_by_key(|lit| Reverse(self.levels[lit.var as usize]));
+            let lbd = self.lbd(&lits);
+
+            match implied {
+                None => return Some(self.attach(lits, Some(lbd))),
+                Some(lit) => {
+                    lits.insert(0, lit);
+                    let index = self.attach(lits, Some(lbd));
+                    self.enqueue(lit, Some(index));
+                }
+            }
+        }
+        None
+    }
+
+    // mark the clause found false without any decision, the reasons of its
+    // literals and, for learned clauses, the clauses they were derived
+    // from. every clause reached this way stays in place for the rest of
+    // the search, so the marked input clauses are unsatisfiable on their
+    // own.
+    fn mark_core(&mut self, conflict: usize) {
+        let Some(core) = &mut self.core else {
+            return;
+        };
+
+        let inputs = core.clauses.len();
+        let mut visited = vec![false; self.clauses.len()];
+        let mut pending = vec![conflict];
+        while let Some(index) = pending.pop() {
+            if visited[index] {
+                continue;
+            }
+            visited[index] = true;
+            match index.checked_sub(inputs) {
+                Some(learned) => pending.extend(&core.antecedents[learned]),
+                None => core.marked[index] = true,
+            }
+            // literals that were dropped while learning are false at level 0
+            for lit in &self.clauses[index] {
+                if let Some(reason) = self.reasons[lit.var as usize] {
+                    pending.push(reason);
+                }
+            }
+        }
+    }
+
+    // the positions among the loaded clauses of those marked by
+    // `mark_core`, in order
+    pub(crate) fn core_positions(&self) -> Vec<usize> {
+        let Some(core) = &self.core else {
+            return Vec::new();
+        };
+        (0..core.marked.len()).filter(|&i| core.marked[i]).collect()
+    }
+
+    // the input clauses marked by `mark_core`, in input order
+    pub(crate) fn core(&self) -> Vec<Clause> {
+        let Some(core) = &self.core else {
+            return Vec::new();
+        };
+
+        core.clauses
+            .iter()
+            .zip(&core.marked)
+            .filter(|&(_, &marked)| marked)
+            .map(|(clause, _)| {
+                clause
+                    .iter()
+                    .map(|&lit| to_literal(&self.vars, lit))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+impl Solver {
+    // for a refutation found before any decision, the variable the false
+    // clause would have forced last, with that clause and the reason the
+    // variable got the opposite value
+    fn root_conflict(&self) -> Refutation {
+        let Some(conflict) = self.conflict else {
+            return Refutation::Search;
+        };
+        if self.stats.decisions > 0 {
+            return Refutation::Search;
+        }
+
+        let position = |lit: &Lit| self.trail.iter().position(|&var| var == lit.var);
+        let Some(&lit) = self.clauses[conflict]
+            .iter()
+            .max_by_key(|lit| position(lit))
+        else {
+            return Refutation::EmptyClause;
+        };
+        let reason = self.reasons[lit.var as usize].expect("no decisions were made");
+        let (positive, negative) = match lit.negated {
+            false => (conflict, reason),
+            true => (reason, conflict),
+        };
+        let clause = |index: usize| -> Clause {
+            self.clauses[index]
+                .iter()
+                .map(|&lit| to_literal(&self.vars, lit))
+                .collect()
+        };
+        Refutation::RootConflict {
+            var: self.vars.name(lit.var).unwrap().to_string(),
+            positive: clause(positive),
+            negative: clause(negative),
+        }
+    }
+}
+
+fn to_literal(vars: &VarMap, lit: Lit) -> Literal {
+    Literal {
+        name: vars.name(lit.var).unwrap().to_string(),
+        negated: lit.negated,
+    }
+}
+
+fn lit_value(values: &[Option<bool>], lit: Lit) -> Option<bool> {
+    values[lit.var as usize].map(|value| value != lit.negated)
+}
+
+// `f64::powi` is not available without std
+fn powi(mut base: f64, mut exp: u64) -> f64 {
+    let mut result = 1.0;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result *= base;
+        }
+        base *= base;
+        exp >>= 1;
+    }
+    result
+}
+
+// the `i`th term, counting from 1, of 1, 1, 2, 1, 1, 2, 4, 1, 1, 2, ...
+// where each block repeats everything before it and then doubles
+fn luby(mut i: u64) -> u64 {
+    loop {
+        let mut k = 1;
+        while (1 << k) - 1 < i {
+            k += 1;
+        }
+        if i == (1 << k) - 1 {
+            return 1 << (k - 1);
+        }
+        i -= (1 << (k - 1)) - 1;
+    }
+}
+
+/// Solves `cnf` and, if it is unsatisfiable, returns a subset of its
+/// clauses that is unsatisfiable by itself. The subset is not necessarily
+/// minimal.
+pub fn solve_with_core(cnf: CNF) -> Result<Assignment, Vec<Clause>> {
+    let mut solver = Solver::new(cnf);
+    solver.track_core();
+    match solver.solve() {
+        SatResult::Sat(model) => Ok(model),
+        SatResult::Unsat => Err(solver.core()),
+    }
+}
+
+// solves `cnf` like `solve_with_core`, but reports the core as indices
+// into `cnf`. the clauses in the core have their repeated literals
+// removed, so they need not compare equal to the inputs they came from
+fn solve_with_core_indices(cnf: &CNF) -> Result<Assignment, Vec<usize>> {
+    let mut solver = Solver::from_clauses(cnf, SolverConfig::default());
+    solver.track_core();
+    if let SatResult::Sat(model) = solver.solve() {
+        return Ok(model);
+    }
+    // without subsumption every clause but the tautologies is loaded, in
+    // input order
+    let loaded: Vec<usize> = (0..cnf.len()).filter(|&i| !is_tautology(&cnf[i])).collect();
+    Err(solver
+        .core_positions()
+        .into_iter()
+        .map(|position| loaded[position])
+        .collect())
+}
+
+/// Solves `cnf` like `solve_with_core`, reporting the core as the `meta`
+/// of its clauses, so which input lines the refutation was learned from.
+/// `meta` must have one entry per clause, as from `parse_with_meta`.
+pub fn solve_with_core_meta(cnf: CNF, meta: &[ClauseMeta]) -> Result<Assignment, Vec<ClauseMeta>> {
+    assert_eq!(cnf.len(), meta.len(), "one meta entry per clause");
+    match solve_with_core_indices(&cnf) {
+        Ok(model) => Ok(model),
+        Err(core) => Err(core.into_iter().map(|i| meta[i]).collect()),
+    }
+}
+
+/// Solves `cnf` like `solve` and also returns what the search did.
+/// `None` means the formula is unsatisfiable. A Horn formula is solved
+/// without a search, only the variables forced true count as
+/// propagations.
+pub fn solve_with_stats(cnf: CNF) -> (Option<Assignment>, Stats) {
+    if is_horn(&cnf) {
+        let (model, propagations) = forward_chain(&cnf);
+        let stats = Stats {
+            propagations,
+            ..Stats::default()
+        };
+        return (model, stats);
+    }
+    let mut solver = Solver::new(cnf);
+    let model = match solver.solve() {
+        SatResult::Sat(model) => Some(model),
+        SatResult::Unsat => None,
+    };
+    (model, solver.stats)
+}
+
+/// Solves `cnf` together with parity constraints. The constraints are
+/// reasoned about by Gaussian elimination instead of being expanded into
+/// clauses, which would take exponentially many.
+pub fn solve_with_xor(cnf: CNF, xors: Vec<XorClause>) -> SatResult {
+    let mut solver = Solver::new(cnf);
+    solver.add_xors(&xors);
+    solver.solve()
+}
+
+/// Solves `cnf` and, if it is unsatisfiable, returns a proof of that: the
+/// clauses learned along the way, ending with the empty clause. Each
+/// follows from the formula and the clauses before it by unit
+/// propagation, which is what DRAT checkers verify.
+pub fn solve_with_proof(cnf: CNF) -> Result<Assignment, Vec<ProofStep>> {
+    let mut solver = Solver::new(cnf);
+    solver.track_proof();
+    match solver.solve() {
+        SatResult::Sat(model) => Ok(model),
+        SatResult::Unsat => Err(solver.proof.unwrap_or_default()),
+    }
+}
+
+/// Solves `cnf` with the given search settings.
+pub fn solve_with_config(cnf: CNF, config: SolverConfig) -> SatResult {
+    Solver::with_config(cnf, config).solve()
+}
+
+/// Solves `cnf` with the variables in `fixed` pinned to their values. They
+/// are decided before anything else and never flipped, so `None` means no
+/// model extends `fixed`, including when the pinned values already
+/// contradict the formula. The model contains the pinned variables too.
+pub fn solve_with_fixed(cnf: CNF, fixed: Assignment) -> Option<Assignment> {
+    let mut pinned: Vec<Literal> = fixed
+        .into_iter()
+        .map(|(name, value)| Literal {
+            name,
+            negated: !value,
+        })
+        .collect();
+    // decide in a fixed order rather than hash order
+    pinned.sort_by(|a, b| a.name.cmp(&b.name));
+    Solver::new(cnf).solve_under(&pinned)
+}
+
+/// Solves `cnf` with every literal of `assumptions` forced true, in one
+/// call without keeping a `Solver` around. Unlike `solve_with_fixed` the
+/// assumptions are literals, so `-a` forces `a` false. `None` means no
+/// model makes all of them true, also when they contradict each other.
+pub fn solve_assuming(cnf: CNF, assumptions: &[Literal]) -> Option<Assignment> {
+    Solver::new(cnf).solve_under(assumptions)
+}
+
+/// Returned when a solve runs out of time before reaching a verdict.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeout;
+
+impl core::fmt::Display for Timeout {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "solver timed out")
+    }
+}
+
+impl core::error::Error for Timeout {}
+
+/// Why a formula was found unsatisfiable, as far as it is cheap to tell.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Refutation {
+    /// The formula contains the empty clause.
+    EmptyClause,
+    /// Unit propagation forced `var` both ways before any decision was
+    /// made: `positive` forced it true and `negative` forced it false.
+    RootConflict {
+        var: String,
+        positive: Clause,
+        negative: Clause,
+    },
+    /// The contradiction was only found by searching. `solve_with_core`
+    /// can tell which clauses are responsible.
+    Search,
+}
+
+impl core::fmt::Display for Refutation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Refutation::EmptyClause => write!(f, "the formula contains the empty clause"),
+            Refutation::RootConflict {
+                var,
+                positive,
+                negative,
+            } => write!(
+                f,
+                "variable `{var}` is forced true by {} and false by {}",
+                format_cnf(core::slice::from_ref(positive)),
+                format_cnf(core::slice::from_ref(negative))
+            ),
+            Refutation::Search => write!(f, "unsatisfiable after search"),
+        }
+    }
+}
+
+impl core::error::Error for Refutation {}
+
+/// Solves `cnf` like `solve`, explaining an unsatisfiable result when unit
+/// propagation alone refutes the formula. This is much cheaper than
+/// tracking a core.
+pub fn solve_with_refutation(cnf: CNF) -> Result<Assignment, Refutation> {
+    let mut solver = Solver::new(cnf);
+    match solver.solve() {
+        SatResult::Sat(model) => Ok(model),
+        SatResult::Unsat => Err(solver.root_conflict()),
+    }
+}
+
+/// Whether a formula implies a literal, see `entails`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Entailment {
+    /// Every model sets the literal true. The clauses are a subset of the
+    /// formula that implies it by itself, and no clause can be left out.
+    Entailed(Vec<Clause>),
+    /// Some model sets the literal false.
+    NotEntailed,
+}
+
+/// Whether every model of `cnf` makes `lit` true, found by refuting `cnf`
+/// together with the negation of `lit`. When it does, the core of that
+/// refutation is shrunk to the clauses the literal actually follows from.
+/// An unsatisfiable formula entails every literal.
+pub fn entails(cnf: &CNF, lit: &Literal) -> Entailment {
+    let negation = vec![Literal {
+        name: lit.name.clone(),
+        negated: !lit.negated,
+    }];
+    let refutes = |support: &[Clause]| {
+        let mut cnf = support.to_vec();
+        cnf.push(negation.clone());
+        Solver::new(cnf).solve() == SatResult::Unsat
+    };
+
+    let mut query = cnf.clone();
+    query.push(negation.clone());
+    let Err(core) = solve_with_core(query) else {
+        return Entailment::NotEntailed;
+    };
+    let mut support: Vec<Clause> = core
+        .into_iter()
+        .filter(|clause| *clause != negation)
+        .collect();
+    // leave out each clause the rest still implies the literal without
+    let mut i = 0;
+    while i < support.len() {
+        let clause = support.remove(i);
+        if !refutes(&support) {
+            support.insert(i, clause);
+            i += 1;
+        }
+    }
+    Entailment::Entailed(support)
+}
+
+/// The clauses of an unsatisfiable `cnf` each of which can be left out on
+/// its own with the rest still unsatisfiable, in input order. Removing
+/// several of them at once may make the formula satisfiable. Clauses
+/// outside an unsatisfiable core are always redundant, each of the others
+/// is checked by solving without it. A satisfiable `cnf` has none.
+pub fn redundant_clauses(cnf: &CNF) -> Vec<Clause> {
+    let core = match solve_with_core_indices(cnf) {
+        Ok(_) => return Vec::new(),
+        Err(core) => core,
+    };
+
+    (0..cnf.len())
+        .filter(|&i| {
+            if !core.contains(&i) {
+                return true;
+            }
+            let mut rest = cnf.clone();
+            rest.remove(i);
+            Solver::new(rest).solve() == SatResult::Unsat
+        })
+        .map(|i| cnf[i].clone())
+        .collect()
+}
+
+/// Solves `cnf` like `solve`, reporting decisions, conflicts and restarts
+/// to `observer` as they happen.
+pub fn solve_with_observer(cnf: CNF, observer: &mut impl SolverObserver) -> SatResult {
+    let mut solver = Solver::new(cnf);
+    match solver.run_observed(observer) {
+        Status::Sat => SatResult::Sat(solver.model()),
+        Status::Unsat => SatResult::Unsat,
+        Status::Unknown => unreachable!("search without limits always finishes"),
+    }
+}
+
+/// Solves `cnf` while `oracle` may reject models, as in lazy clause
+/// generation. Every model found is passed to `oracle`: `None` accepts it,
+/// and clauses returned instead are added for good and the search goes on,
+/// keeping what it learned. `Unsat` means no model was accepted.
+///
+/// Panics if the oracle rejects a model with clauses that all hold in it,
+/// since the same model could then be found forever.
+pub fn solve_with_oracle<F: FnMut(&Assignment) -> Option<Vec<Clause>>>(
+    cnf: CNF,
+    mut oracle: F,
+) -> SatResult {
+    let mut solver = Solver::new(cnf);
+    while let Some(model) = solver.solve_under(&[]) {
+        let Some(clauses) = oracle(&model) else {
+            return SatResult::Sat(model);
+        };
+        assert!(
+            !crate::verify(&clauses, &model),
+            "the oracle rejected a model without a clause it falsifies"
+        );
+        solver.add_cnf(clauses);
+    }
+    SatResult::Unsat
+}
+
+/// Solves `cnf`, giving up after `max_conflicts` conflicts. `None` means the
+/// budget ran out first, otherwise the inner `None` means the formula is
+/// unsatisfiable. Unlike a time limit this gives the same answer on every
+/// machine.
+pub fn solve_with_budget(cnf: CNF, max_conflicts: u64) -> Option<Option<Assignment>> {
+    let limits = SolverLimits {
+        max_conflicts: Some(max_conflicts),
+        ..SolverLimits::default()
+    };
+    match solve_with_limits(cnf, limits) {
+        LimitedResult::Sat(model) => Some(Some(model)),
+        LimitedResult::Unsat => Some(None),
+        LimitedResult::Stopped(_) => None,
+    }
+}
+
+/// Solves `cnf`, giving up with `Timeout` once `limit` has passed. `Ok(None)`
+/// means the formula is unsatisfiable.
+#[cfg(not(feature = "no_std"))]
+pub fn solve_with_timeout(cnf: CNF, limit: Duration) -> Result<Option<Assignment>, Timeout> {
+    let limits = SolverLimits {
+        max_time: Some(limit),
+        ..SolverLimits::default()
+    };
+    match solve_with_limits(cnf, limits) {
+        LimitedResult::Sat(model) => Ok(Some(model)),
+        LimitedResult::Unsat => Ok(None),
+        LimitedResult::Stopped(_) => Err(Timeout),
+    }
+}
+
+/// Solves `cnf` like `solve` and also returns the wall-clock time taken,
+/// from the call to the verdict. `None` means the formula is
+/// unsatisfiable.
+#[cfg(not(feature = "no_std"))]
+pub fn solve_timed(cnf: CNF) -> (Option<Assignment>, Duration) {
+    let start = Instant::now();
+    let model = match crate::solve(cnf) {
+        SatResult::Sat(model) => Some(model),
+        SatResult::Unsat => None,
+    };
+    (model, start.elapsed())
+}
+
+/// The outcome of `solve_with_limits`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LimitedResult {
+    Sat(Assignment),
+    Unsat,
+    /// The limit was reached before a verdict.
+    Stopped(Limit),
+}
+
+/// Solves `cnf` within `limits`, stopping at whichever is reached first
+/// and reporting it. `solve_with_budget` and `solve_with_timeout` are the
+/// special cases with a single limit.
+pub fn solve_with_limits(cnf: CNF, limits: SolverLimits) -> LimitedResult {
+    let mut solver = Solver::new(cnf);
+    #[cfg(not(feature = "no_std"))]
+    if let Some(limit) = limits.max_time {
+        solver.set_time_limit(limit);
+    }
+    if let Some(max) = limits.max_conflicts {
+        solver.set_conflict_limit(max);
+    }
+    if let Some(max) = limits.max_propagations {
+        solver.set_propagation_limit(max);
+    }
+    match solver.run() {
+        Status::Sat => LimitedResult::Sat(solver.model()),
+        Status::Unsat => LimitedResult::Unsat,
+        Status::Unknown => {
+            LimitedResult::Stopped(solver.stopped.expect("only a limit stops early"))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Literal, parse_dimacs, parse_with_meta, parse2, solve, verify};
+
+    #[test]
+    fn test_solver_formulas() {
+        let sat = [
+            "{a}",
+            "{a},{b}",
+            "{a,b}",
+            "{-a,b}",
+            "{a,b},{-a,c},{-b,-c}",
+            "{a,b},{b,c},{-a,-b},{-c,d}",
+        ];
+        for formula in sat {
+            let cnf = parse2(formula);
+            let SatResult::Sat(model) = Solver::new(cnf.clone()).solve() else {
+                panic!("{formula} should be satisfiable");
+            };
+            assert!(verify(&cnf, &model), "{formula}");
+        }
+
+        for formula in ["{a},{-a}", "{a,b},{-a,b},{a,-b},{-a,-b}"] {
+            assert_eq!(Solver::new(parse2(formula)).solve(), SatResult::Unsat);
+        }
+    }
+
+    #[test]
+    fn test_deep_search_does_not_overflow() {
+        // every variable needs its own decision, so the search is 5000 deep
+        let cnf: CNF = (0..5000)
+            .map(|i| {
+                vec![
+                    Literal {
+                        name: format!("x{i}"),
+                        negated: false,
+                    },
+                    Literal {
+                        name: format!("x{}", i + 1),
+                        negated: true,
+                    },
+                ]
+            })
+            .collect();
+
+        let SatResult::Sat(model) = Solver::new(cnf.clone()).solve() else {
+            panic!("chain should be satisfiable");
+        };
+        assert!(verify(&cnf, &model));
+    }
+
+    #[test]
+    fn test_core_excludes_unrelated_clauses() {
+        let core = solve_with_core(parse2("{a},{-a},{b,c}")).unwrap_err();
+        assert_eq!(core, parse2("{a},{-a}"));
+    }
+
+    #[test]
+    fn test_core_is_unsatisfiable() {
+        for formula in [
+            "{a,b},{-a,b},{a,-b},{-a,-b},{c,d}",
+            "{c},{a,b},{-c,-a},{-a,b},{-b,e},{-e,-b},{d,-e}",
+            // the second conflict reuses a clause marked by the first one
+            "{-b},{c,b,-a},{a,d},{-d,-c},{c,-d},{-a,d,-c}",
+        ] {
+            let core = solve_with_core(parse2(formula)).unwrap_err();
+            assert!(!core.is_empty());
+            assert_eq!(solve(core), SatResult::Unsat, "{formula}");
+        }
+
+        let core = solve_with_core(vec![vec![], vec![]]).unwrap_err();
+        assert_eq!(core, vec![Clause::new()]);
+
+        assert!(solve_with_core(parse2("{a,b},{-a}")).is_ok());
+    }
+
+    #[test]
+    fn test_entails() {
+        let b = Literal {
+            name: "b".to_string(),
+            negated: false,
+        };
+        assert_eq!(
+            entails(&parse2("{a},{-a,b}"), &b),
+            Entailment::Entailed(parse2("{a},{-a,b}"))
+        );
+        assert_eq!(
+            entails(&parse2("{c},{a},{-a,b},{-c,d},{b,e}"), &b),
+            Entailment::Entailed(parse2("{a},{-a,b}"))
+        );
+        assert_eq!(entails(&parse2("{a,b}"), &b), Entailment::NotEntailed);
+        let not_b = Literal {
+            negated: true,
+            ..b.clone()
+        };
+        assert_eq!(
+            entails(&parse2("{a},{-a,b}"), &not_b),
+            Entailment::NotEntailed
+        );
+        assert_eq!(
+            entails(&parse2("{a},{-a}"), &b),
+            Entailment::Entailed(parse2("{a},{-a}"))
+        );
+    }
+
+    #[test]
+    fn test_redundant_clauses() {
+        assert_eq!(
+            redundant_clauses(&parse2("{a},{-a},{b,c}")),
+            parse2("{b,c}")
+        );
+        // either copy of `{a}` can go, but not both
+        assert_eq!(
+            redundant_clauses(&parse2("{a},{-a,b},{a},{-b}")),
+            parse2("{a},{a}")
+        );
+        assert!(redundant_clauses(&parse2("{a,b},{-a,b},{a,-b},{-a,-b}")).is_empty());
+        assert!(redundant_clauses(&parse2("{a,b},{-a}")).is_empty());
+
+        // `parse2` would drop the repeated literal
+        let a = |negated| Literal {
+            name: "a".to_string(),
+            negated,
+        };
+        let cnf = vec![vec![a(false), a(false)], vec![a(true)]];
+        assert!(redundant_clauses(&cnf).is_empty());
+    }
+
+    #[test]
+    fn test_solve_under_ints() {
+        let mut solver = Solver::new(parse_dimacs("p cnf 2 1\n1 2 0\n").unwrap());
+        let model = solver.solve_under_ints(&[-1]).unwrap();
+        assert!(!model["1"]);
+        assert!(model["2"]);
+        assert!(solver.solve_under_ints(&[-1, -2]).is_none());
+        assert!(solver.solve_under_ints(&[1]).unwrap()["1"]);
+
+        // ids follow the order of first occurrence
+        let mut solver = Solver::new(parse2("{b},{a,b}"));
+        assert!(!solver.solve_under_ints(&[-2]).unwrap()["a"]);
+    }
+
+    #[test]
+    #[should_panic(expected = "no variable has the DIMACS id 3")]
+    fn test_solve_under_ints_unknown_id() {
+        Solver::new(parse2("{a,b}")).solve_under_ints(&[3]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_and_load_state() {
+        let config = SolverConfig {
+            rand_polarity: Some(3),
+            restarts: RestartPolicy::Geometric(1.5),
+            branch_priority: [("1".to_string(), 2.0)].into_iter().collect(),
+            ..SolverConfig::default()
+        };
+        let mut formulas = vec![pigeonhole(7, 6)];
+        formulas.extend((0..10).map(|seed| crate::random_3sat(60, 250, seed)));
+        for cnf in formulas {
+            let mut solver = Solver::with_config(cnf.clone(), config.clone());
+            solver.set_conflict_limit(50);
+            if solver.run() != Status::Unknown {
+                continue; // solved before it could be interrupted
+            }
+            let mut loaded = Solver::load_state(&solver.save_state()).unwrap();
+            assert_eq!(loaded.stats, solver.stats);
+
+            solver.set_conflict_limit(u64::MAX);
+            let expected = solver.solve();
+            assert_eq!(loaded.solve(), expected);
+            assert_eq!(loaded.stats, solver.stats);
+            if let SatResult::Sat(model) = expected {
+                assert!(verify(&cnf, &model));
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_save_state_with_core_and_proof() {
+        let mut solver = Solver::new(pigeonhole(6, 5));
+        solver.track_core();
+        solver.track_proof();
+        solver.set_conflict_limit(20);
+        assert_eq!(solver.run(), Status::Unknown);
+        let mut loaded = Solver::load_state(&solver.save_state()).unwrap();
+
+        solver.set_conflict_limit(u64::MAX);
+        assert_eq!(solver.solve(), SatResult::Unsat);
+        assert_eq!(loaded.solve(), SatResult::Unsat);
+        assert_eq!(loaded.core(), solver.core());
+        assert_eq!(loaded.proof, solver.proof);
+
+        let cnf = crate::random_3sat(30, 100, 1);
+        let names: Vec<String> = (1..6).map(|i| i.to_string()).collect();
+        let mut solver = Solver::new(cnf);
+        solver.add_xors(&[parity(&names, true)]);
+        let mut loaded = Solver::load_state(&solver.save_state()).unwrap();
+        assert_eq!(loaded.solve(), solver.solve());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_load_state_errors() {
+        let mut solver = Solver::new(parse2("{a,b},{-a,b},{c}"));
+        solver.solve_under(&[]);
+        let state = solver.save_state();
+        assert!(Solver::load_state(&state).is_ok());
+
+        assert_eq!(
+            Solver::load_state(b"p cnf 1 1").err(),
+            Some(SolverError::UnknownFormat)
+        );
+        assert_eq!(
+            Solver::load_state(&state[..state.len() - 1]).err(),
+            Some(SolverError::Truncated)
+        );
+        let mut longer = state.clone();
+        longer.push(0);
+        assert_eq!(
+            Solver::load_state(&longer).err(),
+            Some(SolverError::Corrupt)
+        );
+
+        // well formed, but watching a clause that does not exist
+        solver.watches[0].push(solver.clauses.len());
+        assert_eq!(
+            Solver::load_state(&solver.save_state()).err(),
+            Some(SolverError::Corrupt)
+        );
+    }
+
+    #[test]
+    fn test_import_dimacs_clauses() {
+        // numbered in order of first occurrence, so DIMACS ids line up
+        let (cnf, _) = crate::renumber(pigeonhole(6, 5));
+        let vars = VarMap::from_cnf(&cnf);
+        let mut first = Solver::new(cnf.clone());
+        assert!(first.solve_under(&[]).is_none());
+        let mut export = String::from("c learned by another solver\n");
+        for clause in first.learned_clauses() {
+            crate::dimacs::write_clause(&mut export, &clause, &vars);
+        }
+
+        let mut second = Solver::new(cnf);
+        let count = second.import_dimacs_clauses(&export).unwrap();
+        assert_eq!(count, first.learned_clauses().len());
+        assert_eq!(second.stats.learned_clauses, count);
+        assert!(second.solve_under(&[]).is_none());
+        assert!(second.stats.conflicts < first.stats.conflicts);
+
+        // a final `0` is optional and unknown numbers become variables
+        let mut solver = Solver::new(parse2("{a,b}"));
+        assert_eq!(
+            solver.import_dimacs_clauses("-1 0 1 -1 0\n-2 3").unwrap(),
+            2
+        );
+        let model = solver.solve_under(&[]).unwrap();
+        assert!(!model["a"] && model["b"] && model["3"]);
+        assert!(matches!(
+            solver.import_dimacs_clauses("1 x 0"),
+            Err(ParseError::InvalidLiteral { .. })
+        ));
+        assert!(matches!(
+            solver.import_dimacs_clauses("5000000000 0\n"),
+            Err(ParseError::InvalidLiteral { .. })
+        ));
+
+        // only the next number adds a variable, and nothing is added when
+        // a later one skips ahead
+        assert_eq!(
+            solver.import_dimacs_clauses("1 0\n5 0"),
+            Err(ParseError::VariableOutOfRange {
+                line: 2,
+                var: 5,
+                declared: 3
+            })
+        );
+        assert_eq!(solver.learned_clauses().len(), 2);
+        // `4` only occurs in a tautology but still takes its number
+        assert_eq!(solver.import_dimacs_clauses("4 -4 0 5 0").unwrap(), 1);
+        assert_eq!(solver.vars.name(3), Some("4"));
+        assert!(solver.solve_under(&[]).unwrap()["5"]);
+
+        // the only variable is named `2`, so number 2 needs another name
+        let mut solver = Solver::new(parse_dimacs("p cnf 2 1\n2 0\n").unwrap());
+        assert_eq!(solver.import_dimacs_clauses("-1 2 0\n2 3 0").unwrap(), 2);
+        assert_eq!(solver.vars.names().collect::<Vec<_>>(), ["2", "2'", "3"]);
+        assert_eq!(solver.import_dimacs_clauses("-2 4 0").unwrap(), 1);
+        assert_eq!(solver.vars.name(3), Some("4"));
+        let mut last: Vec<String> = solver.learned_clauses()[2]
+            .iter()
+            .map(Literal::to_string)
+            .collect();
+        last.sort();
+        assert_eq!(last, ["-2'", "4"]);
+    }
+
+    #[test]
+    fn test_core_meta() {
+        // lines 3 and 6 play no part in the refutation
+        let input = "{a,b},\n{-a,b},\n{c},\n{a,-b},\n{-b,-a},\n{c,-d}";
+        let (cnf, meta) = parse_with_meta(input).unwrap();
+        let core = solve_with_core_meta(cnf, &meta).unwrap_err();
+        let lines: Vec<usize> = core.iter().map(|meta| meta.line).collect();
+        assert_eq!(lines, [1, 2, 4, 5]);
+
+        let (cnf, meta) = parse_with_meta("{a,b},{-a}").unwrap();
+        assert!(solve_with_core_meta(cnf, &meta).is_ok());
+
+        // a repeated literal, which `parse_with_meta` would drop
+        let (_, meta) = parse_with_meta("{a},\n{-a}").unwrap();
+        let a = |negated| Literal {
+            name: "a".to_string(),
+            negated,
+        };
+        let cnf = vec![vec![a(false), a(false)], vec![a(true)]];
+        let core = solve_with_core_meta(cnf, &meta).unwrap_err();
+        assert_eq!(core, meta);
+    }
+
+    // every pigeon sits in a hole and no two pigeons share one
+    fn pigeonhole(pigeons: usize, holes: usize) -> CNF {
+        let var = |pigeon, hole, negated| Literal {
+            name: format!("p{pigeon}h{hole}"),
+            negated,
+        };
+
+        let mut cnf: CNF = (0..pigeons)
+            .map(|p| (0..holes).map(|h| var(p, h, false)).collect())
+            .collect();
+        for h in 0..holes {
+            for p in 0..pigeons {
+                for q in p + 1..pigeons {
+                    cnf.push(vec![var(p, h, true), var(q, h, true)]);
+                }
+            }
+        }
+        cnf
+    }
+
+    #[cfg(not(feature = "no_std"))]
+    #[test]
+    fn test_timeout() {
+        let start = Instant::now();
+        let result = solve_with_timeout(pigeonhole(11, 10), Duration::from_millis(100));
+        assert_eq!(result, Err(Timeout));
+        assert!(start.elapsed() < Duration::from_secs(5));
+
+        let limit = Duration::from_secs(10);
+        assert!(matches!(
+            solve_with_timeout(parse2("{a,b},{-a}"), limit),
+            Ok(Some(_))
+        ));
+        assert_eq!(solve_with_timeout(parse2("{a},{-a}"), limit), Ok(None));
+    }
+
+    #[test]
+    fn test_observer() {
+        #[derive(Default)]
+        struct Counts {
+            decisions: Vec<(String, bool)>,
+            conflicts: u64,
+            restarts: u64,
+        }
+        impl SolverObserver for Counts {
+            fn on_decision(&mut self, var: &str, value: bool) {
+                self.decisions.push((var.to_string(), value));
+            }
+            fn on_conflict(&mut self) {
+                self.conflicts += 1;
+            }
+            fn on_restart(&mut self) {
+                self.restarts += 1;
+            }
+        }
+
+        let mut counts = Counts::default();
+        let result = solve_with_observer(pigeonhole(5, 4), &mut counts);
+        assert_eq!(result, SatResult::Unsat);
+        let (_, stats) = solve_with_stats(pigeonhole(5, 4));
+        assert_eq!(counts.decisions.len() as u64, stats.decisions);
+        assert_eq!(counts.conflicts, stats.conflicts);
+        assert_eq!(counts.restarts, stats.restarts);
+
+        let mut counts = Counts::default();
+        solve_with_observer(parse2("{a,b},{-a,c}"), &mut counts);
+        assert_eq!(
+            counts.decisions,
+            [("a".to_string(), true), ("b".to_string(), true)]
+        );
+        assert_eq!(counts.conflicts, 0);
+        assert_eq!(
+            solve_with_observer(parse2("{a},{-a}"), &mut ()),
+            SatResult::Unsat
+        );
+    }
+
+    #[test]
+    fn test_conflict_budget() {
+        assert_eq!(solve_with_budget(pigeonhole(7, 6), 10), None);
+        assert_eq!(solve_with_budget(pigeonhole(4, 3), 100_000), Some(None));
+        // no search needed, so no budget either
+        assert_eq!(solve_with_budget(parse2("{a},{-a}"), 0), Some(None));
+        let model = solve_with_budget(parse2("{a,b},{-a}"), 0).unwrap().unwrap();
+        assert_eq!((model["a"], model["b"]), (false, true));
+
+        // the budget is counted the same way every run
+        let (_, stats) = solve_with_stats(pigeonhole(5, 4));
+        assert_eq!(
+            solve_with_budget(pigeonhole(5, 4), stats.conflicts - 2),
+            None
+        );
+        assert_eq!(
+            solve_with_budget(pigeonhole(5, 4), stats.conflicts),
+            Some(None)
+        );
+    }
+
+    #[cfg(not(feature = "no_std"))]
+    #[test]
+    fn test_solve_timed() {
+        let (model, elapsed) = solve_timed(pigeonhole(6, 5));
+        assert_eq!(model, None);
+        assert!(elapsed > Duration::ZERO);
+
+        for seed in 0..10 {
+            let cnf = crate::random_3sat(30, 128, seed);
+            let (model, _) = solve_timed(cnf.clone());
+            match solve(cnf.clone()) {
+                SatResult::Sat(_) => assert!(verify(&cnf, &model.unwrap())),
+                SatResult::Unsat => assert_eq!(model, None),
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_with_limits() {
+        let conflicts = |max| SolverLimits {
+            max_conflicts: Some(max),
+            ..SolverLimits::default()
+        };
+        for (cnf, max) in [
+            (pigeonhole(7, 6), 10),
+            (pigeonhole(4, 3), 100_000),
+            (parse2("{a},{-a}"), 0),
+            (parse2("{a,b},{-a}"), 0),
+        ] {
+            let expected = match solve_with_budget(cnf.clone(), max) {
+                Some(Some(model)) => LimitedResult::Sat(model),
+                Some(None) => LimitedResult::Unsat,
+                None => LimitedResult::Stopped(Limit::Conflicts),
+            };
+            assert_eq!(solve_with_limits(cnf, conflicts(max)), expected);
+        }
+
+        let propagations = SolverLimits {
+            max_propagations: Some(50),
+            ..SolverLimits::default()
+        };
+        assert_eq!(
+            solve_with_limits(pigeonhole(7, 6), propagations),
+            LimitedResult::Stopped(Limit::Propagations)
+        );
+        // whichever trips first is reported
+        let both = SolverLimits {
+            max_conflicts: Some(1),
+            ..propagations
+        };
+        assert_eq!(
+            solve_with_limits(pigeonhole(7, 6), both),
+            LimitedResult::Stopped(Limit::Conflicts)
+        );
+        assert_eq!(
+            solve_with_limits(pigeonhole(4, 3), SolverLimits::default()),
+            LimitedResult::Unsat
+        );
+    }
+
+    #[test]
+    fn test_learning_skips_unrelated_decisions() {
+        // the unrelated pairs are decided before the pigeons, going back
+        // one decision at a time refutes the pigeons once per combination
+        let pairs: Vec<String> = (0..10).map(|i| format!("{{x{i},y{i}}}")).collect();
+        let mut cnf = parse2(&pairs.join(","));
+        cnf.extend(pigeonhole(6, 5));
+
+        let mut solver = Solver::with_config(
+            cnf,
+            SolverConfig {
+                heuristic: Heuristic::FirstUnassigned,
+                ..SolverConfig::default()
+            },
+        );
+        #[cfg(not(feature = "no_std"))]
+        solver.set_time_limit(Duration::from_secs(10));
+        assert_eq!(solver.run(), Status::Unsat);
+    }
+
+    #[test]
+    fn test_pigeonhole_unsat() {
+        for holes in 1..6 {
+            assert_eq!(solve(pigeonhole(holes + 1, holes)), SatResult::Unsat);
+            assert!(matches!(solve(pigeonhole(holes, holes)), SatResult::Sat(_)));
+        }
+    }
+
+    #[test]
+    fn test_heuristics_agree() {
+        let formulas = [
+            "{a,b},{-a,c},{-b,-c}",
+            "{a,b},{b,c},{-a,-b},{-c,d}",
+            "{a,b},{-a,b},{a,-b},{-a,-b}",
+            "{c},{a,b},{-c,-a},{-a,b},{-b,e},{-e,-b},{d,-e}",
+        ];
+        for formula in formulas {
+            let cnf = parse2(formula);
+            let first = solve_with_config(
+                cnf.clone(),
+                SolverConfig {
+                    heuristic: Heuristic::FirstUnassigned,
+                    ..SolverConfig::default()
+                },
+            );
+            let vsids = solve_with_config(cnf.clone(), SolverConfig::default());
+            let lookahead = solve_with_config(
+                cnf.clone(),
+                SolverConfig {
+                    heuristic: Heuristic::Lookahead,
+                    ..SolverConfig::default()
+                },
+            );
+            assert_eq!(
+                matches!(first, SatResult::Sat(_)),
+                matches!(vsids, SatResult::Sat(_)),
+                "{formula}"
+            );
+            assert_eq!(
+                matches!(lookahead, SatResult::Sat(_)),
+                matches!(vsids, SatResult::Sat(_)),
+                "{formula}"
+            );
+            if let SatResult::Sat(model) = lookahead {
+                assert!(verify(&cnf, &model));
+            }
+            if let SatResult::Sat(model) = vsids {
+                assert!(verify(&cnf, &model));
+            }
+        }
+    }
+
+    #[test]
+    fn test_jeroslow_wang() {
+        let run = |cnf: CNF, heuristic| {
+            let mut solver = Solver::with_config(
+                cnf.clone(),
+                SolverConfig {
+                    heuristic,
+                    ..SolverConfig::default()
+                },
+            );
+            let model = solver.solve_under(&[]);
+            if let Some(model) = &model {
+                assert!(verify(&cnf, model));
+            }
+            (model.is_some(), solver.stats.decisions)
+        };
+
+        // formula 8 of the classic DPLL tests takes a single decision, and
+        // leading with an unrelated clause costs the first unassigned
+        // order a decision per variable in it
+        let formula_8 = parse2("{a,b},{-a,b},{a,-b},{-a,-b}");
+        let (sat, decisions) = run(formula_8.clone(), Heuristic::JeroslowWang);
+        assert!(!sat);
+        assert!(decisions <= run(formula_8.clone(), Heuristic::FirstUnassigned).1);
+        let mut padded = parse2("{p,q,r,s}");
+        padded.extend(formula_8);
+        assert_eq!(run(padded.clone(), Heuristic::JeroslowWang), (false, 1));
+        assert!(run(padded, Heuristic::FirstUnassigned).1 > 1);
+
+        // the literal in the most short clauses is tried first
+        let solver = Solver::new(parse2("{a,b,c},{-b,c},{-b,d},{-b,-c,e}"));
+        assert_eq!(solver.jeroslow_wang(), Some((1, false)));
+
+        for seed in 0..30 {
+            let cnf = crate::random_3sat(20, 85, seed);
+            let (sat, _) = run(cnf.clone(), Heuristic::JeroslowWang);
+            assert_eq!(sat, matches!(solve(cnf), SatResult::Sat(_)), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_mom() {
+        let run = |cnf: CNF, heuristic| {
+            let mut solver = Solver::with_config(
+                cnf.clone(),
+                SolverConfig {
+                    heuristic,
+                    ..SolverConfig::default()
+                },
+            );
+            let model = solver.solve_under(&[]);
+            if let Some(model) = &model {
+                assert!(verify(&cnf, model));
+            }
+            (model.is_some(), solver.stats.decisions)
+        };
+
+        let formula_8 = parse2("{a,b},{-a,b},{a,-b},{-a,-b}");
+        let (sat, decisions) = run(formula_8.clone(), Heuristic::Mom);
+        assert!(!sat);
+        assert!(decisions <= run(formula_8.clone(), Heuristic::FirstUnassigned).1);
+        let mut padded = parse2("{p,q,r,s}");
+        padded.extend(formula_8);
+        assert_eq!(run(padded.clone(), Heuristic::Mom), (false, 1));
+        assert!(run(padded, Heuristic::FirstUnassigned).1 > 1);
+
+        // only the binary clauses count, where -b occurs twice
+        let solver = Solver::new(parse2("{a,b,c},{a,b,d},{-b,c},{-b,d},{a,e}"));
+        assert_eq!(solver.mom(), Some((1, false)));
+
+        for seed in 0..30 {
+            let cnf = crate::random_3sat(20, 85, seed);
+            let (sat, _) = run(cnf.clone(), Heuristic::Mom);
+            assert_eq!(sat, matches!(solve(cnf), SatResult::Sat(_)), "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn test_lookahead() {
+        let config = SolverConfig {
+            heuristic: Heuristic::Lookahead,
+            ..SolverConfig::default()
+        };
+        assert_eq!(
+            solve_with_config(pigeonhole(5, 4), config.clone()),
+            SatResult::Unsat
+        );
+        for seed in 0..30 {
+            let cnf = crate::random_3sat(20, 85, seed);
+            match solve_with_config(cnf.clone(), config.clone()) {
+                SatResult::Sat(model) => assert!(verify(&cnf, &model), "seed {seed}"),
+                SatResult::Unsat => assert_eq!(solve(cnf), SatResult::Unsat, "seed {seed}"),
+            }
+        }
+
+        // `a` forces the most either way
+        let mut solver = Solver::with_config(parse2("{-a,b},{-a,c},{a,d},{d,e}"), config.clone());
+        assert_eq!(solver.lookahead(), Some((0, true)));
+        assert!(solver.trail.is_empty());
+        assert_eq!(solver.stats.propagations, 0);
+
+        // `a` true fails, so `a` is decided false
+        let mut solver = Solver::with_config(parse2("{-a,b},{-a,-b},{a,c,d}"), config.clone());
+        assert_eq!(solver.lookahead(), Some((0, false)));
+        // and the other way round
+        let mut solver = Solver::with_config(parse2("{a,b},{a,-b},{-a,c,d}"), config);
+        assert_eq!(solver.lookahead(), Some((0, true)));
+    }
+
+    #[test]
+    fn test_branch_priority() {
+        #[derive(Default)]
+        struct Decisions(Vec<String>);
+        impl SolverObserver for Decisions {
+            fn on_decision(&mut self, var: &str, _value: bool) {
+                self.0.push(var.to_string());
+            }
+        }
+
+        // nothing is forced, so every variable is decided
+        let cnf = parse2("{a,b},{c,d},{e,f}");
+        for heuristic in [Heuristic::FirstUnassigned, Heuristic::Vsids] {
+            let decisions = |branch_priority: &[(&str, f64)]| {
+                let config = SolverConfig {
+                    heuristic,
+                    branch_priority: branch_priority
+                        .iter()
+                        .map(|&(name, priority)| (name.to_string(), priority))
+                        .collect(),
+                    ..SolverConfig::default()
+                };
+                let mut observer = Decisions::default();
+                Solver::with_config(cnf.clone(), config).run_observed(&mut observer);
+                observer.0
+            };
+            assert_eq!(decisions(&[]), ["a", "b", "c", "d", "e", "f"]);
+            assert_eq!(
+                decisions(&[("e", 2.0), ("c", 1.0)]),
+                ["e", "c", "a", "b", "d", "f"]
+            );
+            assert_eq!(decisions(&[("a", -1.0)]), ["b", "c", "d", "e", "f", "a"]);
+        }
+    }
+
+    #[test]
+    fn test_vsids_decisions() {
+        let decisions = |heuristic, cnf: CNF| {
+            let config = SolverConfig {
+                heuristic,
+                ..SolverConfig::default()
+            };
+            let mut solver = Solver::with_config(cnf, config);
+            solver.run();
+            solver.stats.decisions
+        };
+
+        let formula_8 = parse2("{a,b},{-a,b},{a,-b},{-a,-b}");
+        assert!(
+            decisions(Heuristic::Vsids, formula_8.clone())
+                <= decisions(Heuristic::FirstUnassigned, formula_8)
+        );
+
+        // the conflicts sit on `c`, `d` and `e`, after the first one VSIDS
+        // moves there right away instead of walking through the
+        // unconstrained variables again
+        let mut cnf =
+            parse2("{c,d,e},{c,d,-e},{c,-d,e},{c,-d,-e},{-c,d,e},{-c,d,-e},{-c,-d,e},{-c,-d,-e}");
+        cnf.splice(0..0, parse2("{x1,y1},{x2,y2},{x3,y3},{x4,y4}"));
+        assert!(
+            decisions(Heuristic::Vsids, cnf.clone()) < decisions(Heuristic::FirstUnassigned, cnf)
+        );
+    }
+
+    #[test]
+    fn test_stats() {
+        // deciding `a` forces `b` and `c`, which conflicts. the learned `-b`
+        // then forces `-a` and `d`, and `c` is left to a second decision
+        let (model, stats) = solve_with_stats(parse2("{-a,b},{-b,c},{-b,-c},{a,d}"));
+        assert!(model.is_some());
+        assert_eq!(
+            stats,
+            Stats {
+                decisions: 2,
+                propagations: 5,
+                conflicts: 1,
+                restarts: 0,
+                max_depth: 1,
+                learned_clauses: 1,
+                deleted_clauses: 0,
+                learned_literals: 1,
+            }
+        );
+
+        let (model, stats) = solve_with_stats(parse2("{a},{-a,b}"));
+        assert!(model.is_some());
+        assert_eq!(
+            stats,
+            Stats {
+                propagations: 2,
+                ..Stats::default()
+            }
+        );
+
+        let (model, stats) = solve_with_stats(pigeonhole(5, 4));
+        assert_eq!(model, None);
+        assert!(stats.conflicts > 0 && stats.decisions > 0);
+    }
+
+    #[test]
+    fn test_incremental_solving() {
+        let literal = |name: &str, negated| Literal {
+            name: name.to_string(),
+            negated,
+        };
+
+        let mut solver = Solver::new(parse2("{a,b},{-a,c}"));
+        let model = solver.solve_under(&[literal("a", false)]).unwrap();
+        assert_eq!((model["a"], model["c"]), (true, true));
+
+        // assumptions do not outlive their solve
+        assert!(solver.solve_under(&[literal("c", true)]).is_some());
+        assert!(
+            solver
+                .solve_under(&[literal("a", false), literal("c", true)])
+                .is_none()
+        );
+        assert!(solver.solve_under(&[]).is_some());
+
+        // an added clause stays, and may mention new variables
+        solver.add_clause(parse2("{-b,d}").remove(0));
+        solver.add_clause(parse2("{-d}").remove(0));
+        let model = solver.solve_under(&[]).unwrap();
+        assert_eq!((model["a"], model["b"], model["d"]), (true, false, false));
+        assert!(solver.solve_under(&[literal("b", false)]).is_none());
+        assert!(solver.solve_under(&[literal("e", true)]).is_some());
+
+        solver.add_clause(parse2("{-a}").remove(0));
+        assert!(solver.solve_under(&[]).is_none());
+        assert!(solver.solve_under(&[literal("d", true)]).is_none());
+    }
+
+    #[test]
+    fn test_explain() {
+        let mut solver = Solver::new(parse2("{a},{-a,b}"));
+        assert!(solver.solve_under(&[]).is_some());
+        assert_eq!(solver.explain("a"), Some(Reason::Propagated(0)));
+        assert_eq!(solver.explain("b"), Some(Reason::Propagated(1)));
+        assert_eq!(solver.explain("c"), None);
+
+        let mut solver = Solver::new(parse2("{a,b}"));
+        let literal = Literal {
+            name: "b".to_string(),
+            negated: false,
+        };
+        assert!(solver.solve_under(&[literal]).is_some());
+        assert_eq!(solver.explain("b"), Some(Reason::Decision));
+    }
+
+    #[test]
+    fn test_add_cnf() {
+        let mut solver = Solver::new(CNF::new());
+        solver.add_cnf(parse2("{a}"));
+        assert!(solver.solve_under(&[]).unwrap()["a"]);
+        solver.add_cnf(parse2("{-a}"));
+        assert!(solver.solve_under(&[]).is_none());
+
+        let mut solver = Solver::new(parse2("{a,b}"));
+        assert!(solver.solve_under(&[]).is_some());
+        solver.add_cnf(parse2("{-a,c},{-c},{b,a}"));
+        assert_eq!(solver.vars.names().collect::<Vec<_>>(), ["a", "b", "c"]);
+        let model = solver.solve_under(&[]).unwrap();
+        assert_eq!((model["a"], model["b"], model["c"]), (false, true, false));
+    }
+
+    #[test]
+    fn test_failed_assumptions() {
+        let literal = |name: &str, negated| Literal {
+            name: name.to_string(),
+            negated,
+        };
+
+        // `a` and `b` force `c`, which contradicts `-c`. `d` is innocent.
+        let mut solver = Solver::new(parse2("{-a,-b,c},{d,e}"));
+        let assumptions = [
+            literal("a", false),
+            literal("d", false),
+            literal("b", false),
+            literal("c", true),
+        ];
+        assert!(solver.solve_under(&assumptions).is_none());
+        let mut failed = solver.failed_assumptions();
+        failed.sort_by(|x, y| x.name.cmp(&y.name));
+        assert_eq!(
+            failed,
+            [literal("a", false), literal("b", false), literal("c", true)]
+        );
+
+        // contradicting assumptions, and one the formula refutes alone
+        assert!(
+            solver
+                .solve_under(&[literal("e", false), literal("e", true)])
+                .is_none()
+        );
+        assert_eq!(solver.failed_assumptions().len(), 2);
+        solver.add_clause(parse2("{-e}").remove(0));
+        assert!(
+            solver
+                .solve_under(&[literal("a", false), literal("e", false)])
+                .is_none()
+        );
+        assert_eq!(solver.failed_assumptions(), [literal("e", false)]);
+
+        assert!(solver.solve_under(&[literal("a", false)]).is_some());
+        assert!(solver.failed_assumptions().is_empty());
+        let mut solver = Solver::new(parse2("{a},{-a}"));
+        assert!(solver.solve_under(&[literal("b", false)]).is_none());
+        assert!(solver.failed_assumptions().is_empty());
+    }
+
+    #[test]
+    fn test_added_clause_watches_unassigned_literals() {
+        // after the first solve `a` and `b` are false at level 0, the added
+        // clause has to be propagated on `c` rather than watch them
+        let mut solver = Solver::new(parse2("{-a},{-b}"));
+        assert!(solver.solve_under(&[]).is_some());
+        solver.add_clause(parse2("{a,b,c}").remove(0));
+        solver.add_clause(parse2("{-c,a,b,d}").remove(0));
+        let model = solver.solve_under(&[]).unwrap();
+        assert!(model["c"] && model["d"]);
+
+        solver.add_clause(parse2("{a,b,-d}").remove(0));
+        assert!(solver.solve_under(&[]).is_none());
+    }
+
+    #[test]
+    fn test_rand_polarity() {
+        // without conflicts the model is the first guess for every variable
+        let clauses: Vec<String> = (0..64).map(|i| format!("{{x{i},y{i}}}")).collect();
+        let cnf = parse2(&clauses.join(","));
+        let run = |rand_polarity, phase_saving| {
+            let config = SolverConfig {
+                rand_polarity,
+                phase_saving,
+                ..SolverConfig::default()
+            };
+            let SatResult::Sat(model) = solve_with_config(cnf.clone(), config) else {
+                panic!("every clause can be satisfied");
+            };
+            assert!(verify(&cnf, &model));
+            model
+        };
+
+        for phase_saving in [true, false] {
+            assert!(run(None, phase_saving).values().all(|&value| value));
+            let random = run(Some(7), phase_saving);
+            assert_eq!(random, run(Some(7), phase_saving));
+            assert!(random.values().any(|&value| !value));
+        }
+        assert_ne!(run(Some(7), true), run(Some(8), true));
+    }
+
+    #[test]
+    fn test_learned_clauses() {
+        let cnf = pigeonhole(5, 4);
+        let mut solver = Solver::new(cnf.clone());
+        assert!(solver.learned_clauses().is_empty());
+        assert!(solver.solve_under(&[]).is_none());
+
+        let learned = solver.learned_clauses();
+        assert!(!learned.is_empty());
+        assert_eq!(learned.len(), solver.stats.learned_clauses);
+        for clause in learned {
+            // the formula with the clause false is unsatisfiable
+            let mut refuted = cnf.clone();
+            refuted.extend(clause.iter().map(|literal| {
+                vec![Literal {
+                    name: literal.name.clone(),
+                    negated: !literal.negated,
+                }]
+            }));
+            assert_eq!(
+                solve(refuted),
+                SatResult::Unsat,
+                "{}",
+                format_cnf(&[clause])
+            );
+        }
+
+        // forgotten clauses are gone
+        let config = SolverConfig {
+            clause_deletion: ClauseDeletion::Lbd { limit: 10 },
+            ..SolverConfig::default()
+        };
+        let mut solver = Solver::with_config(pigeonhole(6, 5), config);
+        assert!(solver.solve_under(&[]).is_none());
+        assert!(solver.stats.deleted_clauses > 0);
+        assert_eq!(solver.learned_clauses().len(), solver.stats.learned_clauses);
+    }
+
+    #[test]
+    fn test_add_retractable() {
+        let mut solver = Solver::new(parse2("{a,b}"));
+        let not_a = solver.add_retractable(parse2("{-a}").remove(0));
+        let not_b = solver.add_retractable(parse2("{-b}").remove(0));
+        assert_ne!(not_a, not_b);
+
+        let model = solver.solve_under(std::slice::from_ref(&not_a)).unwrap();
+        assert!(!model["a"] && model["b"]);
+        let model = solver.solve_under(std::slice::from_ref(&not_b)).unwrap();
+        assert!(model["a"] && !model["b"]);
+        assert!(
+            solver
+                .solve_under(&[not_a.clone(), not_b.clone()])
+                .is_none()
+        );
+
+        // disabled, or not mentioned at all, the clause does not count
+        let disabled = Literal {
+            negated: true,
+            ..not_b.clone()
+        };
+        assert!(solver.solve_under(&[not_a.clone(), disabled]).is_some());
+        assert!(solver.solve_under(&[]).is_some());
+
+        solver.add_clause(vec![not_a.clone()]);
+        assert!(solver.solve_under(std::slice::from_ref(&not_b)).is_none());
+    }
+
+    #[test]
+    fn test_phase_saving() {
+        let not_x = Literal {
+            name: "x".to_string(),
+            negated: true,
+        };
+        for phase_saving in [true, false] {
+            let config = SolverConfig {
+                phase_saving,
+                ..SolverConfig::default()
+            };
+            let mut solver = Solver::with_config(parse2("{x,y}"), config);
+            solver.solve_under(std::slice::from_ref(&not_x)).unwrap();
+
+            // `x` is decided again, with the value the assumption gave it
+            let model = solver.solve_under(&[]).unwrap();
+            assert_eq!(model["x"], !phase_saving);
+        }
+    }
+
+    fn parity(vars: &[String], parity: bool) -> XorClause {
+        XorClause {
+            vars: vars.to_vec(),
+            parity,
+        }
+    }
+
+    fn xor_holds(model: &Assignment, xor: &XorClause) -> bool {
+        xor.vars.iter().filter(|&name| model[name]).count() % 2 == xor.parity as usize
+    }
+
+    #[test]
+    fn test_xor_with_clauses() {
+        let names = |names: &str| names.split(',').map(str::to_string).collect::<Vec<_>>();
+
+        let SatResult::Sat(model) =
+            solve_with_xor(parse2("{a,b}"), vec![parity(&names("a,b"), false)])
+        else {
+            panic!("expected a model");
+        };
+        assert!(model["a"] && model["b"]);
+
+        let result = solve_with_xor(parse2("{-a},{-b}"), vec![parity(&names("a,b"), true)]);
+        assert_eq!(result, SatResult::Unsat);
+
+        // variables may occur only in the parity constraints
+        let xors = vec![parity(&names("a,c"), true), parity(&names("c,d"), true)];
+        let SatResult::Sat(model) = solve_with_xor(parse2("{a}"), xors.clone()) else {
+            panic!("expected a model");
+        };
+        assert!(xors.iter().all(|xor| xor_holds(&model, xor)));
+        assert!(!model["c"] && model["d"]);
+    }
+
+    #[test]
+    fn test_xor_cycle_is_unsat() {
+        // around an odd cycle every neighbour differs, which needs an even
+        // number of changes
+        let n = 101;
+        let xors = (0..n)
+            .map(|i| parity(&[format!("x{i}"), format!("x{}", (i + 1) % n)], true))
+            .collect();
+        assert_eq!(solve_with_xor(CNF::new(), xors), SatResult::Unsat);
+    }
+
+    #[test]
+    fn test_random_xor_system() {
+        let mut seed: u64 = 0x9e3779b97f4a7c15;
+        let mut next = move || {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed
+        };
+
+        // constraints that a hidden assignment satisfies
+        let hidden: Vec<bool> = (0..40).map(|_| next() % 2 == 0).collect();
+        let mut xors: Vec<XorClause> = (0..36)
+            .map(|_| {
+                let vars: Vec<usize> = (0..4).map(|_| (next() % 40) as usize).collect();
+                let value = vars.iter().filter(|&&var| hidden[var]).count() % 2 == 1;
+                parity(
+                    &vars.iter().map(|var| format!("v{var}")).collect::<Vec<_>>(),
+                    value,
+                )
+            })
+            .collect();
+
+        let SatResult::Sat(model) = solve_with_xor(CNF::new(), xors.clone()) else {
+            panic!("the hidden assignment satisfies every constraint");
+        };
+        assert!(xors.iter().all(|xor| xor_holds(&model, xor)));
+
+        // the sum of two constraints with the wrong parity
+        let mut vars = xors[0].vars.clone();
+        vars.extend(xors[1].vars.clone());
+        xors.push(parity(&vars, xors[0].parity == xors[1].parity));
+        assert_eq!(solve_with_xor(CNF::new(), xors), SatResult::Unsat);
+    }
+
+    // adding each clause of `proof` to `cnf` in turn, assuming it false and
+    // unit propagating always ends in a conflict
+    fn is_rup_proof(cnf: &CNF, proof: &[ProofStep]) -> bool {
+        let sorted = |clause: &Clause| {
+            let mut names: Vec<String> = clause.iter().map(Literal::to_string).collect();
+            names.sort();
+            names
+        };
+        let mut clauses = cnf.clone();
+        for step in proof {
+            let clause = match step {
+                ProofStep::Add(clause) => clause,
+                ProofStep::Delete(clause) => {
+                    let position = clauses.iter().position(|c| sorted(c) == sorted(clause));
+                    clauses.remove(position.expect("only clauses in the formula are deleted"));
+                    continue;
+                }
+            };
+            let mut assignment: Assignment = clause
+                .iter()
+                .map(|literal| (literal.name.clone(), literal.negated))
+                .collect();
+            if crate::dpll::simplify(clauses.clone(), &assignment)
+                .and_then(|rest| crate::dpll::unit_propagate(rest, &mut assignment))
+                .is_some()
+            {
+                return false;
+            }
+            clauses.push(clause.clone());
+        }
+        proof.last() == Some(&ProofStep::Add(Clause::new()))
+    }
+
+    #[test]
+    fn test_proof() {
+        for cnf in [
+            parse2("{a,b},{-a,b},{a,-b},{-a,-b}"),
+            parse2("{a},{-a}"),
+            vec![vec![]],
+            pigeonhole(5, 4),
+        ] {
+            let proof = solve_with_proof(cnf.clone()).unwrap_err();
+            assert!(is_rup_proof(&cnf, &proof), "{cnf:?}");
+        }
+        assert!(solve_with_proof(parse2("{a,b},{-a}")).is_ok());
+    }
+
+    #[test]
+    fn test_luby() {
+        let terms: Vec<u64> = (1..=15).map(luby).collect();
+        assert_eq!(terms, [1, 1, 2, 1, 1, 2, 4, 1, 1, 2, 1, 1, 2, 4, 8]);
+    }
+
+    #[test]
+    fn test_restart_policies() {
+        let restarts = |restarts, cnf: CNF| {
+            let config = SolverConfig {
+                restarts,
+                ..SolverConfig::default()
+            };
+            let mut solver = Solver::with_config(cnf, config);
+            let status = solver.run();
+            (status, solver.stats.restarts)
+        };
+
+        for policy in [RestartPolicy::Luby(1), RestartPolicy::Geometric(1.5)] {
+            let (status, count) = restarts(policy, pigeonhole(6, 5));
+            assert_eq!(status, Status::Unsat);
+            assert!(count > 0, "{policy:?}");
+        }
+
+        assert_eq!(
+            restarts(RestartPolicy::None, pigeonhole(6, 5)),
+            (Status::Unsat, 0)
+        );
+    }
+
+    #[test]
+    fn test_restart_blocking() {
+        let run = |restart_blocking, cnf: CNF| {
+            let config = SolverConfig {
+                restarts: RestartPolicy::Luby(1),
+                restart_blocking,
+                ..SolverConfig::default()
+            };
+            let mut solver = Solver::with_config(cnf.clone(), config);
+            match solver.solve() {
+                SatResult::Sat(model) => verify(&cnf, &model),
+                SatResult::Unsat => false,
+            }
+        };
+
+        for seed in 0..30 {
+            let cnf = crate::random_3sat(40, 170, seed);
+            let sat = matches!(solve(cnf.clone()), SatResult::Sat(_));
+            for window in [1, 5, 50] {
+                assert_eq!(run(Some(window), cnf.clone()), sat, "seed {seed}");
+            }
+        }
+
+        assert!(!run(Some(1), pigeonhole(6, 5)));
+
+        // two conflicts averaged 2 assignments, so 5 blocks and 3 does not
+        let config = SolverConfig {
+            restart_blocking: Some(2),
+            ..SolverConfig::default()
+        };
+        let mut solver = Solver::with_config(parse2("{a,b,c,d,e}"), config);
+        solver.trail_sizes = VecDeque::from([2, 2]);
+        solver.trail_sum = 4;
+        solver.trail = (0..5).collect();
+        solver.conflicts_since_restart = 7;
+        solver.block_restart();
+        assert_eq!(solver.conflicts_since_restart, 0);
+        assert_eq!((solver.trail_sizes.len(), solver.trail_sum), (2, 7));
+
+        solver.trail.truncate(3);
+        solver.conflicts_since_restart = 7;
+        solver.block_restart();
+        assert_eq!(solver.conflicts_since_restart, 7);
+    }
+
+    #[test]
+    #[should_panic(expected = "not in both watch lists")]
+    fn test_check_watches_catches_lost_watch() {
+        let mut solver = Solver::new(parse2("{a,b,c},{-a,b}"));
+        solver.watches[solver.clauses[0][1].index()].clear();
+        solver.check_watches();
+    }
+
+    #[test]
+    fn test_watches_move_past_false_literals() {
+        let mut solver = Solver::new(parse2("{a,b,c,d},{-d,e}"));
+        for var in 0..3 {
+            solver.enqueue(Lit::new(var, true), None);
+            assert_eq!(solver.propagate(), None);
+        }
+        // only `d` is left in the first clause, which in turn forces `e`
+        assert_eq!(solver.values[3], Some(true));
+        assert_eq!(solver.values[4], Some(true));
+
+        solver.backtrack(0);
+        solver.enqueue(Lit::new(3, false), None);
+        solver.enqueue(Lit::new(4, true), None);
+        assert_eq!(solver.propagate(), Some(1));
+    }
+
+    #[test]
+    fn test_binary_clauses_are_not_watched() {
+        let mut solver = Solver::new(parse2("{-a,b},{-b,c},{-c,-a}"));
+        assert!(solver.watches.iter().all(Vec::is_empty));
+        assert_eq!(solver.binaries.iter().map(Vec::len).sum::<usize>(), 6);
+        solver.check_watches();
+
+        // `a` forces `b` and `-c` through the lists, which falsify the
+        // second clause
+        solver.enqueue(Lit::new(0, false), None);
+        assert_eq!(solver.propagate(), Some(1));
+        assert_eq!(solver.values[1], Some(true));
+        assert_eq!(solver.values[2], Some(false));
+    }
+
+    #[test]
+    fn test_backtrack_restores_values() {
+        let mut solver = Solver::new(parse2("{-a,b},{-b,c}"));
+        solver.enqueue(Lit::new(0, false), None);
+        assert_eq!(solver.propagate(), None);
+        assert_eq!(solver.values, vec![Some(true), Some(true), Some(true)]);
+
+        solver.backtrack(0);
+        assert_eq!(solver.values, vec![None, None, None]);
+        assert!(solver.trail.is_empty());
+        assert_eq!(solver.queue_head, 0);
+    }
+
+    #[test]
+    fn test_tautologies_are_dropped() {
+        let mut solver = Solver::new(parse2("{a,-a,b},{c}"));
+        assert_eq!(solver.clauses.len(), 1);
+        let SatResult::Sat(model) = solver.solve() else {
+            panic!("expected a model");
+        };
+        assert_eq!(model.len(), 3);
+        assert_eq!(model.get("c"), Some(&true));
+    }
+
+    #[test]
+    fn test_subsumption() {
+        let cnf = parse2("{a},{a,b},{a,b,c},{-a,c}");
+        let config = SolverConfig {
+            subsumption: true,
+            ..SolverConfig::default()
+        };
+        assert_eq!(
+            Solver::with_config(cnf.clone(), config.clone())
+                .clauses
+                .len(),
+            2
+        );
+        assert_eq!(Solver::new(cnf.clone()).clauses.len(), 4);
+
+        let SatResult::Sat(model) = solve_with_config(cnf.clone(), config) else {
+            panic!("expected a model");
+        };
+        assert!(verify(&cnf, &model));
+        assert_eq!(model.len(), 3);
+    }
+
+    #[test]
+    fn test_inprocessing_keeps_verdicts() {
+        let config = SolverConfig {
+            restarts: RestartPolicy::Luby(1),
+            inprocessing: Some(1),
+            ..SolverConfig::default()
+        };
+        let mut formulas: Vec<CNF> = [
+            "{a}",
+            "{a},{b}",
+            "{a,b}",
+            "{-a,b}",
+            "{a},{-a}",
+            "{a,b},{-a,c},{-b,-c}",
+            "{a,b},{b,c},{-a,-b},{-c,d}",
+            "{a,b},{-a,b},{a,-b},{-a,-b}",
+        ]
+        .into_iter()
+        .map(parse2)
+        .collect();
+        formulas.push(pigeonhole(5, 4));
+        formulas.extend((0..40).map(|seed| crate::random_3sat(30, 128, seed)));
+        for cnf in formulas {
+            let mut solver = Solver::with_config(cnf.clone(), config.clone());
+            match (solver.solve(), solve(cnf.clone())) {
+                (SatResult::Sat(model), SatResult::Sat(_)) => {
+                    assert!(verify(&cnf, &model), "{}", format_cnf(&cnf))
+                }
+                (SatResult::Unsat, SatResult::Unsat) => {}
+                _ => panic!("verdicts differ on {}", format_cnf(&cnf)),
+            }
+        }
+    }
+
+    #[test]
+    fn test_inprocessing_shrinks_clauses() {
+        let input_clauses = |solver: &Solver| {
+            let kept = solver.clauses.iter().zip(&solver.lbds);
+            kept.filter(|(clause, lbd)| !clause.is_empty() && lbd.is_none())
+                .count()
+        };
+        let cnf = crate::random_3sat(150, 600, 1);
+        let run = |inprocessing| {
+            let config = SolverConfig {
+                restarts: RestartPolicy::Luby(1),
+                inprocessing,
+                ..SolverConfig::default()
+            };
+            let mut solver = Solver::with_config(cnf.clone(), config);
+            let result = solver.solve();
+            if let SatResult::Sat(model) = &result {
+                assert!(verify(&cnf, model));
+            }
+            (matches!(result, SatResult::Sat(_)), input_clauses(&solver))
+        };
+        let (sat, before) = run(None);
+        let (inprocessed, after) = run(Some(1));
+        assert_eq!(sat, inprocessed);
+        assert_eq!(before, 600);
+        assert!(after < before, "{after} clauses left");
+    }
+
+    #[test]
+    fn test_inprocessing_restores_eliminated_vars() {
+        let config = SolverConfig {
+            restarts: RestartPolicy::Luby(1),
+            inprocessing: Some(1),
+            ..SolverConfig::default()
+        };
+        for seed in 0..5 {
+            let cnf = crate::random_3sat(50, 180, seed);
+            let mut solver = Solver::with_config(cnf.clone(), config.clone());
+            let model = solver.solve_under(&[]).unwrap();
+            assert!(verify(&cnf, &model), "seed {seed}");
+            assert!(!solver.eliminated.is_empty(), "seed {seed}");
+
+            // assuming an eliminated variable either way, and then adding
+            // a clause over it, brings its clauses back
+            let var = solver.eliminated[0].0;
+            let name = solver.vars.name(var).unwrap().to_string();
+            let mut expected = cnf.clone();
+            for negated in [false, true] {
+                let literal = Literal::from((name.as_str(), negated));
+                let mut assumed = cnf.clone();
+                assumed.push(vec![literal.clone()]);
+                let model = solver.solve_under(&[literal]);
+                // others may be eliminated again on the way
+                assert!(!solver.is_eliminated(var));
+                let sat = matches!(solve(assumed.clone()), SatResult::Sat(_));
+                assert_eq!(
+                    model.is_some(),
+                    sat,
+                    "seed {seed}, {name} negated {negated}"
+                );
+                if let Some(model) = model {
+                    assert!(verify(&assumed, &model), "seed {seed}");
+                }
+            }
+
+            let mut solver = Solver::with_config(cnf.clone(), config.clone());
+            solver.solve_under(&[]).unwrap();
+            let var = solver.eliminated.last().unwrap().0;
+            let name = solver.vars.name(var).unwrap().to_string();
+            let clause = vec![Literal::from((name.as_str(), model[&name]))];
+            expected.push(clause.clone());
+            solver.add_clause(clause);
+            let model = solver.solve_under(&[]);
+            assert_eq!(
+                model.is_some(),
+                matches!(solve(expected.clone()), SatResult::Sat(_)),
+                "seed {seed}"
+            );
+            if let Some(model) = model {
+                assert!(verify(&expected, &model), "seed {seed}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_solve_with_fixed() {
+        let cnf = parse2("{a,b},{-a,c},{-b,-c}");
+        let fixed: Assignment = [("c".to_string(), false), ("z".to_string(), true)].into();
+        let model = solve_with_fixed(cnf.clone(), fixed).unwrap();
+        assert!(verify(&cnf, &model));
+        assert_eq!(model.get("c"), Some(&false));
+        assert_eq!(model.get("a"), Some(&false));
+        assert_eq!(model.get("z"), Some(&true));
+
+        // `-b` forces `a` and with it `c`
+        let fixed: Assignment = [("b".to_string(), false), ("c".to_string(), false)].into();
+        assert_eq!(solve_with_fixed(cnf.clone(), fixed), None);
+        assert!(solve_with_fixed(cnf, Assignment::new()).is_some());
+    }
+
+    #[test]
+    fn test_solve_with_oracle() {
+        // the theory forbids `a` and `b` both true
+        let mut calls = 0;
+        let cnf = parse2("{a,c},{b,c},{-c,a}");
+        let result = solve_with_oracle(cnf.clone(), |model| {
+            calls += 1;
+            (model["a"] && model["b"]).then(|| parse2("{-a,-b}"))
+        });
+        let SatResult::Sat(model) = result else {
+            panic!("a and c without b is a model");
+        };
+        assert!(verify(&cnf, &model));
+        assert!(!(model["a"] && model["b"]));
+        assert!(calls >= 1);
+
+        let result = solve_with_oracle(parse2("{a},{b}"), |model| {
+            (model["a"] && model["b"]).then(|| parse2("{-a,-b}"))
+        });
+        assert_eq!(result, SatResult::Unsat);
+        let accepted = solve_with_oracle(parse2("{a}"), |_| None);
+        assert_eq!(accepted, SatResult::Sat([("a".to_string(), true)].into()));
+    }
+
+    #[test]
+    #[should_panic(expected = "without a clause it falsifies")]
+    fn test_solve_with_oracle_must_exclude_the_model() {
+        solve_with_oracle(parse2("{a}"), |_| Some(parse2("{a}")));
+    }
+
+    #[test]
+    fn test_solve_assuming() {
+        let cnf = parse2("{a,b}");
+        let model = solve_assuming(cnf.clone(), &parse2("{-a}")[0]).unwrap();
+        assert!(!model["a"] && model["b"]);
+        assert_eq!(solve_assuming(cnf.clone(), &parse2("{-a,-b}")[0]), None);
+        assert_eq!(solve_assuming(cnf, &parse2("{a,-a}")[0]), None);
+    }
+
+    #[test]
+    fn test_clause_deletion() {
+        let config = |clause_deletion| SolverConfig {
+            clause_deletion,
+            ..SolverConfig::default()
+        };
+
+        let mut solver =
+            Solver::with_config(pigeonhole(6, 5), config(ClauseDeletion::Lbd { limit: 20 }));
+        solver.track_proof();
+        assert_eq!(solver.run(), Status::Unsat);
+        assert!(solver.stats.deleted_clauses > 0);
+        assert!(solver.stats.learned_clauses <= 20);
+        let kept = solver.lbds.iter().filter(|lbd| lbd.is_some()).count();
+        assert_eq!(kept, solver.stats.learned_clauses);
+
+        // forgotten clauses are logged, and the rest still checks
+        let proof = solver.proof.take().unwrap();
+        assert!(
+            proof
+                .iter()
+                .any(|step| matches!(step, ProofStep::Delete(_)))
+        );
+        assert!(is_rup_proof(&pigeonhole(6, 5), &proof));
+
+        let mut solver = Solver::with_config(pigeonhole(6, 5), config(ClauseDeletion::Never));
+        assert_eq!(solver.run(), Status::Unsat);
+        assert_eq!(solver.stats.deleted_clauses, 0);
+        assert_eq!(
+            solver.stats.learned_clauses as u64,
+            solver.stats.conflicts - 1
+        );
+
+        for seed in 0..50 {
+            let cnf = crate::random_3sat(30, 128, seed);
+            let tight = solve_with_config(cnf.clone(), config(ClauseDeletion::Lbd { limit: 4 }));
+            match tight {
+                SatResult::Sat(model) => assert!(verify(&cnf, &model)),
+                SatResult::Unsat => assert_eq!(solve(cnf), SatResult::Unsat),
+            }
+        }
+    }
+
+    #[test]
+    fn test_learned_clause_minimization() {
+        let config = |minimize| SolverConfig {
+            minimize,
+            ..SolverConfig::default()
+        };
+
+        let run = |cnf: CNF, minimize| {
+            let mut solver = Solver::with_config(cnf, config(minimize));
+            solver.track_proof();
+            let status = solver.run();
+            (status, solver.stats, solver.proof.take().unwrap())
+        };
+        let (status, plain, _) = run(pigeonhole(6, 5), false);
+        assert_eq!(status, Status::Unsat);
+        let (status, minimized, proof) = run(pigeonhole(6, 5), true);
+        assert_eq!(status, Status::Unsat);
+        assert!(
+            minimized.average_learned_len() < plain.average_learned_len(),
+            "{} vs {}",
+            minimized.average_learned_len(),
+            plain.average_learned_len()
+        );
+        // shortened clauses still follow by unit propagation
+        assert!(is_rup_proof(&pigeonhole(6, 5), &proof));
+
+        for seed in 0..50 {
+            let cnf = crate::random_3sat(30, 128, seed);
+            let minimized = solve_with_config(cnf.clone(), config(true));
+            let plain = solve_with_config(cnf.clone(), config(false));
+            match minimized {
+                SatResult::Sat(model) => assert!(verify(&cnf, &model)),
+                SatResult::Unsat => assert_eq!(plain, SatResult::Unsat),
+            }
+        }
+    }
+
+    #[test]
+    fn test_refutation() {
+        let refutation = solve_with_refutation(parse2("{a},{-a}")).unwrap_err();
+        assert_eq!(
+            refutation,
+            Refutation::RootConflict {
+                var: "a".to_string(),
+                positive: parse2("{a}").remove(0),
+                negative: parse2("{-a}").remove(0),
+            }
+        );
+        assert_eq!(
+            refutation.to_string(),
+            "variable `a` is forced true by {a} and false by {-a}"
+        );
+
+        // the units make `{c,-a,b}` false, `-c` being forced last
+        let Err(Refutation::RootConflict { var, negative, .. }) =
+            solve_with_refutation(parse2("{a},{c,-a,b},{-b},{-c}"))
+        else {
+            panic!("expected a root conflict");
+        };
+        assert_eq!(var, "c");
+        assert_eq!(negative, parse2("{-c}").remove(0));
+
+        assert_eq!(
+            solve_with_refutation(vec![vec![]]),
+            Err(Refutation::EmptyClause)
+        );
+        assert_eq!(
+            solve_with_refutation(pigeonhole(4, 3)),
+            Err(Refutation::Search)
+        );
+        assert!(solve_with_refutation(parse2("{a},{-a,b}")).is_ok());
+    }
+}
@@ -0,0 +1,61 @@
+use alloc::vec::Vec;
+use bincode::Options;
+use serde::{Serialize, de::DeserializeOwned};
+
+// the first bytes of every saved state, the last one is the version
+const MAGIC: &[u8; 5] = b"SATS\x02";
+
+/// Why `Solver::load_state` rejected a saved state.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverError {
+    /// Not a state written by `Solver::save_state`, or one written by an
+    /// incompatible version.
+    UnknownFormat,
+    /// The state ends early.
+    Truncated,
+    /// The state contradicts itself, such as a clause over a variable that
+    /// does not exist.
+    Corrupt,
+}
+
+impl core::fmt::Display for SolverError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            SolverError::UnknownFormat => write!(f, "not a saved solver state"),
+            SolverError::Truncated => write!(f, "saved solver state ends early"),
+            SolverError::Corrupt => write!(f, "saved solver state is inconsistent"),
+        }
+    }
+}
+
+impl core::error::Error for SolverError {}
+
+// fixed size little endian numbers, so a state reads back the same on any
+// machine, and nothing may follow the value
+fn options() -> impl Options {
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_little_endian()
+        .reject_trailing_bytes()
+}
+
+pub(crate) fn encode(value: &impl Serialize) -> Vec<u8> {
+    let mut bytes = MAGIC.to_vec();
+    options()
+        .serialize_into(&mut bytes, value)
+        .expect("derived impls serialize without errors");
+    bytes
+}
+
+pub(crate) fn decode<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, SolverError> {
+    let payload = bytes
+        .strip_prefix(MAGIC)
+        .ok_or(SolverError::UnknownFormat)?;
+    options()
+        .deserialize(payload)
+        .map_err(|error| match *error {
+            // reading from a slice only fails on its end
+            bincode::ErrorKind::Io(_) => SolverError::Truncated,
+            _ => SolverError::Corrupt,
+        })
+}
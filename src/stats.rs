@@ -0,0 +1,214 @@
+use crate::preprocess::components;
+use crate::{CNF, Set, VarMap};
+use alloc::{collections::BinaryHeap, vec, vec::Vec};
+use core::cmp::Reverse;
+
+/// Counters collected during a solver run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Stats {
+    /// Variables assigned by branching.
+    pub decisions: u64,
+    /// Variables assigned because a clause forced them.
+    pub propagations: u64,
+    /// Clauses found false during the search.
+    pub conflicts: u64,
+    /// Times the search started over from the top.
+    pub restarts: u64,
+    /// The most decisions in effect at once.
+    pub max_depth: usize,
+    /// Learned clauses currently kept in the clause database.
+    pub learned_clauses: usize,
+    /// Learned clauses forgotten to make room for new ones.
+    pub deleted_clauses: u64,
+    /// Literals in all clauses learned so far, forgotten ones included.
+    pub learned_literals: u64,
+}
+
+impl Stats {
+    /// The average number of literals per learned clause, 0 if nothing was
+    /// learned.
+    pub fn average_learned_len(&self) -> f64 {
+        let learned = self.learned_clauses as u64 + self.deleted_clauses;
+        if learned == 0 {
+            return 0.0;
+        }
+        self.learned_literals as f64 / learned as f64
+    }
+}
+
+/// The size and shape of a formula, see `formula_stats`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FormulaStats {
+    /// Distinct variables.
+    pub num_vars: usize,
+    pub num_clauses: usize,
+    /// How many clauses have each length, indexed by the length.
+    pub clause_lengths: Vec<usize>,
+    /// Literal occurrences per variable, 0 without variables.
+    pub literals_per_var: f64,
+}
+
+impl FormulaStats {
+    /// Clauses per variable. Random 3-SAT formulas are hardest around 4.26.
+    pub fn clause_ratio(&self) -> f64 {
+        if self.num_vars == 0 {
+            return 0.0;
+        }
+        self.num_clauses as f64 / self.num_vars as f64
+    }
+}
+
+/// Counts the variables, clauses and literals of `cnf`, without solving.
+pub fn formula_stats(cnf: &CNF) -> FormulaStats {
+    let num_vars = cnf
+        .iter()
+        .flatten()
+        .map(|literal| literal.name.as_str())
+        .collect::<Set<_>>()
+        .len();
+    let longest = cnf.iter().map(Vec::len).max();
+    let mut clause_lengths = vec![0; longest.map_or(0, |longest| longest + 1)];
+    for clause in cnf {
+        clause_lengths[clause.len()] += 1;
+    }
+    let literals: usize = cnf.iter().map(Vec::len).sum();
+    FormulaStats {
+        num_vars,
+        num_clauses: cnf.len(),
+        clause_lengths,
+        literals_per_var: if num_vars == 0 {
+            0.0
+        } else {
+            literals as f64 / num_vars as f64
+        },
+    }
+}
+
+/// How a formula's variables hang together, see `structure_hint`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StructureHint {
+    /// Groups of variables sharing no clause with each other, which can be
+    /// solved separately.
+    pub components: usize,
+    /// Variables in the largest component.
+    pub largest_component: usize,
+    /// The degeneracy of the primal graph, which links variables sharing a
+    /// clause: the largest number of neighbours left when removing
+    /// variables fewest neighbours first. It bounds the treewidth from
+    /// below, and a small one suggests BDDs or component solving will do.
+    pub degeneracy: usize,
+}
+
+/// Measures the structure of `cnf` on its primal graph in about linear
+/// time in the size of the graph, far cheaper than its treewidth. A clause
+/// with `n` variables adds `n * (n - 1) / 2` links, so long clauses make
+/// it slower.
+pub fn structure_hint(cnf: &CNF) -> StructureHint {
+    let vars = VarMap::from_cnf(cnf);
+    let mut neighbours: Vec<Vec<usize>> = vec![Vec::new(); vars.len()];
+    for clause in cnf {
+        let mut ids: Vec<usize> = clause
+            .iter()
+            .map(|literal| vars.id(&literal.name).expect("interned from cnf") as usize)
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        for (i, &a) in ids.iter().enumerate() {
+            for &b in &ids[i + 1..] {
+                neighbours[a].push(b);
+                neighbours[b].push(a);
+            }
+        }
+    }
+    for targets in &mut neighbours {
+        targets.sort_unstable();
+        targets.dedup();
+    }
+
+    // links go both ways, so strongly connected is connected
+    let component = components(&neighbours);
+    let mut sizes = vec![0; component.iter().max().map_or(0, |&max| max + 1)];
+    for &id in &component {
+        sizes[id] += 1;
+    }
+
+    let mut degrees: Vec<usize> = neighbours.iter().map(Vec::len).collect();
+    let mut removed = vec![false; vars.len()];
+    let mut heap: BinaryHeap<_> = (0..vars.len())
+        .map(|var| Reverse((degrees[var], var)))
+        .collect();
+    let mut degeneracy = 0;
+    while let Some(Reverse((degree, var))) = heap.pop() {
+        // entries from before a neighbour was removed are stale
+        if removed[var] || degree != degrees[var] {
+            continue;
+        }
+        removed[var] = true;
+        degeneracy = degeneracy.max(degree);
+        for &other in &neighbours[var] {
+            if !removed[other] {
+                degrees[other] -= 1;
+                heap.push(Reverse((degrees[other], other)));
+            }
+        }
+    }
+
+    StructureHint {
+        components: sizes.len(),
+        largest_component: sizes.iter().copied().max().unwrap_or(0),
+        degeneracy,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse2;
+
+    #[test]
+    fn test_formula_stats() {
+        // formula 7 of the classic DPLL tests
+        let stats = formula_stats(&parse2("{a,b},{b,c},{-a,-b},{-c,d}"));
+        assert_eq!(
+            stats,
+            FormulaStats {
+                num_vars: 4,
+                num_clauses: 4,
+                clause_lengths: vec![0, 0, 4],
+                literals_per_var: 2.0,
+            }
+        );
+        assert_eq!(stats.clause_ratio(), 1.0);
+
+        let stats = formula_stats(&parse2("{a},{a,-b,c},{-c}"));
+        assert_eq!(stats.clause_lengths, [0, 2, 0, 1]);
+        assert_eq!(stats.literals_per_var, 5.0 / 3.0);
+
+        let stats = formula_stats(&CNF::new());
+        assert!(stats.clause_lengths.is_empty());
+        assert_eq!(stats.clause_ratio(), 0.0);
+    }
+
+    #[test]
+    fn test_structure_hint() {
+        let hint = structure_hint(&parse2("{a,b},{-b,c},{x,-y},{y,z,-x}"));
+        assert_eq!(
+            hint,
+            StructureHint {
+                components: 2,
+                largest_component: 3,
+                degeneracy: 2,
+            }
+        );
+
+        // a chain peels off one end at a time
+        let chain = parse2("{a,b},{b,c},{c,d},{d,e}");
+        assert_eq!(structure_hint(&chain).degeneracy, 1);
+        // the variables of one clause are all linked
+        let clique = parse2("{a,b,c,d}");
+        assert_eq!(structure_hint(&clique).degeneracy, 3);
+        assert_eq!(structure_hint(&parse2("{a},{b},{-a}")).components, 2);
+        assert_eq!(structure_hint(&CNF::new()).components, 0);
+    }
+}
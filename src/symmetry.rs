@@ -0,0 +1,114 @@
+use crate::cardinality::next_aux;
+use crate::{CNF, Literal};
+use alloc::{format, string::String, vec, vec::Vec};
+
+/// Appends lex-leader clauses for the symmetry `perm`, given as pairs of a
+/// variable and its image. The clauses only allow assignments that are,
+/// read in the order of `perm`, lexicographically at most their image with
+/// false before true. If `perm` maps models of `cnf` to models, every
+/// class of models related by it keeps at least one member, so
+/// satisfiability is unchanged while much of the search space is cut.
+///
+/// Auxiliary variables are named `@` followed by a number, after any that
+/// are already in `cnf`.
+pub fn add_symmetry_breaking(cnf: &mut CNF, perm: &[(String, String)]) {
+    // fixed points compare equal to themselves
+    let pairs: Vec<&(String, String)> = perm.iter().filter(|(x, y)| x != y).collect();
+
+    // `equal(i)` is true exactly when the first `i` pairs are equal, and
+    // then pair `i` must not go from true to false. defining it both ways
+    // keeps the models in one to one correspondence with those of the
+    // original variables
+    let first = next_aux(cnf);
+    let equal = |i: usize, negated| Literal {
+        name: format!("@{}", first + i - 1),
+        negated,
+    };
+    let lit = |name: &String, negated| Literal {
+        name: name.clone(),
+        negated,
+    };
+
+    for (i, (x, y)) in pairs.iter().enumerate() {
+        let mut smaller = vec![lit(x, true), lit(y, false)];
+        if i > 0 {
+            smaller.push(equal(i, true));
+        }
+        cnf.push(smaller);
+
+        if i + 1 < pairs.len() {
+            for value in [true, false] {
+                let mut clause = vec![lit(x, value), lit(y, value), equal(i + 1, false)];
+                if i > 0 {
+                    clause.push(equal(i, true));
+                }
+                cnf.push(clause);
+                cnf.push(vec![equal(i + 1, true), lit(x, value), lit(y, !value)]);
+            }
+            if i > 0 {
+                cnf.push(vec![equal(i + 1, true), equal(i, false)]);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, all_models, model_count, parse2, solve, verify};
+
+    fn perm(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(x, y)| (x.to_string(), y.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_symmetry_breaking_removes_models() {
+        // swapping `a` and `b` maps models to models, only a <= b is kept
+        let mut cnf = parse2("{a,b}");
+        add_symmetry_breaking(&mut cnf, &perm(&[("a", "b"), ("b", "a")]));
+        assert_eq!(model_count(cnf.clone()), 2);
+        for model in all_models(cnf) {
+            assert!(!model["a"] || model["b"]);
+        }
+
+        // rotating three variables, exactly one of which is true. only `a`
+        // is larger than its image
+        let original = parse2("{a,b,c},{-a,-b},{-a,-c},{-b,-c}");
+        let mut cnf = original.clone();
+        add_symmetry_breaking(&mut cnf, &perm(&[("a", "b"), ("b", "c"), ("c", "a")]));
+        let models: Vec<_> = all_models(cnf).collect();
+        assert_eq!(models.len(), 2);
+        for model in &models {
+            assert!(verify(&original, model));
+            assert!(!model["a"]);
+        }
+    }
+
+    #[test]
+    fn test_symmetry_breaking_keeps_satisfiability() {
+        // pigeons 1 and 2 are interchangeable
+        let mut cnf = parse2("{p1h1,p1h2},{p2h1,p2h2},{-p1h1,-p2h1},{-p1h2,-p2h2}");
+        let swap = [
+            ("p1h1", "p2h1"),
+            ("p1h2", "p2h2"),
+            ("p2h1", "p1h1"),
+            ("p2h2", "p1h2"),
+        ];
+        add_symmetry_breaking(&mut cnf, &perm(&swap));
+        assert!(matches!(solve(cnf.clone()), SatResult::Sat(_)));
+        assert_eq!(
+            all_models(cnf)
+                .filter(|model| model["p1h1"] && model["p2h2"])
+                .count(),
+            0
+        );
+
+        // fixed points add nothing
+        let mut cnf = parse2("{a}");
+        add_symmetry_breaking(&mut cnf, &perm(&[("a", "a")]));
+        assert_eq!(cnf, parse2("{a}"));
+    }
+}
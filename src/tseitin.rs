@@ -0,0 +1,277 @@
+use crate::{CNF, Clause, Literal, Set};
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+
+/// A boolean formula over named variables. An empty `And` is true and an
+/// empty `Or` is false.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoolExpr {
+    Var(String),
+    Not(Box<BoolExpr>),
+    And(Vec<BoolExpr>),
+    Or(Vec<BoolExpr>),
+    Implies(Box<BoolExpr>, Box<BoolExpr>),
+    Iff(Box<BoolExpr>, Box<BoolExpr>),
+}
+
+/// Converts `expr` into a CNF that is satisfiable exactly when `expr` is,
+/// and whose models agree with `expr` on its variables. Every compound
+/// subformula gets an auxiliary variable named `@` followed by a number,
+/// so the formula itself should not use names starting with `@`.
+pub fn tseitin(expr: &BoolExpr) -> CNF {
+    let mut encoder = Encoder {
+        cnf: CNF::new(),
+        next: 0,
+    };
+    let root = encoder.encode(expr);
+    encoder.cnf.push(vec![root]);
+    encoder.cnf
+}
+
+struct Encoder {
+    cnf: CNF,
+    // number of the next auxiliary variable
+    next: usize,
+}
+
+impl Encoder {
+    // returns a literal that is true exactly when `expr` is
+    fn encode(&mut self, expr: &BoolExpr) -> Literal {
+        match expr {
+            BoolExpr::Var(name) => Literal {
+                name: name.clone(),
+                negated: false,
+            },
+            BoolExpr::Not(inner) => negate(self.encode(inner)),
+            BoolExpr::And(operands) => {
+                let lits: Vec<Literal> = operands.iter().map(|op| self.encode(op)).collect();
+                self.and(lits)
+            }
+            BoolExpr::Or(operands) => {
+                // a | b is !(!a & !b)
+                let lits = operands.iter().map(|op| negate(self.encode(op))).collect();
+                negate(self.and(lits))
+            }
+            BoolExpr::Implies(a, b) => {
+                let a = self.encode(a);
+                let b = negate(self.encode(b));
+                negate(self.and(vec![a, b]))
+            }
+            BoolExpr::Iff(a, b) => {
+                let (a, b) = (self.encode(a), self.encode(b));
+                let x = self.aux();
+                self.cnf
+                    .push(vec![negate(x.clone()), negate(a.clone()), b.clone()]);
+                self.cnf
+                    .push(vec![negate(x.clone()), a.clone(), negate(b.clone())]);
+                self.cnf.push(vec![x.clone(), a.clone(), b.clone()]);
+                self.cnf.push(vec![x.clone(), negate(a), negate(b)]);
+                x
+            }
+        }
+    }
+
+    // x <-> (l1 & ... & ln)
+    fn and(&mut self, lits: Vec<Literal>) -> Literal {
+        let x = self.aux();
+        let mut all: Clause = vec![x.clone()];
+        for lit in lits {
+            self.cnf.push(vec![negate(x.clone()), lit.clone()]);
+            all.push(negate(lit));
+        }
+        self.cnf.push(all);
+        x
+    }
+
+    fn aux(&mut self) -> Literal {
+        let name = format!("@{}", self.next);
+        self.next += 1;
+        Literal {
+            name,
+            negated: false,
+        }
+    }
+}
+
+/// Finds variables that `cnf` defines as an AND or OR of other literals,
+/// the way `tseitin` encodes its gates: `z <-> (a & b)` as `{z,-a,-b}`,
+/// `{-z,a}` and `{-z,b}`, and `z <-> (a | b)` as `{-z,a,b}`, `{z,-a}` and
+/// `{z,-b}`. Each variable is reported once, with the first definition in
+/// clause order, as `And` or `Or` over `Var` and negated `Var` operands in
+/// the order of the long clause. Gates need at least two inputs and no
+/// input on the defined variable itself, but definitions may refer to
+/// each other.
+pub fn detect_definitions(cnf: &CNF) -> Vec<(String, BoolExpr)> {
+    let binaries: Set<(&Literal, &Literal)> = cnf
+        .iter()
+        .filter_map(|clause| match &clause[..] {
+            [a, b] => Some((a.min(b), a.max(b))),
+            _ => None,
+        })
+        .collect();
+    let has_binary = |a: &Literal, b: &Literal| binaries.contains(&(a.min(b), a.max(b)));
+    let operand = |lit: &Literal| {
+        let var = BoolExpr::Var(lit.name.clone());
+        if lit.negated {
+            BoolExpr::Not(Box::new(var))
+        } else {
+            var
+        }
+    };
+
+    let mut defined = Set::new();
+    let mut definitions = Vec::new();
+    for clause in cnf.iter().filter(|clause| clause.len() >= 3) {
+        for output in clause {
+            if defined.contains(&output.name) {
+                continue;
+            }
+            let inputs: Vec<&Literal> = clause.iter().filter(|&lit| lit != output).collect();
+            // the long clause and one binary per input, each with the
+            // output negated
+            let gate = inputs.len() == clause.len() - 1
+                && inputs.iter().all(|&input| {
+                    input.name != output.name
+                        && has_binary(&negate(output.clone()), &negate(input.clone()))
+                });
+            if !gate {
+                continue;
+            }
+            // `{z,-a,-b}` gives `z <-> (a & b)`, `{-z,a,b}` gives `z <-> (a | b)`
+            let expr = if output.negated {
+                BoolExpr::Or(inputs.into_iter().map(operand).collect())
+            } else {
+                let negated = inputs.into_iter().map(|input| negate(input.clone()));
+                BoolExpr::And(negated.map(|input| operand(&input)).collect())
+            };
+            defined.insert(&output.name);
+            definitions.push((output.name.clone(), expr));
+        }
+    }
+    definitions
+}
+
+fn negate(lit: Literal) -> Literal {
+    Literal {
+        negated: !lit.negated,
+        ..lit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Assignment, SatResult, solve};
+
+    fn var(name: &str) -> BoolExpr {
+        BoolExpr::Var(name.to_string())
+    }
+
+    fn eval(expr: &BoolExpr, model: &Assignment) -> bool {
+        match expr {
+            BoolExpr::Var(name) => model[name],
+            BoolExpr::Not(inner) => !eval(inner, model),
+            BoolExpr::And(operands) => operands.iter().all(|op| eval(op, model)),
+            BoolExpr::Or(operands) => operands.iter().any(|op| eval(op, model)),
+            BoolExpr::Implies(a, b) => !eval(a, model) || eval(b, model),
+            BoolExpr::Iff(a, b) => eval(a, model) == eval(b, model),
+        }
+    }
+
+    // the CNF allows an assignment to `names` exactly when `expr` holds
+    fn check(expr: &BoolExpr, names: &[&str]) {
+        let cnf = tseitin(expr);
+        for bits in 0..1u32 << names.len() {
+            let model: Assignment = names
+                .iter()
+                .enumerate()
+                .map(|(i, name)| (name.to_string(), bits >> i & 1 == 1))
+                .collect();
+            let mut fixed = cnf.clone();
+            fixed.extend(model.iter().map(|(name, &value)| {
+                vec![Literal {
+                    name: name.clone(),
+                    negated: !value,
+                }]
+            }));
+            let sat = matches!(solve(fixed), SatResult::Sat(_));
+            assert_eq!(sat, eval(expr, &model), "{expr:?} under {model:?}");
+        }
+    }
+
+    #[test]
+    fn test_connectives() {
+        let (a, b, c) = (var("a"), var("b"), var("c"));
+        let not = |e: &BoolExpr| BoolExpr::Not(Box::new(e.clone()));
+        let implies = |x: &BoolExpr, y: &BoolExpr| {
+            BoolExpr::Implies(Box::new(x.clone()), Box::new(y.clone()))
+        };
+        let iff =
+            |x: &BoolExpr, y: &BoolExpr| BoolExpr::Iff(Box::new(x.clone()), Box::new(y.clone()));
+
+        let exprs = [
+            a.clone(),
+            not(&a),
+            BoolExpr::And(vec![a.clone(), not(&b), c.clone()]),
+            BoolExpr::Or(vec![not(&a), b.clone(), c.clone()]),
+            implies(&a, &b),
+            iff(&a, &not(&b)),
+            iff(&iff(&a, &b), &c),
+            implies(
+                &BoolExpr::Or(vec![a.clone(), b.clone()]),
+                &BoolExpr::And(vec![c.clone(), not(&a)]),
+            ),
+            not(&BoolExpr::And(vec![
+                implies(&a, &b),
+                implies(&b, &c),
+                a.clone(),
+                not(&c),
+            ])),
+            BoolExpr::And(vec![]),
+            BoolExpr::Or(vec![]),
+        ];
+        for expr in &exprs {
+            check(expr, &["a", "b", "c"]);
+        }
+    }
+
+    #[test]
+    fn test_auxiliary_names() {
+        let expr = BoolExpr::Or(vec![
+            BoolExpr::And(vec![var("a"), var("b")]),
+            BoolExpr::Not(Box::new(var("a"))),
+        ]);
+        let cnf = tseitin(&expr);
+        let mut names: Vec<&str> = cnf.iter().flatten().map(|lit| lit.name.as_str()).collect();
+        names.sort();
+        names.dedup();
+        assert_eq!(names, ["@0", "@1", "a", "b"]);
+    }
+
+    #[test]
+    fn test_detect_definitions() {
+        let and = crate::parse2("{z,-a,-b},{-z,a},{-z,b}");
+        assert_eq!(
+            detect_definitions(&and),
+            [("z".to_string(), BoolExpr::And(vec![var("a"), var("b")]))]
+        );
+        let or = crate::parse2("{-z,a,-b},{z,-a},{b,z}");
+        let not_b = BoolExpr::Not(Box::new(var("b")));
+        assert_eq!(
+            detect_definitions(&or),
+            [("z".to_string(), BoolExpr::Or(vec![var("a"), not_b]))]
+        );
+        // a binary clause missing
+        assert!(detect_definitions(&crate::parse2("{z,-a,-b},{-z,a}")).is_empty());
+        assert!(detect_definitions(&crate::parse2("{-z,a},{z,-a}")).is_empty());
+
+        // the gates `tseitin` introduces, the root only defined through them
+        let expr = BoolExpr::Or(vec![
+            BoolExpr::And(vec![var("a"), var("b")]),
+            BoolExpr::Not(Box::new(var("c"))),
+        ]);
+        let definitions = detect_definitions(&tseitin(&expr));
+        let names: Vec<&str> = definitions.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["@0", "@1"]);
+        assert_eq!(definitions[0].1, BoolExpr::And(vec![var("a"), var("b")]));
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{CNF, Map};
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Interns variable names as dense `u32` ids, in order of first occurrence.
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VarMap {
+    names: Vec<String>,
+    ids: Map<String, u32>,
+}
+
+impl VarMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interns every variable of `cnf` in order of first occurrence.
+    pub fn from_cnf(cnf: &CNF) -> Self {
+        let mut vars = VarMap::new();
+        for literal in cnf.iter().flatten() {
+            vars.intern(&literal.name);
+        }
+        vars
+    }
+
+    /// Returns the id of `name`, assigning the next free id if it is new.
+    pub fn intern(&mut self, name: &str) -> u32 {
+        if let Some(&id) = self.ids.get(name) {
+            return id;
+        }
+        let id = self.names.len() as u32;
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        id
+    }
+
+    pub fn id(&self, name: &str) -> Option<u32> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: u32) -> Option<&str> {
+        self.names.get(id as usize).map(String::as_str)
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Names in id order.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.names.iter().map(String::as_str)
+    }
+}
+
+/// A literal over an interned variable, the solver's internal counterpart
+/// of `Literal`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct Lit {
+    pub(crate) var: u32,
+    pub(crate) negated: bool,
+}
+
+impl Lit {
+    pub(crate) fn new(var: u32, negated: bool) -> Self {
+        Lit { var, negated }
+    }
+
+    // position in per-literal tables, the two polarities of a variable are
+    // adjacent
+    pub(crate) fn index(self) -> usize {
+        self.var as usize * 2 + self.negated as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_varmap_round_trip() {
+        let mut vars = VarMap::new();
+        assert_eq!(vars.intern("a"), 0);
+        assert_eq!(vars.intern("b"), 1);
+        assert_eq!(vars.intern("a"), 0);
+
+        assert_eq!(vars.len(), 2);
+        assert_eq!(vars.id("b"), Some(1));
+        assert_eq!(vars.name(1), Some("b"));
+        assert_eq!(vars.id("c"), None);
+        assert_eq!(vars.name(2), None);
+    }
+}
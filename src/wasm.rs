@@ -0,0 +1,73 @@
+//! An entry point for JavaScript, built with
+//!
+//! ```text
+//! cargo rustc --lib --release --target wasm32-unknown-unknown \
+//!     --features wasm --crate-type cdylib
+//! wasm-bindgen --target web target/wasm32-unknown-unknown/release/sat_solver.wasm
+//! ```
+
+use crate::{Assignment, SatResult, parse_formula, solve};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+/// Parses a DIMACS or brace syntax formula, solves it and returns the
+/// verdict as JSON: `{"sat":true,"model":{"a":true}}`, `{"sat":false}`,
+/// or `{"error":"..."}` when the input does not parse.
+#[wasm_bindgen]
+pub fn solve_json(input: &str) -> String {
+    let cnf = match parse_formula(input) {
+        Ok(cnf) => cnf,
+        Err(err) => return format!(r#"{{"error":{}}}"#, json_string(&err.to_string())),
+    };
+    match solve(cnf) {
+        SatResult::Sat(model) => format!(r#"{{"sat":true,"model":{}}}"#, json_model(&model)),
+        SatResult::Unsat => r#"{"sat":false}"#.to_string(),
+    }
+}
+
+// an object with the variables in name order
+fn json_model(model: &Assignment) -> String {
+    let mut entries: Vec<(&String, &bool)> = model.iter().collect();
+    entries.sort();
+    let fields: Vec<String> = entries
+        .into_iter()
+        .map(|(name, value)| format!("{}:{value}", json_string(name)))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn json_string(text: &str) -> String {
+    let mut out = String::with_capacity(text.len() + 2);
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            c if c.is_control() => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_solve_json() {
+        assert_eq!(
+            solve_json("{a,b},{-a}"),
+            r#"{"sat":true,"model":{"a":false,"b":true}}"#
+        );
+        assert_eq!(solve_json("p cnf 1 2\n1 0\n-1 0\n"), r#"{"sat":false}"#);
+        assert_eq!(solve_json("{a,,b}"), r#"{"error":"3..3: empty literal"}"#);
+        assert_eq!(json_string("x\"\\\u{1}"), r#""x\"\\\u0001""#);
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{CNF, Map, Set};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+// an XOR over more variables takes too many clauses to be worth looking for
+const MAX_EXTRACTED_VARS: usize = 10;
+
+/// A parity constraint: an odd number of `vars` are true if `parity` is
+/// true, an even number if it is false. A variable listed twice cancels
+/// out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct XorClause {
+    pub vars: Vec<String>,
+    pub parity: bool,
+}
+
+/// Finds groups of clauses that together state a parity constraint and
+/// replaces them with it. An XOR over `k` variables is the `2^(k-1)`
+/// clauses over exactly those variables whose number of negated literals
+/// has the same parity, each forbidding one assignment of the wrong parity.
+/// Returns the clauses left over, in input order, and the XORs found, over
+/// 2 to 10 variables.
+pub fn extract_xors(cnf: CNF) -> (CNF, Vec<XorClause>) {
+    // clauses by their sorted variables and whether an odd number of their
+    // literals is negated, in order of first appearance
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_of: Map<(Vec<&str>, bool), usize> = Map::new();
+    for (index, clause) in cnf.iter().enumerate() {
+        let mut names: Vec<&str> = clause.iter().map(|literal| literal.name.as_str()).collect();
+        names.sort_unstable();
+        names.dedup();
+        if names.len() != clause.len() || !(2..=MAX_EXTRACTED_VARS).contains(&names.len()) {
+            continue;
+        }
+        let odd = clause.iter().filter(|literal| literal.negated).count() % 2 == 1;
+        let group = *group_of.entry((names, odd)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(index);
+    }
+
+    let mut extracted = vec![false; cnf.len()];
+    let mut xors = Vec::new();
+    for group in groups {
+        let first = &cnf[group[0]];
+        // each clause forbids the assignment making all its literals false
+        let forbidden: Set<Vec<bool>> = group
+            .iter()
+            .map(|&index| {
+                let mut signs: Vec<(&str, bool)> = cnf[index]
+                    .iter()
+                    .map(|literal| (literal.name.as_str(), literal.negated))
+                    .collect();
+                signs.sort_unstable();
+                signs.into_iter().map(|(_, negated)| negated).collect()
+            })
+            .collect();
+        if forbidden.len() != 1 << (first.len() - 1) {
+            continue;
+        }
+
+        for &index in &group {
+            extracted[index] = true;
+        }
+        // the forbidden assignments have an odd number of true variables
+        // exactly when the clauses have an odd number of negations
+        let odd = first.iter().filter(|literal| literal.negated).count() % 2 == 1;
+        xors.push(XorClause {
+            vars: first
+                .iter()
+                .map(|literal| literal.name.to_string())
+                .collect(),
+            parity: !odd,
+        });
+    }
+
+    let rest = cnf
+        .into_iter()
+        .zip(extracted)
+        .filter_map(|(clause, extracted)| (!extracted).then_some(clause))
+        .collect();
+    (rest, xors)
+}
+
+// what the parity constraints say about the current assignment
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Deduction {
+    // the variables of a combination of constraints that are all assigned
+    // with the wrong parity
+    Conflict(Vec<u32>),
+    // the only unassigned variable of a combination of constraints, the
+    // value it must take and the assigned variables that decide it
+    Implied(u32, bool, Vec<u32>),
+}
+
+// the constraints as rows of a matrix over GF(2), with a bit per variable
+// that occurs in any of them
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub(crate) struct XorSystem {
+    // variable of each column
+    columns: Vec<u32>,
+    rows: Vec<(Vec<u64>, bool)>,
+}
+
+impl XorSystem {
+    // every column a variable below `num_vars`, and a bit per column in
+    // every row
+    #[cfg(feature = "serde")]
+    pub(crate) fn is_consistent(&self, num_vars: usize) -> bool {
+        let words = self.columns.len().div_ceil(64);
+        self.columns.iter().all(|&var| (var as usize) < num_vars)
+            && self.rows.iter().all(|(bits, _)| bits.len() == words)
+    }
+
+    // `intern` maps a name to its variable id
+    pub(crate) fn new(xors: &[XorClause], mut intern: impl FnMut(&str) -> u32) -> Self {
+        let interned: Vec<Vec<u32>> = xors
+            .iter()
+            .map(|xor| xor.vars.iter().map(|name| intern(name)).collect())
+            .collect();
+
+        let mut columns = Vec::new();
+        let mut column_of = Vec::new();
+        for &var in interned.iter().flatten() {
+            let var = var as usize;
+            if column_of.len() <= var {
+                column_of.resize(var + 1, None);
+            }
+            if column_of[var].is_none() {
+                column_of[var] = Some(columns.len());
+                columns.push(var as u32);
+            }
+        }
+
+        let words = columns.len().div_ceil(64);
+        let rows = interned
+            .iter()
+            .zip(xors)
+            .map(|(vars, xor)| {
+                let mut bits = vec![0; words];
+                for &var in vars {
+                    let column = column_of[var as usize].unwrap();
+                    bits[column / 64] ^= 1 << (column % 64);
+                }
+                (bits, xor.parity)
+            })
+            .collect();
+
+        XorSystem { columns, rows }
+    }
+
+    // Gauss-Jordan elimination on a copy of the rows, pivoting only on
+    // unassigned variables. afterwards every combination of the rows that
+    // leaves one or no unassigned variable is a row by itself, so reading
+    // the rows off finds everything the constraints imply.
+    pub(crate) fn deduce(&self, values: &[Option<bool>]) -> Vec<Deduction> {
+        let mut rows = self.rows.clone();
+        let mut pivots = 0;
+        for (column, &var) in self.columns.iter().enumerate() {
+            if values[var as usize].is_some() {
+                continue;
+            }
+            let (word, bit) = (column / 64, 1 << (column % 64));
+            let Some(pivot) = (pivots..rows.len()).find(|&row| rows[row].0[word] & bit != 0) else {
+                continue;
+            };
+            rows.swap(pivots, pivot);
+            let (bits, parity) = rows[pivots].clone();
+            for (row, other) in rows.iter_mut().enumerate() {
+                if row != pivots && other.0[word] & bit != 0 {
+                    for (a, b) in other.0.iter_mut().zip(&bits) {
+                        *a ^= b;
+                    }
+                    other.1 ^= parity;
+                }
+            }
+            pivots += 1;
+        }
+
+        let mut deductions = Vec::new();
+        for (bits, parity) in &rows {
+            let mut assigned = Vec::new();
+            let mut unassigned = Vec::new();
+            // what the unassigned variables have to add up to
+            let mut rest = *parity;
+            for (column, &var) in self.columns.iter().enumerate() {
+                if bits[column / 64] & (1 << (column % 64)) == 0 {
+                    continue;
+                }
+                match values[var as usize] {
+                    Some(value) => {
+                        rest ^= value;
+                        assigned.push(var);
+                    }
+                    None => unassigned.push(var),
+                }
+            }
+
+            match unassigned[..] {
+                [] if rest => deductions.push(Deduction::Conflict(assigned)),
+                [var] => deductions.push(Deduction::Implied(var, rest, assigned)),
+                _ => {}
+            }
+        }
+        deductions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{SatResult, VarMap, parse2, solve_with_xor, verify};
+
+    fn xor(vars: &[&str], parity: bool) -> XorClause {
+        XorClause {
+            vars: vars.iter().map(|name| name.to_string()).collect(),
+            parity,
+        }
+    }
+
+    #[test]
+    fn test_deduce_combines_rows() {
+        let mut vars = VarMap::new();
+        let xors = [xor(&["a", "b", "c"], true), xor(&["b", "c"], false)];
+        let system = XorSystem::new(&xors, |name| vars.intern(name));
+
+        // neither row alone fixes `a`, their sum does
+        let values = vec![None; 3];
+        assert_eq!(
+            system.deduce(&values),
+            vec![Deduction::Implied(0, true, vec![])]
+        );
+
+        let values = vec![Some(true), Some(true), Some(false)];
+        let deductions = system.deduce(&values);
+        assert_eq!(deductions.len(), 2);
+        assert!(
+            deductions
+                .iter()
+                .all(|deduction| matches!(deduction, Deduction::Conflict(_)))
+        );
+    }
+
+    #[test]
+    fn test_extract_xors() {
+        // a xor b xor c, one clause per even assignment
+        let cnf = parse2("{a,b,c},{-a,-b,c},{-a,b,-c},{a,-b,-c},{d,a}");
+        let (rest, xors) = extract_xors(cnf);
+        assert_eq!(rest, parse2("{d,a}"));
+        assert_eq!(xors, [xor(&["a", "b", "c"], true)]);
+
+        // a xor b, and its negation
+        let (rest, xors) = extract_xors(parse2("{a,b},{-b,-a}"));
+        assert!(rest.is_empty());
+        assert_eq!(xors, [xor(&["a", "b"], true)]);
+        let (_, xors) = extract_xors(parse2("{-a,b},{a,-b}"));
+        assert_eq!(xors, [xor(&["a", "b"], false)]);
+
+        // three of the four clauses are not enough
+        let cnf = parse2("{a,b,c},{-a,-b,c},{-a,b,-c}");
+        assert_eq!(extract_xors(cnf.clone()), (cnf, vec![]));
+    }
+
+    #[test]
+    fn test_extracted_xors_keep_models() {
+        let cnf = parse2("{a,b,c},{-a,-b,c},{-a,b,-c},{a,-b,-c},{-a},{-b}");
+        let (rest, xors) = extract_xors(cnf.clone());
+        let SatResult::Sat(model) = solve_with_xor(rest, xors) else {
+            panic!("expected a model");
+        };
+        assert!(verify(&cnf, &model));
+        assert!(model["c"]);
+    }
+
+    #[test]
+    fn test_repeated_variable_cancels() {
+        let mut vars = VarMap::new();
+        let system = XorSystem::new(&[xor(&["a", "a", "b"], true)], |name| vars.intern(name));
+        assert_eq!(
+            system.deduce(&[None, None]),
+            vec![Deduction::Implied(1, true, vec![])]
+        );
+    }
+}